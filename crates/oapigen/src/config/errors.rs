@@ -0,0 +1,19 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("could not read the config file {}", .0.display())]
+    ReadConfigError(PathBuf, #[source] io::Error),
+    #[error("the config file {} is not valid TOML", .0.display())]
+    DeserializeTomlError(PathBuf, #[source] toml::de::Error),
+    #[error("the config file {} is not valid YAML", .0.display())]
+    DeserializeYamlError(PathBuf, #[source] serde_yaml::Error),
+    #[error("the config file {} has invalid options", .0.display())]
+    InvalidConfig(PathBuf, #[source] serde_json::Error),
+    #[error("the environment variable {0} does not match an option of the config")]
+    InvalidEnvironmentVariable(String),
+    #[error("the options set by the environment variables {0} are not valid")]
+    InvalidEnvironment(String, #[source] serde_json::Error),
+}
@@ -0,0 +1,251 @@
+use crate::config::errors::ConfigError;
+use crate::models::Config;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// The prefix of the environment variables that override the options of the config.
+const ENV_PREFIX: &str = "OAPIGEN_";
+/// The separator of the nested keys in the name of an environment variable.
+const ENV_SEPARATOR: &str = "__";
+
+/// Loads the config from a TOML file, or from a YAML one if its extension is `yaml` or `yml`,
+/// and overrides its options with the environment variables.
+/// Without a file the options come from the defaults and the environment variables only.
+///
+/// The missing options fall back to their defaults, while the unknown ones are an error.
+/// The environment variables are named after the path of the option, in upper case
+/// with the `OAPIGEN_` prefix and `__` between the nested keys, like `OAPIGEN_LIBRARIES__DATETIME=jiff`.
+/// Their values are read as TOML values, or as strings if they are not valid TOML,
+/// so that `OAPIGEN_LIBRARIES__TRANSPORTS='["reqwest"]'` sets a list.
+/// The options that are strings keep the value as it is, so `OAPIGEN_PACKAGE__VERSION=1.0` is not a float.
+pub fn load_config(path: Option<&Path>) -> Result<Config, ConfigError> {
+    load_config_with_env(path, std::env::vars())
+}
+
+fn load_config_with_env(
+    path: Option<&Path>,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Config, ConfigError> {
+    let mut options = match path {
+        Some(path) => {
+            let options = read_config(path)?;
+            // the file is checked on its own, so that its errors are not blamed on the environment
+            Config::deserialize(&options)
+                .map_err(|e| ConfigError::InvalidConfig(path.to_path_buf(), e))?;
+            options
+        }
+        None => Value::Object(Map::new()),
+    };
+
+    let mut overrides = vec![];
+    for (name, value) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        set_env_option(&mut options, key, &value)
+            .ok_or_else(|| ConfigError::InvalidEnvironmentVariable(name.clone()))?;
+        overrides.push(name);
+    }
+    overrides.sort();
+
+    Config::deserialize(options)
+        .map_err(|e| ConfigError::InvalidEnvironment(overrides.join(", "), e))
+}
+
+/// Reads the options of a config file, keeping them untyped so that they can be overridden.
+fn read_config(path: &Path) -> Result<Value, ConfigError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::ReadConfigError(path.to_path_buf(), e))?;
+
+    let is_yaml = path
+        .extension()
+        .is_some_and(|extension| extension == "yaml" || extension == "yml");
+    let options = match is_yaml {
+        true => serde_yaml::from_str(&content)
+            .map_err(|e| ConfigError::DeserializeYamlError(path.to_path_buf(), e))?,
+        false => toml::from_str(&content)
+            .map_err(|e| ConfigError::DeserializeTomlError(path.to_path_buf(), e))?,
+    };
+
+    // an empty YAML file is null rather than an empty mapping
+    match options {
+        Value::Null => Ok(Value::Object(Map::new())),
+        options => Ok(options),
+    }
+}
+
+/// Sets the option at the path of `key` to the value of an environment variable, read as a TOML value
+/// unless the option only accepts it as a string, like the `1.0` of a version that TOML reads as a float.
+fn set_env_option(options: &mut Value, key: &str, value: &str) -> Option<()> {
+    set_option(options, key, env_to_value(value))?;
+    if Config::deserialize(&*options).is_ok() {
+        return Some(());
+    }
+
+    let mut as_string = options.clone();
+    set_option(&mut as_string, key, Value::String(value.to_string()))?;
+    if Config::deserialize(&as_string).is_ok() {
+        *options = as_string;
+    }
+    Some(())
+}
+
+/// Sets the option at the path of `key`, like `LIBRARIES__DATETIME`, creating the missing tables.
+/// Returns None if one of the parents of the option is not a table.
+fn set_option(options: &mut Value, key: &str, value: Value) -> Option<()> {
+    let mut keys = key.split(ENV_SEPARATOR).map(str::to_lowercase).peekable();
+    let mut current = options;
+    while let Some(key) = keys.next() {
+        let table = current.as_object_mut()?;
+        if keys.peek().is_none() {
+            table.insert(key, value);
+            return Some(());
+        }
+        current = table
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    None
+}
+
+/// Reads the value of an environment variable as a TOML value, or as a string otherwise.
+fn env_to_value(value: &str) -> Value {
+    format!("value = {value}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_config_with_env;
+    use crate::models::{DateTimeLibraries, Pagination, ServerLibraries, TransportLibraries};
+    use rstest::rstest;
+    use std::path::Path;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("src/config/fixtures/oapigen.toml")]
+    #[case("src/config/fixtures/oapigen.yaml")]
+    fn test_load_config(#[case] path: &str) {
+        let got = load_config_with_env(Some(Path::new(path)), vec![]).unwrap();
+
+        assert_eq!(got.output_path, Path::new("src/api.rs"));
+        assert!(matches!(got.libraries.datetime, DateTimeLibraries::Jiff));
+        assert!(matches!(
+            got.libraries.transports.as_slice(),
+            [TransportLibraries::Reqwest, TransportLibraries::Ureq]
+        ));
+        assert!(matches!(got.libraries.server, ServerLibraries::Axum));
+        assert!(got.targets.client && got.targets.blocking_client && !got.targets.server);
+        assert!(matches!(
+            got.pagination.get("listPets"),
+            Some(Pagination::Cursor { parameter, next_cursor, items })
+                if parameter == "cursor" && next_cursor == "next_cursor" && items == "data"
+        ));
+        assert!(matches!(
+            got.pagination.get("listOwners"),
+            Some(Pagination::Link { items }) if items == "owners"
+        ));
+        assert_eq!(
+            got.types.get("uuid").map(String::as_str),
+            Some("uuid::Uuid")
+        );
+        assert_eq!(got.derives, ["Clone", "PartialEq"]);
+        assert!(!got.filters.operations.allows("deletePet"));
+        assert!(got.filters.operations.allows("listPets"));
+        assert!(got.filters.schemas.allows("Owner"));
+        assert!(!got.filters.schemas.allows("Order"));
+    }
+
+    #[test]
+    fn test_load_config_with_env() {
+        let env = vars(&[
+            ("OAPIGEN_OUTPUT_PATH", "src/generated.rs"),
+            ("OAPIGEN_LIBRARIES__DATETIME", "chrono"),
+            ("OAPIGEN_LIBRARIES__SERVER", "actix-web"),
+            ("OAPIGEN_TARGETS__SERVER", "true"),
+            ("OAPIGEN_TARGETS__BLOCKING_CLIENT", "false"),
+            ("PATH", "/bin"),
+        ]);
+
+        let got =
+            load_config_with_env(Some(Path::new("src/config/fixtures/oapigen.toml")), env).unwrap();
+
+        assert_eq!(got.output_path, Path::new("src/generated.rs"));
+        assert!(matches!(got.libraries.datetime, DateTimeLibraries::Chrono));
+        assert!(matches!(got.libraries.server, ServerLibraries::ActixWeb));
+        assert_eq!(got.libraries.transports.len(), 2);
+        assert!(got.targets.client && !got.targets.blocking_client && got.targets.server);
+    }
+
+    #[rstest]
+    #[case("1.0", "1.0")]
+    #[case("2", "2")]
+    #[case("true", "true")]
+    #[case("1.2.3", "1.2.3")]
+    fn test_load_string_with_env(#[case] value: &str, #[case] expected: &str) {
+        let env = vars(&[
+            ("OAPIGEN_PACKAGE__VERSION", value),
+            ("OAPIGEN_PACKAGE__NAME", value),
+        ]);
+
+        let got = load_config_with_env(None, env).unwrap();
+
+        assert_eq!(got.package.version.as_deref(), Some(expected));
+        assert_eq!(got.package.name.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_load_defaults() {
+        let env = vars(&[("OAPIGEN_LIBRARIES__TRANSPORTS", r#"["ureq"]"#)]);
+
+        let got = load_config_with_env(None, env).unwrap();
+
+        assert_eq!(got.output_path, Path::new(""));
+        assert!(matches!(got.libraries.datetime, DateTimeLibraries::Chrono));
+        assert!(matches!(
+            got.libraries.transports.as_slice(),
+            [TransportLibraries::Ureq]
+        ));
+        assert!(!got.targets.client && !got.targets.blocking_client && !got.targets.server);
+        assert!(got.pagination.is_empty());
+        assert!(got.types.is_empty() && got.derives.is_empty());
+        assert!(got.filters.operations.allows("listPets") && got.filters.schemas.allows("Pet"));
+    }
+
+    #[rstest]
+    #[case("unknown option", Some("src/config/fixtures/unknown_option.toml"), &[])]
+    #[case("unknown pagination", Some("src/config/fixtures/unknown_pagination.toml"), &[])]
+    #[case("missing file", Some("src/config/fixtures/missing.toml"), &[])]
+    #[case("invalid toml", Some("src/config/fixtures/invalid.toml"), &[])]
+    #[case("unknown env", None, &[("OAPIGEN_TARGETS__CLIENTS", "true")])]
+    #[case("invalid env", None, &[("OAPIGEN_LIBRARIES__SERVER", "rocket")])]
+    #[case("env below an option", None, &[("OAPIGEN_OUTPUT_PATH", "src"), ("OAPIGEN_OUTPUT_PATH__FILE", "api.rs")])]
+    fn test_invalid_config(
+        #[case] name: &str,
+        #[case] path: Option<&str>,
+        #[case] env: &[(&str, &str)],
+    ) {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_suffix(name);
+
+        let got = load_config_with_env(path.map(Path::new), vars(env)).unwrap_err();
+        let got = match std::error::Error::source(&got) {
+            Some(source) => format!("{got}: {source}"),
+            None => got.to_string(),
+        };
+
+        settings.bind(|| {
+            insta::assert_snapshot!(got);
+        });
+    }
+}
@@ -0,0 +1,5 @@
+mod errors;
+mod loading;
+
+pub use errors::ConfigError;
+pub use loading::load_config;
@@ -1,10 +1,10 @@
 use crate::models;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Formats boolean according to the registry provided in
 /// [https://spec.openapis.org/registry/format/].
 ///
 /// The list of supported format is a subset of the registry.
 pub fn format_null() -> (String, models::Imports) {
-    ("".to_string(), HashMap::new())
+    ("".to_string(), BTreeMap::new())
 }
@@ -2,7 +2,13 @@
 
 use crate::models;
 use log::warn;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+/// The formats that [format_number] turns into their own rust type, the other ones are represented as `f32`.
+pub(crate) const KNOWN_FORMATS: &[&str] = &[
+    "int64", "integer", "int32", "int16", "int8", "uint64", "uint32", "uint16", "uint8", "float",
+    "double",
+];
 
 /// Formats numbers (including integers) according to the registry provided in
 /// [https://spec.openapis.org/registry/format/].
@@ -26,5 +32,5 @@ pub fn format_number(type_format: &str) -> (String, models::Imports) {
         }
     };
 
-    (rust_type.to_string(), HashMap::new())
+    (rust_type.to_string(), BTreeMap::new())
 }
@@ -3,18 +3,21 @@
 use crate::models;
 use crate::models::DateTimeLibraries;
 use log::warn;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 // the various names for the imports unsed in the various
 const NAME_IMPORTS_CHRONO: &str = "chrono_datetime_utc";
 const NAME_IMPORTS_JIFF: &str = "jiff_timestamp";
 
+/// The formats that [format_string] turns into their own rust type, the other ones are represented as `String`.
+pub(crate) const KNOWN_FORMATS: &[&str] = &["date-time", "binary"];
+
 /// Formats strings according to the registry provided in
 /// [https://spec.openapis.org/registry/format/].
 ///
 /// The list of supported format is a subset of the registry.
 pub fn format_string(config: &models::Config, type_format: &str) -> (String, models::Imports) {
-    let mut imports = HashMap::new();
+    let mut imports = BTreeMap::new();
 
     let rust_type = match type_format {
         "date-time" => match config.libraries.datetime {
@@ -33,6 +36,7 @@ pub fn format_string(config: &models::Config, type_format: &str) -> (String, mod
                 "Timestamp"
             }
         },
+        "binary" => "Vec<u8>",
         _ => {
             warn!("format {type_format} is unknown for strings, defaulting to string");
             "String"
@@ -0,0 +1,234 @@
+use crate::generating::errors::GeneratingError;
+use crate::generating::files::write_spec;
+use crate::models;
+use std::path::{Component, Path, PathBuf};
+
+/// Builder generates the code of a spec from a build script, so that a crate can include it
+/// without running the CLI or committing the generated code, like
+///
+/// ```no_run
+/// // build.rs
+/// fn main() -> Result<(), oapigen::GeneratingError> {
+///     oapigen::Builder::new()
+///         .spec("api.yaml")
+///         .config(oapigen::Config::default())
+///         .out_dir_env()
+///         .generate()?;
+///     Ok(())
+/// }
+/// ```
+///
+/// and then `include!(concat!(env!("OUT_DIR"), "/api.rs"));` in the crate.
+///
+/// It prints `cargo:rerun-if-changed` for the spec and for the files referenced by its `$ref`,
+/// so that cargo runs the build script again only when one of them changes.
+#[derive(Default)]
+pub struct Builder {
+    spec: Option<PathBuf>,
+    config: models::Config,
+    out_dir_env: bool,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the spec to generate, in YAML or JSON.
+    pub fn spec(mut self, path: impl AsRef<Path>) -> Self {
+        self.spec = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the config of the generation. Its output path is used unless [out_dir_env](Self::out_dir_env) is set.
+    pub fn config(mut self, config: models::Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Writes the code in the `OUT_DIR` of the build script, in a file named after the spec,
+    /// like `api.rs` for `api.yaml`, or in a directory with that name for the module tree and the standalone crate layouts.
+    pub fn out_dir_env(mut self) -> Self {
+        self.out_dir_env = true;
+        self
+    }
+
+    /// Generates the code of the spec and writes it, leaving the files untouched if they
+    /// already have the generated code. Returns the path of the generated code.
+    pub fn generate(mut self) -> Result<PathBuf, GeneratingError> {
+        let spec_path = self.spec.ok_or(GeneratingError::MissingSpecError)?;
+        if self.out_dir_env {
+            let out_dir = std::env::var_os("OUT_DIR").ok_or(GeneratingError::MissingOutDirError)?;
+            let stem = spec_path.file_stem().unwrap_or(spec_path.as_os_str());
+            let is_directory = self.config.layout.modules || self.config.layout.standalone_crate;
+            let output = match is_directory {
+                true => PathBuf::from(stem),
+                false => Path::new(stem).with_extension("rs"),
+            };
+            self.config.output_path = Path::new(&out_dir).join(output);
+        }
+
+        println!("cargo:rerun-if-changed={}", spec_path.display());
+        let content =
+            std::fs::read_to_string(&spec_path).map_err(GeneratingError::ReadSpecError)?;
+        for file in referenced_files(&spec_path, &content)? {
+            println!("cargo:rerun-if-changed={}", file.display());
+        }
+
+        let spec = oas3::from_yaml(content)?;
+        write_spec(&self.config, spec)?;
+        Ok(self.config.output_path)
+    }
+}
+
+/// Returns the files referenced by the `$ref` of a spec, like `schemas.yaml#/Pet`, and by the `$ref`
+/// of the files they reference, each relative to the directory of the file that references it.
+/// The references within a file are skipped, and so is the spec itself when a file references it back.
+/// The files that cannot be read or parsed are returned without following their references.
+fn referenced_files(spec_path: &Path, content: &str) -> Result<Vec<PathBuf>, GeneratingError> {
+    let spec_path = normalize_path(spec_path);
+    let mut files = Vec::new();
+    let mut queue = vec![(spec_path.clone(), serde_yaml::from_str(content)?)];
+    while let Some((path, document)) = queue.pop() {
+        for file in document_references(&path, document) {
+            if file == spec_path || files.contains(&file) {
+                continue;
+            }
+            let document = std::fs::read_to_string(&file)
+                .ok()
+                .and_then(|content| serde_yaml::from_str(&content).ok());
+            if let Some(document) = document {
+                queue.push((file.clone(), document));
+            }
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns the files referenced by the `$ref` of the document at `path`, relative to its directory.
+fn document_references(path: &Path, document: serde_yaml::Value) -> Vec<PathBuf> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut files = Vec::new();
+    let mut stack = vec![document];
+    while let Some(value) = stack.pop() {
+        match value {
+            serde_yaml::Value::Mapping(mapping) => {
+                for (key, value) in mapping {
+                    match (key.as_str(), value.as_str()) {
+                        (Some("$ref"), Some(reference)) => {
+                            let file = reference.split('#').next().unwrap_or_default();
+                            let is_remote = file.contains("://");
+                            if !file.is_empty() && !is_remote {
+                                files.push(normalize_path(&directory.join(file)));
+                            }
+                        }
+                        _ => stack.push(value),
+                    }
+                }
+            }
+            serde_yaml::Value::Sequence(sequence) => stack.extend(sequence),
+            _ => {}
+        }
+    }
+    files
+}
+
+/// Removes the `.` and the `..` of a path without reading the file system, so that the same file
+/// reached from different directories has the same path, like `api/pet.yaml` for `api/schemas/../pet.yaml`.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // the parent of the root is the root
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_path, referenced_files};
+    use indoc::indoc;
+    use rstest::rstest;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_referenced_files() {
+        let spec = indoc! {"
+            paths:
+              /pets:
+                get:
+                  responses:
+                    '200':
+                      $ref: 'responses.yaml#/Pets'
+                    default:
+                      $ref: '#/components/responses/Error'
+            components:
+              schemas:
+                Pet:
+                  allOf:
+                    - $ref: schemas/pet.yaml
+                    - $ref: 'https://example.com/schemas.yaml#/Named'
+                    - $ref: 'responses.yaml#/Named'
+        "};
+
+        let got = referenced_files(Path::new("api/spec.yaml"), spec).unwrap();
+
+        assert_eq!(
+            got,
+            vec![
+                PathBuf::from("api/responses.yaml"),
+                PathBuf::from("api/schemas/pet.yaml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_referenced_files_transitive() {
+        let dir = std::env::temp_dir().join(format!("oapigen_refs_{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "schemas/pet.yaml",
+            "properties: {owner: {$ref: '../owners/owner.yaml'}}",
+        );
+        // the owner references the pet back, and the spec, so that the references are a cycle
+        write(
+            "owners/owner.yaml",
+            "properties: {pets: {$ref: '../schemas/pet.yaml'}, spec: {$ref: '../spec.yaml#/info'}}",
+        );
+        let spec = "paths: {/pets: {$ref: 'schemas/pet.yaml#/paths'}}";
+        write("spec.yaml", spec);
+
+        let got = referenced_files(&dir.join("spec.yaml"), spec).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            got,
+            vec![dir.join("owners/owner.yaml"), dir.join("schemas/pet.yaml")]
+        );
+    }
+
+    #[rstest]
+    #[case("api/schemas/../pet.yaml", "api/pet.yaml")]
+    #[case("./api/./pet.yaml", "api/pet.yaml")]
+    #[case("../pet.yaml", "../pet.yaml")]
+    #[case("/api/../../pet.yaml", "/pet.yaml")]
+    fn test_normalize_path(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(normalize_path(Path::new(path)), PathBuf::from(expected));
+    }
+}
@@ -0,0 +1,722 @@
+//! Generation of the client that calls the operations of a spec.
+
+use crate::generating::{pagination, security};
+use crate::models;
+use crate::models::{ParameterAsRust, ParameterLocation, SecuritySchemeKind, tokenize_encode_body};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+
+/// Returns the items shared by the async and the blocking clients: the error returned by their
+/// methods, the request and the response exchanged with the transports, the in-memory transport
+/// used in tests, the credentials of the security schemes and the helper used to encode the parameters.
+pub(crate) fn client_runtime(spec: &models::SpecAsRust) -> TokenStream {
+    // the values of the parameters are percent-encoded, except the ones sent as headers,
+    // and so are the API keys sent in the query or in a cookie and the OAuth2 client secrets
+    let schemes: Vec<_> = spec.security_schemes.iter().collect();
+    let encodes_parameters = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.parameters)
+        .any(|p| p.location != ParameterLocation::Header)
+        || security::has_api_key_in(&schemes, ParameterLocation::Query)
+        || security::has_api_key_in(&schemes, ParameterLocation::Cookie)
+        || security::has_client_credentials(spec);
+    let encode_component = match encodes_parameters {
+        true => quote! {
+            /// Percent-encodes a value so that it can be used in a path segment or in a query string.
+            fn encode_component(value: &str) -> String {
+                let mut encoded = String::with_capacity(value.len());
+                for byte in value.bytes() {
+                    match byte {
+                        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                            encoded.push(char::from(byte))
+                        }
+                        _ => encoded.push_str(&format!("%{:02X}", byte)),
+                    }
+                }
+                encoded
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let credentials = match spec.security_schemes.is_empty() {
+        true => TokenStream::new(),
+        false => security::client_credentials(spec),
+    };
+    let next_link = match spec.operations.iter().any(pagination::is_link_paginated) {
+        true => pagination::next_link(),
+        false => TokenStream::new(),
+    };
+    let streaming_response = match has_streaming(spec) {
+        true => quote! {
+            /// A response whose body is still being received, used by the operations that stream their responses.
+            #[derive(Debug)]
+            pub struct StreamingHttpResponse {
+                /// The status code of the response
+                pub status: u16,
+                /// The headers of the response
+                pub headers: http::HeaderMap,
+                /// The body of the response
+                pub body: ByteStream,
+            }
+
+            impl From<HttpResponse> for StreamingHttpResponse {
+                fn from(response: HttpResponse) -> Self {
+                    Self {
+                        status: response.status,
+                        headers: response.headers,
+                        body: ByteStream::from_bytes(response.body),
+                    }
+                }
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let (token_error, display_token_error) = match security::has_client_credentials(spec) {
+        true => (
+            quote! {
+                /// The access token of an OAuth2 scheme could not be obtained.
+                Token(Box<dyn std::error::Error + Send + Sync>),
+            },
+            quote! { Self::Token(e) => write!(f, "cannot obtain the access token: {}", e), },
+        ),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+
+    quote! {
+        /// Errors that can happen while calling an operation.
+        #[derive(Debug)]
+        pub enum ClientError {
+            /// The transport could not send the request or receive the response.
+            Transport(Box<dyn std::error::Error + Send + Sync>),
+            /// The body of the request could not be serialized.
+            Serialize(Box<dyn std::error::Error + Send + Sync>),
+            /// The response could not be decoded.
+            Response(ResponseError),
+            #token_error
+        }
+
+        impl std::fmt::Display for ClientError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Transport(e) => write!(f, "cannot send the request: {}", e),
+                    Self::Serialize(e) => write!(f, "cannot serialize the body: {}", e),
+                    Self::Response(e) => write!(f, "cannot decode the response: {}", e),
+                    #display_token_error
+                }
+            }
+        }
+
+        impl std::error::Error for ClientError {}
+
+        impl From<ResponseError> for ClientError {
+            fn from(e: ResponseError) -> Self {
+                Self::Response(e)
+            }
+        }
+
+        /// A request built by the client, ready to be sent by a transport.
+        #[derive(Debug, Clone)]
+        pub struct HttpRequest {
+            /// The method of the request
+            pub method: http::Method,
+            /// The full URL of the request, including the query string
+            pub url: String,
+            /// The headers of the request
+            pub headers: Vec<(String, String)>,
+            /// The body of the request, empty if the request has no body
+            pub body: Vec<u8>,
+        }
+
+        /// A response received by a transport.
+        #[derive(Debug, Clone, Default)]
+        pub struct HttpResponse {
+            /// The status code of the response
+            pub status: u16,
+            /// The headers of the response
+            pub headers: http::HeaderMap,
+            /// The body of the response
+            pub body: Vec<u8>,
+        }
+
+        #streaming_response
+
+        /// A transport that answers the requests with a function, without touching the network.
+        /// The function acts as a stand-in for the server, and the requests are recorded
+        /// so that tests can check what the client sent.
+        #[derive(Clone)]
+        pub struct InMemoryTransport {
+            handler: std::sync::Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>,
+            requests: std::sync::Arc<std::sync::Mutex<Vec<HttpRequest>>>,
+        }
+
+        impl InMemoryTransport {
+            /// Creates a transport that answers the requests with `handler`.
+            pub fn new(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+                Self {
+                    handler: std::sync::Arc::new(handler),
+                    requests: std::sync::Arc::default(),
+                }
+            }
+
+            /// Returns the requests sent so far, in the order in which they were sent.
+            pub fn requests(&self) -> Vec<HttpRequest> {
+                match self.requests.lock() {
+                    Ok(requests) => requests.clone(),
+                    Err(poisoned) => poisoned.into_inner().clone(),
+                }
+            }
+
+            fn answer(&self, request: HttpRequest) -> HttpResponse {
+                let response = (self.handler)(&request);
+                match self.requests.lock() {
+                    Ok(mut requests) => requests.push(request),
+                    Err(poisoned) => poisoned.into_inner().push(request),
+                }
+                response
+            }
+        }
+
+        impl std::fmt::Debug for InMemoryTransport {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("InMemoryTransport").finish_non_exhaustive()
+            }
+        }
+
+        #encode_component
+        #credentials
+        #next_link
+    }
+}
+
+/// Returns true if one of the operations streams its responses.
+pub(crate) fn has_streaming(spec: &models::SpecAsRust) -> bool {
+    spec.operations
+        .iter()
+        .flat_map(|o| &o.responses)
+        .any(models::ResponseAsRust::is_streaming)
+}
+
+/// Returns the async client, with one method for each operation of the spec,
+/// together with the transport trait that it uses.
+pub(crate) fn async_client(spec: &models::SpecAsRust) -> TokenStream {
+    let client = tokenize_client(spec, true);
+    let paginated = match pagination::has_pagination(spec) {
+        true => pagination::paginated(true),
+        false => TokenStream::new(),
+    };
+    let send_streaming = match has_streaming(spec) {
+        true => quote! {
+            /// Sends a request and returns its response as soon as its headers are received,
+            /// used by the operations that stream their responses.
+            /// By default the whole body is received with [HttpTransport::send].
+            fn send_streaming(
+                &self,
+                request: HttpRequest,
+            ) -> impl std::future::Future<Output = Result<StreamingHttpResponse, Self::Error>> + Send {
+                let response = self.send(request);
+                async move { Ok(response.await?.into()) }
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// The transport used by the client to send its requests.
+        ///
+        /// Implementing it allows to use any HTTP library, to add middleware around
+        /// another transport, or to answer the requests without touching the network.
+        pub trait HttpTransport {
+            /// The error returned when a request cannot be sent.
+            type Error: std::error::Error + Send + Sync + 'static;
+
+            /// Sends a request and returns its response.
+            fn send(
+                &self,
+                request: HttpRequest,
+            ) -> impl std::future::Future<Output = Result<HttpResponse, Self::Error>> + Send;
+
+            #send_streaming
+        }
+
+        impl HttpTransport for InMemoryTransport {
+            type Error = std::convert::Infallible;
+
+            async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+                Ok(self.answer(request))
+            }
+        }
+
+        #client
+        #paginated
+    }
+}
+
+/// Returns the blocking client in the `blocking` module, with the same methods of the async client
+/// minus `async`, together with the blocking transport trait and its adapters.
+pub(crate) fn blocking_client(spec: &models::SpecAsRust, adapters: TokenStream) -> TokenStream {
+    let client = tokenize_client(spec, false);
+    let paginated = match pagination::has_pagination(spec) {
+        true => pagination::paginated(false),
+        false => TokenStream::new(),
+    };
+    let send_streaming = match has_streaming(spec) {
+        true => quote! {
+            /// Sends a request and returns its response as soon as its headers are received,
+            /// used by the operations that stream their responses.
+            /// By default the whole body is received with [HttpTransport::send].
+            fn send_streaming(&self, request: HttpRequest) -> Result<StreamingHttpResponse, Self::Error> {
+                Ok(self.send(request)?.into())
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// The blocking variant of the client, for the programs that cannot use an async runtime.
+        pub mod blocking {
+            use super::*;
+
+            /// The transport used by the blocking client to send its requests.
+            ///
+            /// Implementing it allows to use any HTTP library, to add middleware around
+            /// another transport, or to answer the requests without touching the network.
+            pub trait HttpTransport {
+                /// The error returned when a request cannot be sent.
+                type Error: std::error::Error + Send + Sync + 'static;
+
+                /// Sends a request and returns its response.
+                fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error>;
+
+                #send_streaming
+            }
+
+            impl HttpTransport for InMemoryTransport {
+                type Error = std::convert::Infallible;
+
+                fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+                    Ok(self.answer(request))
+                }
+            }
+
+            #adapters
+
+            #client
+            #paginated
+        }
+    }
+}
+
+/// Renders the client struct, with one method for each operation.
+fn tokenize_client(spec: &models::SpecAsRust, is_async: bool) -> TokenStream {
+    let methods = spec.operations.iter().map(|o| {
+        let method = tokenize_method(spec, o, is_async);
+        let pagination_methods = pagination::tokenize_pagination_methods(o, is_async);
+        quote! {
+            #method
+            #pagination_methods
+        }
+    });
+    let (credentials, set_credentials, security_methods) = match spec.security_schemes.is_empty() {
+        true => (TokenStream::new(), TokenStream::new(), TokenStream::new()),
+        false => (
+            quote! { credentials: Credentials, },
+            quote! { credentials: Credentials::default(), },
+            security::client_methods(spec, is_async),
+        ),
+    };
+
+    let new_comment = match spec.servers.is_empty() {
+        true => quote! {
+            /// Creates a client that sends the requests to `base_url` through `transport`.
+        },
+        false => quote! {
+            /// Creates a client that sends the requests to `base_url` through `transport`.
+            /// The base URL can be one of the [Server]s of the spec, like `Server::default()`.
+        },
+    };
+
+    quote! {
+        /// A client with one method for each operation of the spec,
+        /// that sends the requests through a [HttpTransport].
+        #[derive(Debug, Clone)]
+        pub struct Client<T> {
+            base_url: String,
+            transport: T,
+            #credentials
+        }
+
+        // the headers and the query are pushed one parameter at a time, even when all of them are required
+        #[allow(clippy::vec_init_then_push)]
+        impl<T: HttpTransport> Client<T> {
+            #new_comment
+            pub fn new(base_url: impl Into<String>, transport: T) -> Self {
+                let base_url = base_url.into().trim_end_matches('/').to_string();
+                Self {
+                    base_url,
+                    transport,
+                    #set_credentials
+                }
+            }
+
+            /// Returns the URL to which the requests are sent.
+            pub fn base_url(&self) -> &str {
+                &self.base_url
+            }
+
+            /// Returns the transport used to send the requests.
+            pub fn transport(&self) -> &T {
+                &self.transport
+            }
+
+            #security_methods
+            #(#methods)*
+        }
+    }
+}
+
+/// Renders the method of the client that calls an operation, that is async unless `is_async` is false.
+fn tokenize_method(
+    spec: &models::SpecAsRust,
+    operation: &models::OperationAsRust,
+    is_async: bool,
+) -> TokenStream {
+    let method_name = match operation.method_name().parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn method name to tokens: {}", e)),
+    };
+    let comment = match &operation.description {
+        Some(c) if !c.is_empty() => quote! { #[doc = #c] },
+        _ => TokenStream::new(),
+    };
+    let http_method = format_ident!("{}", operation.method);
+
+    let mut arguments = Vec::new();
+    if !operation.parameters.is_empty() {
+        let params_type = format_ident!("{}", operation.parameters_struct_name());
+        arguments.push(quote! { params: &#params_type });
+    }
+
+    let schemes = security::operation_schemes(spec, operation);
+    let url = tokenize_url(
+        operation,
+        security::has_api_key_in(&schemes, ParameterLocation::Query),
+    );
+    let query = tokenize_query(&operation.parameters);
+    let header_parameters = tokenize_headers(&operation.parameters);
+    let authentication = security::tokenize_client_authentication(spec, operation, is_async);
+
+    let body = match &operation.request_body {
+        None => quote! { let body = Vec::new(); },
+        Some(request_body) => {
+            let body_type = format_ident!("{}", request_body.body.name);
+            let encoded = tokenize_encode_body(
+                &request_body.body,
+                &quote! { |e| ClientError::Serialize(e.into()) },
+            );
+            let encode = quote! {
+                let (content_type, body) = #encoded;
+                headers.push(("Content-Type".to_string(), content_type));
+            };
+            match request_body.is_required {
+                true => {
+                    arguments.push(quote! { body: #body_type });
+                    encode
+                }
+                false => {
+                    arguments.push(quote! { body: Option<#body_type> });
+                    quote! {
+                        let body = match body {
+                            Some(body) => {
+                                #encode
+                                body
+                            }
+                            None => Vec::new(),
+                        };
+                    }
+                }
+            }
+        }
+    };
+    let sends_headers = schemes.iter().any(|s| {
+        !matches!(
+            s.kind,
+            SecuritySchemeKind::ApiKey {
+                location: ParameterLocation::Query,
+                ..
+            }
+        )
+    });
+    let headers =
+        match header_parameters.is_empty() && operation.request_body.is_none() && !sends_headers {
+            true => quote! { let headers = Vec::new(); },
+            false => quote! { let mut headers: Vec<(String, String)> = Vec::new(); },
+        };
+
+    let (asyncness, awaited) = match is_async {
+        true => (quote! { async }, quote! { .await }),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+
+    // an operation without responses only exposes the status code,
+    // and one that streams its responses decodes them while they are received
+    let is_streaming = operation
+        .responses
+        .iter()
+        .any(models::ResponseAsRust::is_streaming);
+    let send = match is_streaming {
+        true => quote! { send_streaming },
+        false => quote! { send },
+    };
+    let (return_type, decode) = match operation.responses.is_empty() {
+        true => (quote! { u16 }, quote! { Ok(response.status) }),
+        false if is_streaming => {
+            let response_enum = format_ident!("{}", operation.response_enum_name());
+            let decoded = quote! {
+                #response_enum::from_stream(response.status, &response.headers, response.body)
+            };
+            let decoded = match is_async {
+                true => quote! { #decoded.await },
+                false => quote! { block_on(#decoded) },
+            };
+            (quote! { #response_enum }, quote! { Ok(#decoded?) })
+        }
+        false => {
+            let response_enum = format_ident!("{}", operation.response_enum_name());
+            (
+                quote! { #response_enum },
+                quote! {
+                    Ok(#response_enum::from_parts(response.status, &response.headers, &response.body)?)
+                },
+            )
+        }
+    };
+
+    let build_request = quote! {
+        #url
+        #query
+        #headers
+        #header_parameters
+        #authentication
+        #body
+        let request = HttpRequest {
+            method: http::Method::#http_method,
+            url,
+            headers,
+            body,
+        };
+    };
+    // the requests of the pages that follow the `Link` header are built without being sent
+    let (request_function, build_request) = match pagination::is_link_paginated(operation) {
+        true => {
+            let request_function = pagination::request_function_name(operation);
+            let mut argument_names = Vec::new();
+            if !operation.parameters.is_empty() {
+                argument_names.push(quote! { params });
+            }
+            if operation.request_body.is_some() {
+                argument_names.push(quote! { body });
+            }
+            (
+                quote! {
+                    /// Builds the request of the operation, without sending it.
+                    #asyncness fn #request_function(&self, #(#arguments),*) -> Result<HttpRequest, ClientError> {
+                        #build_request
+                        Ok(request)
+                    }
+                },
+                quote! { let request = self.#request_function(#(#argument_names),*)#awaited?; },
+            )
+        }
+        false => (TokenStream::new(), build_request),
+    };
+
+    quote! {
+        #request_function
+
+        #comment
+        pub #asyncness fn #method_name(&self, #(#arguments),*) -> Result<#return_type, ClientError> {
+            #build_request
+            let response = self
+                .transport
+                .#send(request)
+                #awaited
+                .map_err(|e| ClientError::Transport(Box::new(e)))?;
+            #decode
+        }
+    }
+}
+
+/// Returns the expression that turns the value of a parameter to the string sent in the request.
+/// Arrays that are not exploded are sent as a single comma separated value.
+fn tokenize_value(parameter: &ParameterAsRust, value: TokenStream) -> TokenStream {
+    match parameter.is_array {
+        true => quote! {
+            #value.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+        },
+        false => quote! { #value.to_string() },
+    }
+}
+
+/// Runs `push` on the values of a parameter, skipping the missing optional parameters
+/// and the values of the exploded arrays one by one.
+fn tokenize_for_each_value(
+    parameter: &ParameterAsRust,
+    push: impl Fn(TokenStream) -> TokenStream,
+) -> TokenStream {
+    let field_name = match parameter.field_name.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    };
+    let is_exploded = parameter.is_array && parameter.explode;
+
+    match (parameter.is_required, is_exploded) {
+        (true, false) => push(tokenize_value(parameter, quote! { params.#field_name })),
+        (false, false) => {
+            let push = push(tokenize_value(parameter, quote! { value }));
+            quote! {
+                if let Some(value) = &params.#field_name {
+                    #push
+                }
+            }
+        }
+        (true, true) => {
+            let push = push(quote! { value.to_string() });
+            quote! {
+                for value in &params.#field_name {
+                    #push
+                }
+            }
+        }
+        (false, true) => {
+            let push = push(quote! { value.to_string() });
+            quote! {
+                for value in params.#field_name.iter().flatten() {
+                    #push
+                }
+            }
+        }
+    }
+}
+
+/// Returns the statement that builds the `url` variable, replacing the template expressions
+/// of the path with the path parameters, that is mutable if the query is appended to it later.
+fn tokenize_url(operation: &models::OperationAsRust, sends_query_api_key: bool) -> TokenStream {
+    let mut template = "{}".to_string();
+    let mut values = vec![quote! { self.base_url }];
+    let mut rest = operation.path.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        template.push_str(&escape_braces(&rest[..start]));
+        let name = &rest[start + 1..end];
+        let parameter = operation
+            .parameters
+            .iter()
+            .find(|p| p.location == ParameterLocation::Path && p.name == name);
+        match parameter {
+            Some(parameter) => {
+                let value = tokenize_value(parameter, {
+                    let field_name = format_ident!("{}", parameter.field_name);
+                    quote! { params.#field_name }
+                });
+                template.push_str("{}");
+                values.push(quote! { encode_component(&#value) });
+            }
+            // a template expression without a parameter is sent as it is
+            None => template.push_str(&escape_braces(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    template.push_str(&escape_braces(rest));
+
+    let is_mut = sends_query_api_key
+        || operation
+            .parameters
+            .iter()
+            .any(|p| p.location == ParameterLocation::Query);
+    let url = match is_mut {
+        true => quote! { let mut url },
+        false => quote! { let url },
+    };
+    let template = Literal::string(&template);
+    quote! { #url = format!(#template, #(#values),*); }
+}
+
+fn escape_braces(s: &str) -> String {
+    s.replace('{', "{{").replace('}', "}}")
+}
+
+/// Returns the statements that append the query parameters to the `url` variable.
+fn tokenize_query(parameters: &[ParameterAsRust]) -> TokenStream {
+    let pushes: Vec<_> = parameters
+        .iter()
+        .filter(|p| p.location == ParameterLocation::Query)
+        .map(|parameter| {
+            let name = &parameter.name;
+            tokenize_for_each_value(parameter, |value| {
+                quote! { query.push((#name, #value)); }
+            })
+        })
+        .collect();
+    if pushes.is_empty() {
+        return TokenStream::new();
+    }
+
+    quote! {
+        let mut query: Vec<(&str, String)> = Vec::new();
+        #(#pushes)*
+        if !query.is_empty() {
+            let query: Vec<_> = query
+                .iter()
+                .map(|(name, value)| format!("{}={}", encode_component(name), encode_component(value)))
+                .collect();
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+    }
+}
+
+/// Returns the statements that add the header and cookie parameters to the `headers` variable.
+fn tokenize_headers(parameters: &[ParameterAsRust]) -> TokenStream {
+    let headers = parameters
+        .iter()
+        .filter(|p| p.location == ParameterLocation::Header)
+        .map(|parameter| {
+            let name = &parameter.name;
+            tokenize_for_each_value(parameter, |value| {
+                quote! { headers.push((#name.to_string(), #value)); }
+            })
+        });
+    let cookies: Vec<_> = parameters
+        .iter()
+        .filter(|p| p.location == ParameterLocation::Cookie)
+        .map(|parameter| {
+            let name = &parameter.name;
+            tokenize_for_each_value(parameter, |value| {
+                quote! { cookies.push((#name, #value)); }
+            })
+        })
+        .collect();
+
+    let cookies = match cookies.is_empty() {
+        true => TokenStream::new(),
+        false => quote! {
+            let mut cookies: Vec<(&str, String)> = Vec::new();
+            #(#cookies)*
+            if !cookies.is_empty() {
+                let cookies: Vec<_> = cookies
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, encode_component(value)))
+                    .collect();
+                headers.push(("Cookie".to_string(), cookies.join("; ")));
+            }
+        },
+    };
+
+    quote! {
+        #(#headers)*
+        #cookies
+    }
+}
@@ -0,0 +1,341 @@
+use crate::generating::errors::GeneratingError;
+use crate::generating::modules::{GeneratedFile, spec_to_files};
+use crate::models;
+use convert_case::{Case, Casing};
+use proc_macro2::{Spacing, TokenStream, TokenTree};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Dependency is a crate that the generated code can use, with the version and the features it needs.
+struct Dependency {
+    /// The name of the crate in the paths of the code, like `actix_web`
+    path: &'static str,
+    /// The name of the crate in `Cargo.toml`, like `actix-web`
+    name: &'static str,
+    version: &'static str,
+    features: &'static [&'static str],
+    /// The features that are needed only by some of the code, with the identifier that needs them,
+    /// like `stream` for `bytes_stream`
+    used_features: &'static [(&'static str, &'static str)],
+}
+
+/// The crates that the generated code can use, sorted by name.
+const DEPENDENCIES: &[Dependency] = &[
+    Dependency {
+        path: "actix_web",
+        name: "actix-web",
+        version: "4",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "axum",
+        name: "axum",
+        version: "0.8",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "chrono",
+        name: "chrono",
+        version: "0.4",
+        features: &["serde"],
+        used_features: &[],
+    },
+    Dependency {
+        path: "futures_core",
+        name: "futures-core",
+        version: "0.3",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "http",
+        name: "http",
+        version: "1",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "jiff",
+        name: "jiff",
+        version: "0.2",
+        features: &["serde"],
+        used_features: &[],
+    },
+    Dependency {
+        path: "regex",
+        name: "regex",
+        version: "1",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "reqwest",
+        name: "reqwest",
+        version: "0.12",
+        features: &[],
+        used_features: &[("stream", "bytes_stream")],
+    },
+    Dependency {
+        path: "serde",
+        name: "serde",
+        version: "1",
+        features: &["derive"],
+        used_features: &[],
+    },
+    Dependency {
+        path: "serde_json",
+        name: "serde_json",
+        version: "1",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "serde_urlencoded",
+        name: "serde_urlencoded",
+        version: "0.7",
+        features: &[],
+        used_features: &[],
+    },
+    Dependency {
+        path: "ureq",
+        name: "ureq",
+        version: "3",
+        features: &[],
+        used_features: &[],
+    },
+];
+
+/// Generates the rust code of the spec as a standalone crate, whatever the layout of the config says
+/// about `standalone_crate`: a `Cargo.toml` and the module tree of [spec_to_files] in `src`,
+/// with the root module in `lib.rs`.
+///
+/// The dependencies of the `Cargo.toml` are the crates used by the paths of the generated code,
+/// its imports included, with the features that the code needs, like the `stream` feature of reqwest
+/// only when a response is streamed.
+/// The name and the version of the package are the ones of the config, or else the ones of the info of the spec.
+pub fn spec_to_crate(
+    config: &models::Config,
+    spec: oas3::Spec,
+) -> Result<Vec<GeneratedFile>, GeneratingError> {
+    let name = match &config.package.name {
+        Some(name) => name.clone(),
+        None => package_name(&spec.info.title),
+    };
+    let version = match &config.package.version {
+        Some(version) => version.clone(),
+        None => package_version(&spec.info.version),
+    };
+
+    let mut files: Vec<_> = spec_to_files(config, spec)?
+        .into_iter()
+        .map(|file| GeneratedFile {
+            path: match file.path == Path::new("mod.rs") {
+                true => PathBuf::from("src").join("lib.rs"),
+                false => PathBuf::from("src").join(file.path),
+            },
+            content: file.content,
+        })
+        .collect();
+
+    let mut used = UsedNames::default();
+    for file in &files {
+        let tokens = syn::parse_str::<TokenStream>(&file.content)?;
+        collect_used_names(tokens, &mut used);
+    }
+    files.insert(
+        0,
+        GeneratedFile {
+            path: PathBuf::from("Cargo.toml"),
+            content: cargo_toml(&name, &version, &used),
+        },
+    );
+    Ok(files)
+}
+
+/// UsedNames are the names used by the generated code.
+#[derive(Default)]
+struct UsedNames {
+    /// The first segments of the paths, like `chrono` for `chrono::Utc::now()`
+    paths: BTreeSet<String>,
+    /// All the identifiers, like `now` for `chrono::Utc::now()`
+    identifiers: BTreeSet<String>,
+}
+
+/// Renders the `Cargo.toml` of the crate, with the dependencies whose path is used by the code.
+fn cargo_toml(name: &str, version: &str, used: &UsedNames) -> String {
+    let mut output = format!(
+        "[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \"2024\"\n\n[dependencies]\n"
+    );
+    for dependency in DEPENDENCIES.iter().filter(|d| used.paths.contains(d.path)) {
+        let features: Vec<_> = dependency
+            .features
+            .iter()
+            .chain(
+                dependency
+                    .used_features
+                    .iter()
+                    .filter(|(_, identifier)| used.identifiers.contains(*identifier))
+                    .map(|(feature, _)| feature),
+            )
+            .map(|f| format!("\"{f}\""))
+            .collect();
+        let line = match features.is_empty() {
+            true => format!("{} = \"{}\"\n", dependency.name, dependency.version),
+            false => format!(
+                "{} = {{ version = \"{}\", features = [{}] }}\n",
+                dependency.name,
+                dependency.version,
+                features.join(", ")
+            ),
+        };
+        output.push_str(&line);
+    }
+    output
+}
+
+/// Adds to `used` the identifiers of the tokens and the first segment of their paths,
+/// like `chrono` for `chrono::Utc::now()` or `serde` for `use serde::{Deserialize, Serialize};`.
+fn collect_used_names(tokens: TokenStream, used: &mut UsedNames) {
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) => collect_used_names(group.stream(), used),
+            TokenTree::Ident(ident) => {
+                used.identifiers.insert(ident.to_string());
+                let is_followed_by_colons = matches!(
+                    tokens.get(i + 1),
+                    Some(TokenTree::Punct(p)) if p.as_char() == ':' && p.spacing() == Spacing::Joint
+                );
+                // a single colon is the one of a field or of a bound, so the path starts after it
+                let is_after_colons = i >= 2
+                    && matches!(
+                        &tokens[i - 2],
+                        TokenTree::Punct(p) if p.as_char() == ':' && p.spacing() == Spacing::Joint
+                    )
+                    && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == ':');
+                if is_followed_by_colons && !is_after_colons {
+                    used.paths.insert(ident.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the name of the package for the title of a spec, in kebab case with only
+/// the characters that cargo accepts, and starting with a letter.
+fn package_name(title: &str) -> String {
+    let name = title
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => ' ',
+        })
+        .collect::<String>()
+        .to_case(Case::Kebab);
+    match name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        true => name,
+        false if name.is_empty() => "api".to_string(),
+        false => format!("api-{name}"),
+    }
+}
+
+/// Returns the version of the package for the version of a spec, that is often not a semantic version:
+/// the leading `v` is dropped and the missing minor and patch are zero, like `1.0.0` for `v1`.
+/// The pre-release and the build are kept only for complete versions, like `1.2.3-beta.1`,
+/// and the versions that are not numbers become `0.1.0`.
+fn package_version(version: &str) -> String {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let (core, suffix) = match version.find(['-', '+']) {
+        Some(i) => version.split_at(i),
+        None => (version, ""),
+    };
+    let mut parts: Vec<_> = core.split('.').collect();
+    let is_numeric = parts.len() <= 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    if !is_numeric {
+        return "0.1.0".to_string();
+    }
+    let suffix = match parts.len() {
+        3 => suffix,
+        _ => "",
+    };
+    parts.resize(3, "0");
+    format!("{}{suffix}", parts.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UsedNames, cargo_toml, collect_used_names, package_name, package_version};
+    use insta::assert_snapshot;
+    use quote::quote;
+    use rstest::rstest;
+    use std::collections::BTreeSet;
+
+    #[rstest]
+    #[case("Swagger Petstore", "swagger-petstore")]
+    #[case("Pet Store API v2", "pet-store-api-v-2")]
+    #[case("pets_service", "pets-service")]
+    #[case("3D printers", "api-3-d-printers")]
+    #[case("", "api")]
+    fn test_package_name(#[case] title: &str, #[case] expected: &str) {
+        assert_eq!(package_name(title), expected);
+    }
+
+    #[rstest]
+    #[case("1.0.0", "1.0.0")]
+    #[case("1.0", "1.0.0")]
+    #[case("v2", "2.0.0")]
+    #[case("1.2.3-beta.1", "1.2.3-beta.1")]
+    #[case("2024-01-01", "2024.0.0")]
+    #[case("latest", "0.1.0")]
+    #[case("1.2.3.4", "0.1.0")]
+    fn test_package_version(#[case] version: &str, #[case] expected: &str) {
+        assert_eq!(package_version(version), expected);
+    }
+
+    #[test]
+    fn test_collect_crate_paths() {
+        let tokens = quote! {
+            use serde::{Deserialize, Serialize};
+            pub struct Pet {
+                pub born_at: chrono::DateTime<chrono::Utc>,
+                pub tags: std::collections::HashMap<String, String>,
+            }
+            impl Pet {
+                fn kind() -> PetKind { PetKind::Dog }
+                fn body(&self) -> Vec<u8> { serde_json::to_vec(&self.tags).unwrap_or_default() }
+            }
+        };
+        let mut got = UsedNames::default();
+
+        collect_used_names(tokens, &mut got);
+
+        assert_eq!(
+            got.paths,
+            BTreeSet::from(["PetKind", "chrono", "serde", "serde_json", "std"].map(String::from))
+        );
+        assert!(got.identifiers.contains("to_vec"));
+    }
+
+    #[rstest]
+    #[case("models", &["chrono", "serde", "std", "Pet"], &[])]
+    #[case("reqwest", &["reqwest", "serde"], &["send"])]
+    #[case("reqwest stream", &["reqwest", "serde"], &["send", "bytes_stream"])]
+    fn test_cargo_toml(#[case] name: &str, #[case] paths: &[&str], #[case] identifiers: &[&str]) {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_suffix(name);
+        let used = UsedNames {
+            paths: paths.iter().map(ToString::to_string).collect(),
+            identifiers: identifiers.iter().map(ToString::to_string).collect(),
+        };
+
+        settings.bind(|| {
+            assert_snapshot!(cargo_toml("pet-store", "1.0.0", &used));
+        });
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::ConfigError;
 use crate::parsing::errors::ParsingError;
 use std::io;
 use thiserror::Error;
@@ -10,4 +11,34 @@ pub enum GeneratingError {
     ParseSpecError(#[from] ParsingError),
     #[error("could not write to file.")]
     WriteToFileError(#[from] io::Error),
+    #[error("could not read the spec.")]
+    ReadSpecError(#[source] io::Error),
+    #[error("the spec is not a valid OpenAPI document.")]
+    DeserializeSpecError(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    ConfigError(#[from] ConfigError),
+    #[error("could not read the generated file.")]
+    ReadGeneratedFileError(#[source] io::Error),
+    #[error("could not turn the lint issues to JSON.")]
+    SerializeLintError(#[source] serde_json::Error),
+    #[error("the builder has no spec.")]
+    MissingSpecError,
+    #[error(
+        "the OUT_DIR environment variable is not set, the builder has to run in a build script."
+    )]
+    MissingOutDirError,
+}
+
+impl GeneratingError {
+    /// Returns true if the error comes from the file system rather than from the spec,
+    /// like a spec or a config that cannot be read or an output that cannot be written.
+    pub fn is_io(&self) -> bool {
+        matches!(
+            self,
+            Self::WriteToFileError(_)
+                | Self::ReadSpecError(_)
+                | Self::ReadGeneratedFileError(_)
+                | Self::ConfigError(ConfigError::ReadConfigError(..))
+        )
+    }
 }
@@ -0,0 +1,285 @@
+use crate::generating::crates::spec_to_crate;
+use crate::generating::errors::GeneratingError;
+use crate::generating::modules::spec_to_files;
+use crate::generating::strings::spec_to_rust;
+use crate::models;
+use std::path::{Path, PathBuf};
+
+/// Reads an OpenAPI spec from a file, either in YAML or in JSON since JSON is also valid YAML.
+pub fn read_spec(path: &Path) -> Result<oas3::Spec, GeneratingError> {
+    let content = std::fs::read_to_string(path).map_err(GeneratingError::ReadSpecError)?;
+    Ok(oas3::from_yaml(content)?)
+}
+
+/// StaleFile is a generated file whose content on disk is not the generated code,
+/// or a file left by a previous generation that the current one does not produce anymore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleFile {
+    /// The path of the file
+    pub path: PathBuf,
+    /// The content of the file on disk, if it exists
+    pub current: Option<String>,
+    /// The generated code that the file should have, or None if the file should be removed
+    pub generated: Option<String>,
+}
+
+impl StaleFile {
+    /// Returns the unified diff from the content on disk to the generated code.
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        similar::TextDiff::from_lines(
+            self.current.as_deref().unwrap_or_default(),
+            self.generated.as_deref().unwrap_or_default(),
+        )
+        .unified_diff()
+        .header(&path, &path)
+        .to_string()
+    }
+}
+
+/// Generates the rust code of the spec and writes it to the output path of the config,
+/// either as a single file, as a module tree or as a standalone crate in the directory
+/// at the output path, depending on the layout. The missing directories are created.
+///
+/// The files are left untouched if they already have the generated code, so that their modification
+/// time does not make cargo build again the crate that includes them.
+/// The files written by a previous generation that the current one does not produce anymore, like the one
+/// of a removed schema, are removed together with the directories they leave empty. They are found
+/// in the `.oapigen-manifest` written next to the generated files, so that no other file is ever removed.
+/// Returns true if any file has been written or removed.
+pub fn write_spec(config: &models::Config, spec: oas3::Spec) -> Result<bool, GeneratingError> {
+    let files = generated_files(config, spec)?;
+    let mut written = false;
+    for (path, content) in &files {
+        written |= write_if_changed(path, content)?;
+    }
+    for path in leftover_files(config, &files)? {
+        std::fs::remove_file(&path)?;
+        remove_empty_parents(&path, &config.output_path);
+        written = true;
+    }
+    if let Some(path) = manifest_path(config) {
+        written |= write_if_changed(&path, &manifest(config, &files))?;
+    }
+    Ok(written)
+}
+
+/// Generates the rust code of the spec and compares it with the files at the output path of the config,
+/// like [write_spec] would write them, without writing anything. Returns the files that are stale,
+/// the ones that [write_spec] would remove included.
+pub fn check_spec(
+    config: &models::Config,
+    spec: oas3::Spec,
+) -> Result<Vec<StaleFile>, GeneratingError> {
+    let files = generated_files(config, spec)?;
+    let mut stale_files = Vec::new();
+    for path in leftover_files(config, &files)? {
+        let current =
+            std::fs::read_to_string(&path).map_err(GeneratingError::ReadGeneratedFileError)?;
+        stale_files.push(StaleFile {
+            path,
+            current: Some(current),
+            generated: None,
+        });
+    }
+    for (path, generated) in files {
+        let current = match std::fs::read_to_string(&path) {
+            Ok(current) => Some(current),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(GeneratingError::ReadGeneratedFileError(e)),
+        };
+        if current.as_ref() != Some(&generated) {
+            stale_files.push(StaleFile {
+                path,
+                current,
+                generated: Some(generated),
+            });
+        }
+    }
+    stale_files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stale_files)
+}
+
+/// Returns the files of the generated code with their paths, following the layout of the config.
+fn generated_files(
+    config: &models::Config,
+    spec: oas3::Spec,
+) -> Result<Vec<(PathBuf, String)>, GeneratingError> {
+    let files = match (config.layout.standalone_crate, config.layout.modules) {
+        (true, _) => spec_to_crate(config, spec)?,
+        (false, true) => spec_to_files(config, spec)?,
+        (false, false) => {
+            return Ok(vec![(
+                config.output_path.clone(),
+                spec_to_rust(config, spec)?,
+            )]);
+        }
+    };
+    Ok(files
+        .into_iter()
+        .map(|file| (config.output_path.join(file.path), file.content))
+        .collect())
+}
+
+/// The name of the file, in the output directory of a module tree or a crate, that lists the files
+/// written by the last generation, so that the next one knows which of them it can remove.
+pub(crate) const MANIFEST: &str = ".oapigen-manifest";
+
+/// Returns the path of the manifest of the generated files, or None for a single file,
+/// whose path is the only one it can have.
+fn manifest_path(config: &models::Config) -> Option<PathBuf> {
+    match config.layout.standalone_crate || config.layout.modules {
+        true => Some(config.output_path.join(MANIFEST)),
+        false => None,
+    }
+}
+
+/// Returns the manifest listing the generated files by their paths relative to the output path, one per line.
+fn manifest(config: &models::Config, files: &[(PathBuf, String)]) -> String {
+    files
+        .iter()
+        .filter_map(|(path, _)| path.strip_prefix(&config.output_path).ok())
+        .map(|path| format!("{}\n", path.display()))
+        .collect()
+}
+
+/// Returns the files listed in the manifest of the previous generation that are not among the generated files
+/// and still exist, sorted by path. Only the files of the manifest are returned, so that the files
+/// written by hand next to the generated ones are never removed. The entries that would leave
+/// the output path are ignored.
+fn leftover_files(
+    config: &models::Config,
+    files: &[(PathBuf, String)],
+) -> Result<Vec<PathBuf>, GeneratingError> {
+    let Some(manifest) = manifest_path(config) else {
+        return Ok(Vec::new());
+    };
+    let previous = match std::fs::read_to_string(manifest) {
+        Ok(previous) => previous,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(GeneratingError::ReadGeneratedFileError(e)),
+    };
+
+    let mut leftovers: Vec<_> = previous
+        .lines()
+        .map(Path::new)
+        .filter(|path| {
+            path.components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)))
+        })
+        .map(|path| config.output_path.join(path))
+        .filter(|path| !files.iter().any(|(generated, _)| generated == path))
+        .filter(|path| path.is_file())
+        .collect();
+    leftovers.sort();
+    leftovers.dedup();
+    Ok(leftovers)
+}
+
+/// Removes the directories between the removed file and the root that are left empty.
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut parent = path.parent();
+    while let Some(directory) = parent.filter(|d| d.starts_with(root) && *d != root) {
+        // a directory that is not empty is not removed, and neither are its parents
+        if std::fs::remove_dir(directory).is_err() {
+            break;
+        }
+        parent = directory.parent();
+    }
+}
+
+/// Writes the content to the file unless it already has it, creating its directories if needed.
+/// Returns true if the file has been written.
+pub(crate) fn write_if_changed(path: &Path, content: &str) -> std::io::Result<bool> {
+    match std::fs::read(path) {
+        Ok(current) if current == content.as_bytes() => return Ok(false),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MANIFEST, check_spec, read_spec, write_if_changed, write_spec};
+    use crate::models;
+    use std::path::Path;
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = std::env::temp_dir().join(format!("oapigen_files_{}", std::process::id()));
+        let path = dir.join("nested").join("api.rs");
+
+        let created = write_if_changed(&path, "pub struct Pet;").unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let unchanged = write_if_changed(&path, "pub struct Pet;").unwrap();
+        let unchanged_modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let changed = write_if_changed(&path, "pub struct Owner;").unwrap();
+        let got = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(created);
+        assert!(!unchanged);
+        assert_eq!(modified, unchanged_modified);
+        assert!(changed);
+        assert_eq!(got, "pub struct Owner;");
+    }
+
+    #[test]
+    fn test_write_spec_removes_leftovers() {
+        let dir = std::env::temp_dir().join(format!("oapigen_leftovers_{}", std::process::id()));
+        let config = models::Config {
+            output_path: dir.clone(),
+            layout: models::Layout {
+                modules: true,
+                file_per_schema: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let spec = || read_spec(Path::new("fixtures/modules.yaml")).unwrap();
+        write_spec(&config, spec()).unwrap();
+        // a schema generated by a previous run, listed in its manifest
+        let removed_schema = dir.join("models").join("removed").join("schema.rs");
+        std::fs::create_dir_all(removed_schema.parent().unwrap()).unwrap();
+        std::fs::write(&removed_schema, "pub struct Removed;").unwrap();
+        let manifest = dir.join(MANIFEST);
+        let mut listed = std::fs::read_to_string(&manifest).unwrap();
+        listed.push_str("models/removed/schema.rs\n../outside.rs\n");
+        std::fs::write(&manifest, listed).unwrap();
+        // files written by hand next to the generated ones
+        let main = dir.join("main.rs");
+        std::fs::write(&main, "fn main() {}").unwrap();
+        let handlers = dir.join("handlers").join("mod.rs");
+        std::fs::create_dir_all(handlers.parent().unwrap()).unwrap();
+        std::fs::write(&handlers, "pub fn handle() {}").unwrap();
+        let notes = dir.join("NOTES.md");
+        std::fs::write(&notes, "not generated").unwrap();
+
+        let stale = check_spec(&config, spec()).unwrap();
+        let written = write_spec(&config, spec()).unwrap();
+        let after_write = check_spec(&config, spec()).unwrap();
+        let removed_dir_exists = removed_schema.parent().unwrap().exists();
+        let notes_exist = notes.exists();
+        let handwritten_exist = main.exists() && handlers.exists();
+        let listed_after_write = std::fs::read_to_string(&manifest).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, removed_schema);
+        assert_eq!(stale[0].generated, None);
+        assert!(stale[0].diff().contains("-pub struct Removed;"));
+        assert!(written);
+        assert!(after_write.is_empty());
+        assert!(!removed_dir_exists);
+        assert!(notes_exist);
+        assert!(handwritten_exist);
+        assert!(!listed_after_write.contains("removed"));
+    }
+}
@@ -1,45 +1,318 @@
+use crate::generating::{client, routers, runtime, server, servers, transports, validation};
 use crate::models;
 use crate::models::schema;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-pub fn merge_schemas(schemas: Vec<schema::SchemaAsRust>) -> TokenStream {
+/// merge_spec merges everything that was inferred from a spec in a single [TokenStream].
+/// The types used by the operations are merged together with the ones from the components,
+/// and the items shared by all the operations are added only once.
+/// The targets selected in the config, like the client, are added after the models.
+pub fn merge_spec(config: &models::Config, spec: &models::SpecAsRust) -> TokenStream {
+    merge_parts(config, spec).into_token_stream()
+}
+
+/// MergedSpec holds the parts of the code of a spec, that are either merged in this order
+/// in a single file or split in the modules of the module tree.
+pub(crate) struct MergedSpec<'a> {
+    /// The imports used by the models, the headers and the parameters
+    pub imports: TokenStream,
+    /// The models, each one together with the schema it comes from
+    pub models: Vec<(&'a schema::SchemaAsRust, TokenStream)>,
+    /// The items shared by the operations and the targets
+    pub runtime: TokenStream,
+    /// The types of the operations, each one together with its operation
+    pub operations: Vec<(&'a models::OperationAsRust, TokenStream)>,
+    /// The servers, the async client and its transport adapters
+    pub client: TokenStream,
+    /// The `blocking` module with the blocking client
+    pub blocking: TokenStream,
+    /// The validations of the types received by the server
+    pub validation: TokenStream,
+    /// The `server` module with the server trait and its router
+    pub server: TokenStream,
+}
+
+impl ToTokens for MergedSpec<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.imports.clone());
+        for (_, model) in &self.models {
+            tokens.extend(model.clone());
+        }
+        tokens.extend(self.runtime.clone());
+        for (_, operation) in &self.operations {
+            tokens.extend(operation.clone());
+        }
+        tokens.extend(self.client.clone());
+        tokens.extend(self.blocking.clone());
+        tokens.extend(self.validation.clone());
+        tokens.extend(self.server.clone());
+    }
+}
+
+/// merge_parts renders the parts of the code of a spec, see [merge_spec].
+pub(crate) fn merge_parts<'a>(
+    config: &models::Config,
+    spec: &'a models::SpecAsRust,
+) -> MergedSpec<'a> {
+    let bodies: Vec<_> = spec
+        .operations
+        .iter()
+        .flat_map(|o| {
+            let request_body = o.request_body.as_ref().map(|r| &r.body);
+            request_body
+                .into_iter()
+                .chain(o.responses.iter().flat_map(|r| &r.body))
+        })
+        .collect();
+    let operation_schemas = bodies.iter().flat_map(|b| &b.content).map(|c| &c.schema);
+    let schemas: Vec<_> = spec.schemas.iter().chain(operation_schemas).collect();
+    let headers: Vec<_> = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.responses)
+        .flat_map(|r| &r.headers)
+        .collect();
+    let part_headers: Vec<_> = bodies
+        .iter()
+        .flat_map(|b| &b.content)
+        .flat_map(|c| match &c.kind {
+            models::PayloadKind::Multipart(parts) => parts.as_slice(),
+            _ => &[],
+        })
+        .flat_map(|p| &p.headers)
+        .collect();
+
+    let parameters = spec.operations.iter().flat_map(|o| &o.parameters);
+
+    let imports = schemas
+        .iter()
+        .map(|schema| &schema.imports)
+        .chain(headers.iter().map(|header| &header.imports))
+        .chain(parameters.map(|parameter| &parameter.imports))
+        .collect();
+    let mut merged = MergedSpec {
+        imports: imports_to_tokens(merge_imports(imports)),
+        models: unique_types(schemas.clone())
+            .into_iter()
+            .map(|s| (s, s.to_token_stream()))
+            .collect(),
+        runtime: TokenStream::new(),
+        operations: Vec::new(),
+        client: TokenStream::new(),
+        blocking: TokenStream::new(),
+        validation: TokenStream::new(),
+        server: TokenStream::new(),
+    };
+    let output = &mut merged.runtime;
+
+    let has_responses = spec.operations.iter().any(|o| !o.responses.is_empty());
+    let targets = &config.targets;
+    let streams = |kind: models::PayloadKind| {
+        bodies
+            .iter()
+            .flat_map(|b| &b.content)
+            .any(|c| c.kind == kind)
+    };
+    let has_lines = streams(models::PayloadKind::JsonLines);
+    let has_events = streams(models::PayloadKind::EventStream);
+    if has_responses || targets.client || targets.blocking_client || targets.server {
+        output.extend(runtime::response_error(
+            has_lines || has_events,
+            !headers.is_empty(),
+        ));
+    }
+    if !headers.is_empty() || !part_headers.is_empty() {
+        output.extend(runtime::header_error());
+    }
+    // the server decodes the request bodies too
+    let has_many_schemas = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.responses)
+        .flat_map(|r| &r.body)
+        .chain(match targets.server {
+            true => request_bodies(spec),
+            false => Vec::new(),
+        })
+        .any(|b| b.content.len() > 1);
+    if has_many_schemas {
+        output.extend(runtime::media_type_matches());
+    }
+    let has_multipart = bodies
+        .iter()
+        .flat_map(|b| &b.content)
+        .any(|c| matches!(c.kind, models::PayloadKind::Multipart(_)));
+    if has_multipart {
+        output.extend(runtime::multipart());
+    }
+    let has_raw = bodies
+        .iter()
+        .flat_map(|b| &b.content)
+        .any(|c| c.kind == models::PayloadKind::Raw);
+    if has_raw {
+        output.extend(runtime::raw_payload());
+    }
+    if has_lines || has_events {
+        output.extend(runtime::streaming(has_lines, has_events));
+    }
+    // the client encodes the request bodies, the server the response bodies
+    let mut encoded_bodies = Vec::new();
+    if targets.client || targets.blocking_client {
+        encoded_bodies.extend(request_bodies(spec));
+    }
+    if targets.server {
+        encoded_bodies.extend(
+            spec.operations
+                .iter()
+                .flat_map(|o| &o.responses)
+                .flat_map(|r| &r.body),
+        );
+    }
+    let encodes_multipart = encoded_bodies
+        .iter()
+        .flat_map(|b| &b.content)
+        .any(|c| matches!(c.kind, models::PayloadKind::Multipart(_)));
+    if encodes_multipart {
+        output.extend(runtime::encode_multipart());
+    }
+    // the responses are decoded by their enums whatever the targets, the request bodies by the server
+    let mut decoded_bodies: Vec<_> = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.responses)
+        .flat_map(|r| &r.body)
+        .collect();
+    if targets.server {
+        decoded_bodies.extend(request_bodies(spec));
+    }
+    let decodes_multipart = decoded_bodies
+        .iter()
+        .flat_map(|b| &b.content)
+        .any(|c| matches!(c.kind, models::PayloadKind::Multipart(_)));
+    if decodes_multipart {
+        output.extend(runtime::decode_multipart());
+    }
+    merged.operations = spec
+        .operations
+        .iter()
+        .map(|o| (o, o.to_token_stream()))
+        .collect();
+
+    if targets.client || targets.blocking_client {
+        merged.client.extend(servers::servers(spec));
+        merged.client.extend(client::client_runtime(spec));
+    }
+    if targets.client {
+        merged.client.extend(client::async_client(spec));
+        for library in &config.libraries.transports {
+            merged.client.extend(transports::async_adapter(
+                library,
+                client::has_streaming(spec),
+            ));
+        }
+    }
+    if targets.blocking_client {
+        let adapters = config
+            .libraries
+            .transports
+            .iter()
+            .map(|library| transports::blocking_adapter(library, client::has_streaming(spec)))
+            .collect();
+        merged.blocking = client::blocking_client(spec, adapters);
+    }
+    if targets.server {
+        merged.validation = merge_validations(spec, &schemas);
+        let router = routers::router(&config.libraries.server, spec);
+        merged.server = server::server(spec, router);
+    }
+    merged
+}
+
+/// merge_validations renders the validation of the types that can be received by the server:
+/// the models, the parameters structs and the request bodies with many contents.
+/// The models are validated only once, like in [merge_types].
+fn merge_validations(spec: &models::SpecAsRust, schemas: &[&schema::SchemaAsRust]) -> TokenStream {
+    let mut output = validation::validation_runtime();
+    for schema in unique_types(schemas.to_vec()) {
+        output.extend(validation::tokenize_validate_schema(schema));
+    }
+    for operation in spec.operations.iter().filter(|o| !o.parameters.is_empty()) {
+        output.extend(validation::tokenize_validate_parameters(operation));
+    }
+    for body in request_bodies(spec)
+        .into_iter()
+        .filter(|b| b.content.len() > 1)
+    {
+        output.extend(validation::tokenize_validate_body(body));
+    }
+    output
+}
+
+fn request_bodies(spec: &models::SpecAsRust) -> Vec<&models::BodyAsRust> {
+    spec.operations
+        .iter()
+        .flat_map(|o| &o.request_body)
+        .map(|r| &r.body)
+        .collect()
+}
+
+pub fn merge_schemas(schemas: Vec<&schema::SchemaAsRust>) -> TokenStream {
     let imports: Vec<_> = schemas.iter().map(|schema| &schema.imports).collect();
 
-    let unique_types: HashSet<_> = HashSet::from_iter(schemas.iter());
-    let types = unique_types.iter().fold(TokenStream::new(), |mut acc, s| {
-        acc.extend(s.to_token_stream());
-        acc
-    });
+    let mut output = imports_to_tokens(merge_imports(imports));
+    output.extend(merge_types(schemas));
+    output
+}
+
+/// merge_types renders the types in the order in which they are passed,
+/// adding only once the ones that are identical.
+fn merge_types(schemas: Vec<&schema::SchemaAsRust>) -> TokenStream {
+    unique_types(schemas)
+        .into_iter()
+        .fold(TokenStream::new(), |mut acc, s| {
+            acc.extend(s.to_token_stream());
+            acc
+        })
+}
 
-    let merged_imports = merge_imports(imports);
-    let mut output = match merged_imports.parse::<TokenStream>() {
+/// unique_types keeps the types in the order in which they are passed, dropping the ones
+/// identical to a previous one, so that the output does not depend on the order of a hash.
+fn unique_types(schemas: Vec<&schema::SchemaAsRust>) -> Vec<&schema::SchemaAsRust> {
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    schemas
+        .into_iter()
+        .filter(|s| seen.insert(s.to_token_stream().to_string()))
+        .collect()
+}
+
+fn imports_to_tokens(merged_imports: String) -> TokenStream {
+    match merged_imports.parse::<TokenStream>() {
         Ok(tokens) => tokens,
         Err(e) => {
             panic!("{}", format!("cannot turn imports to tokens: {}", e))
         }
-    };
-    output.extend(types);
-    output
+    }
 }
 
 /// merge_imports merges the [TokenStream] that represent the imports used by the models.
 /// So for examples if we have two models that use `use chrono::DateTime;` the resulting
 /// [TokenStream] will contain only one mention of `use chrono::DateTime;`.
+/// The imports are sorted by the path they import, so that they are always in the same order.
 fn merge_imports(all_imports: Vec<&models::Imports>) -> String {
-    let mut seen: HashSet<String> = HashSet::new();
-    let mut output = "".to_string();
+    let mut merged: BTreeMap<&String, &String> = BTreeMap::new();
     for imports in all_imports {
         for (import_as_string, actual_import) in imports {
-            if !seen.contains(import_as_string) {
-                seen.insert(import_as_string.clone());
-                output = vec![output, actual_import.clone()].join("\n");
-            }
+            merged.entry(import_as_string).or_insert(actual_import);
         }
     }
 
-    output
+    merged
+        .into_values()
+        .fold("".to_string(), |output, actual_import| {
+            vec![output, actual_import.clone()].join("\n")
+        })
 }
 
 #[cfg(test)]
@@ -79,14 +352,16 @@ mod tests {
         let schema1 = schema::SchemaAsRust {
             name: "user_time".to_string(),
             rust_type: "chrono::DateTime<chrono::Utc>".to_string(),
-            macros: HashSet::from(["#[derive(Serialize, Deserialize, Debug)]".to_string()]),
+            macros: BTreeSet::from(["#[derive(Serialize, Deserialize, Debug)]".to_string()]),
             comment: None,
             is_optional: false,
             imports: imports_1,
+            fields: vec![],
+            constraints: models::ConstraintsAsRust::default(),
             current_type: CurrentType::Type,
         };
 
-        let schemas = vec![schema1];
+        let schemas = vec![&schema1];
         let got = merge_schemas(schemas);
 
         insta::assert_snapshot!(got.to_string());
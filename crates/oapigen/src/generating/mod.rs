@@ -1,5 +1,23 @@
+mod builder;
+mod client;
+mod crates;
 mod errors;
+mod files;
 pub mod merges;
+mod modules;
+mod pagination;
+mod routers;
+mod runtime;
+mod security;
+mod server;
+mod servers;
 mod strings;
+mod transports;
+mod validation;
 
+pub use builder::Builder;
+pub use crates::spec_to_crate;
+pub use errors::GeneratingError;
+pub use files::{StaleFile, check_spec, read_spec, write_spec};
+pub use modules::{GeneratedFile, spec_to_files};
 pub use strings::spec_to_rust;
@@ -0,0 +1,315 @@
+use crate::generating::errors::GeneratingError;
+use crate::generating::merges::{MergedSpec, merge_parts};
+use crate::models;
+use crate::parsing::specs::parse_specs;
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use std::path::PathBuf;
+
+/// GeneratedFile is one of the files of the module tree of a spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    /// The path of the file, relative to the directory of the module tree
+    pub path: PathBuf,
+    /// The formatted rust code of the file
+    pub content: String,
+}
+
+/// Generates the rust code of the spec as a module tree, whatever the layout of the config says
+/// about `modules`, with the root module in `mod.rs`.
+///
+/// The root module has the imports of the models and re-exports the items of its modules,
+/// so that the code has the same paths it has in the single file of [spec_to_rust](crate::spec_to_rust),
+/// while the `blocking` and `server` modules are public like in the single file.
+/// The items that are private in the single file are visible to the whole crate instead,
+/// since they are shared by the modules.
+pub fn spec_to_files(
+    config: &models::Config,
+    spec: oas3::Spec,
+) -> Result<Vec<GeneratedFile>, GeneratingError> {
+    let parsed_spec = parse_specs(config, spec)?;
+    let merged = merge_parts(config, &parsed_spec);
+    let root = module_tree(&config.layout, merged)?;
+
+    let mut files = Vec::new();
+    root.render(PathBuf::new(), true, &mut files);
+    Ok(files)
+}
+
+/// Module is one of the modules of the tree, with its own items and its child modules.
+struct Module {
+    name: String,
+    items: Vec<syn::Item>,
+    children: Vec<Module>,
+    /// Is it a public module, rather than a private one whose items are re-exported by its parent?
+    is_public: bool,
+}
+
+impl Module {
+    /// Returns a private module, whose items are re-exported by its parent.
+    fn private(name: &str, items: Vec<syn::Item>) -> Module {
+        Module {
+            name: name.to_string(),
+            items,
+            children: Vec::new(),
+            is_public: false,
+        }
+    }
+
+    /// Returns a public module with the items of an inline module, like `pub mod server { .. }`.
+    fn public(name: &str, tokens: TokenStream) -> Result<Module, GeneratingError> {
+        let items = match syn::parse2::<syn::ItemMod>(tokens)?.content {
+            Some((_, items)) => items,
+            None => Vec::new(),
+        };
+        Ok(Module {
+            name: name.to_string(),
+            items,
+            children: Vec::new(),
+            is_public: true,
+        })
+    }
+
+    /// Adds the items to the private child module with the given name, creating it if needed.
+    fn extend_child(&mut self, name: String, items: Vec<syn::Item>) {
+        match self.children.iter_mut().find(|c| c.name == name) {
+            Some(child) => child.items.extend(items),
+            None => self.children.push(Module::private(&name, items)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.children.iter().all(Module::is_empty)
+    }
+
+    /// Has the module public items, so that its parent has to re-export them publicly?
+    fn has_public_items(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(visibility(item), Some(syn::Visibility::Public(_))))
+            || self
+                .children
+                .iter()
+                .any(|c| !c.is_public && c.has_public_items())
+    }
+
+    /// Renders the module in `directory`, in `mod.rs` if it is the root or if it has children.
+    fn render(self, directory: PathBuf, is_root: bool, files: &mut Vec<GeneratedFile>) {
+        let children: Vec<_> = self
+            .children
+            .into_iter()
+            .filter(|c| !c.is_empty())
+            .collect();
+        let (path, children_directory) = match (is_root, children.is_empty()) {
+            (true, _) => (directory.join("mod.rs"), directory),
+            (false, false) => {
+                let directory = directory.join(&self.name);
+                (directory.join("mod.rs"), directory)
+            }
+            (false, true) => (directory.join(format!("{}.rs", self.name)), directory),
+        };
+
+        let mut items = Vec::new();
+        // the items of the parent are in scope, as they are in the single file
+        if !is_root && !self.is_public {
+            items.push(item("#[allow(unused_imports)] use super::*;"));
+        }
+        let (imports, own_items): (Vec<_>, Vec<_>) = self
+            .items
+            .into_iter()
+            .partition(|item| matches!(item, syn::Item::Use(_)));
+        items.extend(imports);
+        for child in &children {
+            match child.is_public {
+                true => items.push(item(&format!("pub mod {};", child.name))),
+                false => {
+                    let visibility = match child.has_public_items() {
+                        true => "pub",
+                        false => "pub(crate)",
+                    };
+                    items.push(item(&format!("mod {};", child.name)));
+                    items.push(item(&format!("{visibility} use {}::*;", child.name)));
+                }
+            }
+        }
+        items.extend(own_items);
+
+        let file = syn::File {
+            shebang: None,
+            attrs: Vec::new(),
+            items,
+        };
+        files.push(GeneratedFile {
+            path,
+            content: prettyplease::unparse(&file),
+        });
+        for child in children {
+            child.render(children_directory.clone(), false, files);
+        }
+    }
+}
+
+/// Splits the parts of the code of a spec in the modules of the tree, following the layout.
+fn module_tree(layout: &models::Layout, merged: MergedSpec) -> Result<Module, GeneratingError> {
+    let mut root = Module {
+        name: String::new(),
+        items: syn::parse2::<syn::File>(merged.imports)?.items,
+        children: Vec::new(),
+        is_public: true,
+    };
+
+    let mut models = Module::private("models", Vec::new());
+    for (schema, tokens) in merged.models {
+        let items = exposed_items(tokens)?;
+        match layout.file_per_schema {
+            true => models.extend_child(module_name(&schema.name), items),
+            false => models.items.extend(items),
+        }
+    }
+    root.children.push(models);
+
+    let runtime = exposed_items(merged.runtime)?;
+    root.children.push(Module::private("runtime", runtime));
+
+    let mut operations = Module::private("operations", Vec::new());
+    for (operation, tokens) in merged.operations {
+        let items = exposed_items(tokens)?;
+        match (layout.file_per_tag, operation.tags.first()) {
+            (true, Some(tag)) => operations.extend_child(module_name(tag), items),
+            _ => operations.items.extend(items),
+        }
+    }
+    root.children.push(operations);
+
+    let client = exposed_items(merged.client)?;
+    root.children.push(Module::private("client", client));
+    if !merged.blocking.is_empty() {
+        root.children
+            .push(Module::public("blocking", merged.blocking)?);
+    }
+    let validation = exposed_items(merged.validation)?;
+    root.children
+        .push(Module::private("validation", validation));
+    if !merged.server.is_empty() {
+        root.children.push(Module::public("server", merged.server)?);
+    }
+    Ok(root)
+}
+
+/// Returns the items of the tokens, made visible to the crate so that the other modules can use them.
+fn exposed_items(tokens: TokenStream) -> Result<Vec<syn::Item>, GeneratingError> {
+    let mut items = syn::parse2::<syn::File>(tokens)?.items;
+    items.iter_mut().for_each(expose);
+    Ok(items)
+}
+
+/// Returns the name of the module of a schema or of a tag, that is also the stem of its file.
+/// Keywords get a trailing underscore, since the raw identifiers are not valid file names.
+fn module_name(name: &str) -> String {
+    let name: String = name
+        .to_case(Case::Snake)
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    let name = match name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        true => name,
+        false => format!("_{name}"),
+    };
+    match syn::parse_str::<syn::Ident>(&name) {
+        Ok(_) => name,
+        Err(_) => format!("{name}_"),
+    }
+}
+
+/// Makes the private items visible to the crate, together with the private fields of the structs
+/// and the private functions of their implementations, since they are shared between the modules.
+fn expose(item: &mut syn::Item) {
+    match item {
+        syn::Item::Struct(item) => {
+            for field in item.fields.iter_mut() {
+                expose_visibility(&mut field.vis);
+            }
+        }
+        syn::Item::Impl(item) if item.trait_.is_none() => {
+            for impl_item in item.items.iter_mut() {
+                match impl_item {
+                    syn::ImplItem::Const(i) => expose_visibility(&mut i.vis),
+                    syn::ImplItem::Fn(i) => expose_visibility(&mut i.vis),
+                    syn::ImplItem::Type(i) => expose_visibility(&mut i.vis),
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(vis) = visibility_mut(item) {
+        expose_visibility(vis);
+    }
+}
+
+fn expose_visibility(vis: &mut syn::Visibility) {
+    if matches!(vis, syn::Visibility::Inherited) {
+        *vis = match syn::parse_str("pub(crate)") {
+            Ok(vis) => vis,
+            Err(e) => panic!("{}", format!("cannot turn visibility to tokens: {}", e)),
+        };
+    }
+}
+
+fn visibility(item: &syn::Item) -> Option<&syn::Visibility> {
+    match item {
+        syn::Item::Const(i) => Some(&i.vis),
+        syn::Item::Enum(i) => Some(&i.vis),
+        syn::Item::Fn(i) => Some(&i.vis),
+        syn::Item::Static(i) => Some(&i.vis),
+        syn::Item::Struct(i) => Some(&i.vis),
+        syn::Item::Trait(i) => Some(&i.vis),
+        syn::Item::Type(i) => Some(&i.vis),
+        syn::Item::Union(i) => Some(&i.vis),
+        syn::Item::Mod(i) => Some(&i.vis),
+        _ => None,
+    }
+}
+
+fn visibility_mut(item: &mut syn::Item) -> Option<&mut syn::Visibility> {
+    match item {
+        syn::Item::Const(i) => Some(&mut i.vis),
+        syn::Item::Enum(i) => Some(&mut i.vis),
+        syn::Item::Fn(i) => Some(&mut i.vis),
+        syn::Item::Static(i) => Some(&mut i.vis),
+        syn::Item::Struct(i) => Some(&mut i.vis),
+        syn::Item::Trait(i) => Some(&mut i.vis),
+        syn::Item::Type(i) => Some(&mut i.vis),
+        syn::Item::Union(i) => Some(&mut i.vis),
+        syn::Item::Mod(i) => Some(&mut i.vis),
+        _ => None,
+    }
+}
+
+fn item(code: &str) -> syn::Item {
+    match syn::parse_str(code) {
+        Ok(item) => item,
+        Err(e) => panic!("{}", format!("cannot turn {code} to tokens: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::module_name;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Pet", "pet")]
+    #[case("ListPetsResponse200", "list_pets_response_200")]
+    #[case("pet store", "pet_store")]
+    #[case("pets-v2", "pets_v_2")]
+    #[case("2fa", "_2_fa")]
+    #[case("type", "type_")]
+    #[case("self", "self_")]
+    fn test_module_name(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(module_name(name), expected);
+    }
+}
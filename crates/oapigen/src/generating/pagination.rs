@@ -0,0 +1,514 @@
+//! Generation of the methods of the client that return the items of all the pages of a list operation.
+
+use crate::models;
+use crate::models::{PaginationKind, PaginationParameterAsRust};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Returns true if one of the operations is paginated.
+pub(crate) fn has_pagination(spec: &models::SpecAsRust) -> bool {
+    spec.operations.iter().any(|o| o.pagination.is_some())
+}
+
+/// Returns true if the next page of the operation is found in the `Link` header.
+pub(crate) fn is_link_paginated(operation: &models::OperationAsRust) -> bool {
+    operation
+        .pagination
+        .as_ref()
+        .is_some_and(|p| p.kind == PaginationKind::Link)
+}
+
+/// Returns the name of the method that builds the request of an operation, without sending it.
+pub(crate) fn request_function_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!(
+        "{}_request",
+        operation.method_name().trim_start_matches("r#")
+    )
+}
+
+fn all_function_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!("{}_all", operation.method_name().trim_start_matches("r#"))
+}
+
+fn page_function_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!("{}_page", operation.method_name().trim_start_matches("r#"))
+}
+
+/// Returns the items shared by the paginated methods of the client: the stream of the items
+/// of all the pages for the async client, and an iterator over them for the blocking one.
+pub(crate) fn paginated(is_async: bool) -> TokenStream {
+    match is_async {
+        true => quote! {
+            /// The future that requests a page, returning its items and the future of the next page.
+            type NextPage<'a, T> =
+                std::pin::Pin<Box<dyn std::future::Future<Output = Result<Page<'a, T>, ClientError>> + Send + 'a>>;
+
+            /// The items of a page, together with the request of the next page unless it is the last one.
+            struct Page<'a, T> {
+                items: Vec<T>,
+                next: Option<NextPage<'a, T>>,
+            }
+
+            /// A [futures_core::Stream] of the items of all the pages of a list operation.
+            /// The next page is requested once the items of the previous one are consumed,
+            /// and the stream ends after the first error.
+            pub struct Paginated<'a, T> {
+                items: std::vec::IntoIter<T>,
+                next: Option<NextPage<'a, T>>,
+            }
+
+            impl<'a, T> Paginated<'a, T> {
+                fn new(first: NextPage<'a, T>) -> Self {
+                    Self {
+                        items: Vec::new().into_iter(),
+                        next: Some(first),
+                    }
+                }
+            }
+
+            // the items are never pinned, so they do not need to be `Unpin`
+            impl<T> Unpin for Paginated<'_, T> {}
+
+            impl<T> futures_core::Stream for Paginated<'_, T> {
+                type Item = Result<T, ClientError>;
+
+                fn poll_next(
+                    mut self: std::pin::Pin<&mut Self>,
+                    cx: &mut std::task::Context<'_>,
+                ) -> std::task::Poll<Option<Self::Item>> {
+                    let this = &mut *self;
+                    loop {
+                        if let Some(item) = this.items.next() {
+                            return std::task::Poll::Ready(Some(Ok(item)));
+                        }
+                        let Some(next) = this.next.as_mut() else {
+                            return std::task::Poll::Ready(None);
+                        };
+                        let page = std::task::ready!(next.as_mut().poll(cx));
+                        this.next = None;
+                        match page {
+                            Ok(page) => {
+                                this.items = page.items.into_iter();
+                                this.next = page.next;
+                            }
+                            Err(e) => return std::task::Poll::Ready(Some(Err(e))),
+                        }
+                    }
+                }
+            }
+
+            impl<T> std::fmt::Debug for Paginated<'_, T> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct("Paginated")
+                        .field("items", &self.items.len())
+                        .field("is_last", &self.next.is_none())
+                        .finish()
+                }
+            }
+        },
+        false => quote! {
+            /// The function that requests a page, returning its items and the function of the next page.
+            type NextPage<'a, T> = Box<dyn FnOnce() -> Result<Page<'a, T>, ClientError> + 'a>;
+
+            /// The items of a page, together with the request of the next page unless it is the last one.
+            struct Page<'a, T> {
+                items: Vec<T>,
+                next: Option<NextPage<'a, T>>,
+            }
+
+            /// An [Iterator] over the items of all the pages of a list operation.
+            /// The next page is requested once the items of the previous one are consumed,
+            /// and the iterator ends after the first error.
+            pub struct Paginated<'a, T> {
+                items: std::vec::IntoIter<T>,
+                next: Option<NextPage<'a, T>>,
+            }
+
+            impl<'a, T> Paginated<'a, T> {
+                fn new(first: NextPage<'a, T>) -> Self {
+                    Self {
+                        items: Vec::new().into_iter(),
+                        next: Some(first),
+                    }
+                }
+            }
+
+            impl<T> Iterator for Paginated<'_, T> {
+                type Item = Result<T, ClientError>;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    loop {
+                        if let Some(item) = self.items.next() {
+                            return Some(Ok(item));
+                        }
+                        match self.next.take()?() {
+                            Ok(page) => {
+                                self.items = page.items.into_iter();
+                                self.next = page.next;
+                            }
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+                }
+            }
+
+            impl<T> std::fmt::Debug for Paginated<'_, T> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct("Paginated")
+                        .field("items", &self.items.len())
+                        .field("is_last", &self.next.is_none())
+                        .finish()
+                }
+            }
+        },
+    }
+}
+
+/// Returns the function that finds the URL of the next page in the `Link` header of a response,
+/// with the functions that parse the header and resolve the URL against the one of the page.
+pub(crate) fn next_link() -> TokenStream {
+    quote! {
+        /// Returns the URL of the next page from the `Link` headers of a response,
+        /// like `<https://example.com/pets?page=2>; rel="next"`, resolved against the URL of the page.
+        fn next_link(page_url: &str, headers: &http::HeaderMap) -> Option<String> {
+            let target = headers
+                .get_all("Link")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .find_map(next_link_target)?;
+            Some(resolve_url(page_url, target))
+        }
+
+        /// Returns the target of the link whose relation types include `next` in the value of a `Link` header,
+        /// as defined by RFC 8288: the links are separated by commas, and each one is a URI reference
+        /// between `<` and `>` followed by its parameters separated by semicolons.
+        /// The commas and the semicolons of the URI references and of the quoted values do not separate anything.
+        fn next_link_target(value: &str) -> Option<&str> {
+            split_unquoted(value, ',').into_iter().find_map(|link| {
+                let link = link.trim().strip_prefix('<')?;
+                let end = link.find('>')?;
+                let (target, params) = (&link[..end], &link[end + 1..]);
+                let is_next = split_unquoted(params, ';').into_iter().any(|param| {
+                    let Some((name, rel)) = param.split_once('=') else {
+                        return false;
+                    };
+                    name.trim().eq_ignore_ascii_case("rel")
+                        && rel
+                            .trim()
+                            .trim_matches('"')
+                            .split_whitespace()
+                            .any(|rel| rel.eq_ignore_ascii_case("next"))
+                });
+                is_next.then_some(target)
+            })
+        }
+
+        /// Splits the value at the separators that are neither between `<` and `>` nor between quotes.
+        fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+            let mut parts = Vec::new();
+            let (mut start, mut is_quoted, mut is_bracketed) = (0, false, false);
+            for (i, c) in value.char_indices() {
+                match c {
+                    '"' if !is_bracketed => is_quoted = !is_quoted,
+                    '<' if !is_quoted => is_bracketed = true,
+                    '>' if !is_quoted => is_bracketed = false,
+                    c if c == separator && !is_quoted && !is_bracketed => {
+                        parts.push(&value[start..i]);
+                        start = i + c.len_utf8();
+                    }
+                    _ => {}
+                }
+            }
+            parts.push(&value[start..]);
+            parts
+        }
+
+        /// Resolves a URI reference against the URL of a page as defined by RFC 3986,
+        /// like `https://example.com/v1/pets?page=2` for `pets?page=2` against `https://example.com/v1/owners`.
+        fn resolve_url(base: &str, reference: &str) -> String {
+            let has_scheme = reference.find(':').is_some_and(|i| {
+                let scheme = &reference[..i];
+                scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            });
+            if has_scheme {
+                return reference.to_string();
+            }
+            let base = &base[..base.find('#').unwrap_or(base.len())];
+            if reference.starts_with("//") {
+                let scheme = &base[..base.find(':').map_or(0, |i| i + 1)];
+                return format!("{scheme}{reference}");
+            }
+
+            let origin = origin(base);
+            let path_and_query = &base[origin.len()..];
+            let (base_path, base_query) =
+                path_and_query.split_at(path_and_query.find('?').unwrap_or(path_and_query.len()));
+            let (path, rest) = reference.split_at(reference.find(['?', '#']).unwrap_or(reference.len()));
+            if path.is_empty() {
+                let query = match rest.starts_with('?') {
+                    true => "",
+                    false => base_query,
+                };
+                return format!("{origin}{base_path}{query}{rest}");
+            }
+            let path = match path.starts_with('/') {
+                true => path.to_string(),
+                // the reference replaces the last segment of the path of the base
+                false => match base_path.rfind('/') {
+                    Some(i) => format!("{}{path}", &base_path[..=i]),
+                    None => format!("/{path}"),
+                },
+            };
+            format!("{origin}{}{rest}", remove_dot_segments(&path))
+        }
+
+        /// Removes the `.` and `..` segments of an absolute path, like `/v1/pets` for `/v1/owners/../pets`.
+        fn remove_dot_segments(path: &str) -> String {
+            let parts: Vec<_> = path.split('/').skip(1).collect();
+            let mut segments = Vec::with_capacity(parts.len());
+            for (i, part) in parts.iter().enumerate() {
+                let is_last = i + 1 == parts.len();
+                match *part {
+                    "." => {}
+                    ".." => {
+                        segments.pop();
+                    }
+                    part => segments.push(part),
+                }
+                // a path that ends with a dot segment is a directory
+                if is_last && matches!(*part, "." | "..") {
+                    segments.push("");
+                }
+            }
+            format!("/{}", segments.join("/"))
+        }
+
+        /// Returns the scheme and the authority of a URL, like `https://example.com`.
+        fn origin(url: &str) -> &str {
+            let host = url.find("://").map_or(0, |i| i + 3);
+            let end = url[host..].find(['/', '?', '#']).map_or(url.len(), |i| host + i);
+            &url[..end]
+        }
+    }
+}
+
+/// Renders the methods of the client that return the items of all the pages of an operation,
+/// starting from the page selected by its parameters.
+pub(crate) fn tokenize_pagination_methods(
+    operation: &models::OperationAsRust,
+    is_async: bool,
+) -> TokenStream {
+    let Some(pagination) = &operation.pagination else {
+        return TokenStream::new();
+    };
+    let method_name = match operation.method_name().parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn method name to tokens: {}", e)),
+    };
+    let all_function = all_function_name(operation);
+    let page_function = page_function_name(operation);
+    let item_type = match pagination.item_type.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn item type to tokens: {}", e)),
+    };
+    let (params, params_argument, params_reference) = match operation.parameters.is_empty() {
+        true => (TokenStream::new(), TokenStream::new(), TokenStream::new()),
+        false => {
+            let params_type = format_ident!("{}", operation.parameters_struct_name());
+            (
+                quote! { params },
+                quote! { params: #params_type },
+                quote! { &params },
+            )
+        }
+    };
+
+    let (awaited, where_sync) = match is_async {
+        true => (quote! { .await }, quote! { where T: Sync }),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+    let wrap = |body: TokenStream| match is_async {
+        true => quote! { Box::pin(async move { #body }) },
+        false => quote! { Box::new(move || { #body }) },
+    };
+    let call_page = |argument: TokenStream| match is_async {
+        true => quote! { self.#page_function(#argument).await },
+        false => quote! { self.#page_function(#argument)() },
+    };
+
+    let response_enum = format_ident!("{}", operation.response_enum_name());
+    let variant = format_ident!("{}", pagination.status.variant_name());
+    let page = match operation.responses.len() {
+        1 => quote! { let #response_enum::#variant(page, ..) = response; },
+        _ => quote! {
+            let page = match response {
+                #response_enum::#variant(page, ..) => page,
+                other => return Err(ResponseError::UnexpectedStatus(other.status()).into()),
+            };
+        },
+    };
+    let items_field = field(&pagination.items);
+    let items = match pagination.is_items_optional {
+        true => quote! { let items = page.#items_field.unwrap_or_default(); },
+        false => quote! { let items = page.#items_field; },
+    };
+    let doc = format!(
+        " Returns the items of all the pages of [Self::{}], starting from the page selected by the parameters.",
+        operation.method_name().trim_start_matches("r#")
+    );
+
+    match &pagination.kind {
+        PaginationKind::Link => {
+            let request_function = request_function_name(operation);
+            let first_page = call_page(quote! { request });
+            let first = wrap(quote! {
+                let request = self.#request_function(#params_reference)#awaited?;
+                #first_page
+            });
+            let body = wrap(quote! {
+                let http_response = self
+                    .transport
+                    .send(request.clone())
+                    #awaited
+                    .map_err(|e| ClientError::Transport(Box::new(e)))?;
+                let response = #response_enum::from_parts(
+                    http_response.status,
+                    &http_response.headers,
+                    &http_response.body,
+                )?;
+                #page
+                #items
+                // the headers may carry credentials, so they are not sent to other origins
+                let next = next_link(&request.url, &http_response.headers).map(|url| {
+                    let headers = match origin(&url) == origin(&self.base_url) {
+                        true => request.headers,
+                        false => Vec::new(),
+                    };
+                    self.#page_function(HttpRequest { url, headers, ..request })
+                });
+                Ok(Page { items, next })
+            });
+            quote! {
+                #[doc = #doc]
+                /// The next page is requested at the URL of the `Link` header of the previous one,
+                /// with the headers of the first request only if it has the same origin.
+                pub fn #all_function(&self, #params_argument) -> Paginated<'_, #item_type> #where_sync {
+                    Paginated::new(#first)
+                }
+
+                fn #page_function(&self, request: HttpRequest) -> NextPage<'_, #item_type> #where_sync {
+                    #body
+                }
+            }
+        }
+        PaginationKind::Cursor {
+            parameter,
+            next_cursor,
+        } => {
+            let next_cursor = field(next_cursor);
+            let set = set_parameter(parameter, quote! { cursor });
+            // an empty cursor is returned by some APIs on the last page instead of no cursor
+            let is_not_empty = match parameter.rust_type.as_str() {
+                "String" => quote! { if !cursor.is_empty() },
+                _ => TokenStream::new(),
+            };
+            let body = wrap(quote! {
+                let response = self.#method_name(&params)#awaited?;
+                #page
+                #items
+                let next = match page.#next_cursor {
+                    Some(cursor) #is_not_empty => {
+                        #set
+                        Some(self.#page_function(params))
+                    }
+                    _ => None,
+                };
+                Ok(Page { items, next })
+            });
+            quote! {
+                #[doc = #doc]
+                /// The next page is requested with the cursor returned by the previous one,
+                /// until a page returns no cursor or an empty one.
+                pub fn #all_function(&self, #params_argument) -> Paginated<'_, #item_type> #where_sync {
+                    Paginated::new(self.#page_function(#params))
+                }
+
+                fn #page_function(&self, mut #params_argument) -> NextPage<'_, #item_type> #where_sync {
+                    #body
+                }
+            }
+        }
+        PaginationKind::Offset { offset, limit } => {
+            let offset_type = rust_type(offset);
+            let offset_field = field(&offset.field_name);
+            let current = match offset.is_required {
+                true => quote! { params.#offset_field },
+                false => quote! { params.#offset_field.unwrap_or_default() },
+            };
+            let set = set_parameter(offset, quote! { #current + items.len() as #offset_type });
+            let is_short = match limit {
+                Some(limit) => {
+                    let limit_type = rust_type(limit);
+                    let limit_field = field(&limit.field_name);
+                    match limit.is_required {
+                        true => quote! { || (items.len() as #limit_type) < params.#limit_field },
+                        false => quote! {
+                            || params.#limit_field.is_some_and(|limit| (items.len() as #limit_type) < limit)
+                        },
+                    }
+                }
+                None => TokenStream::new(),
+            };
+            let body = wrap(quote! {
+                let response = self.#method_name(&params)#awaited?;
+                #page
+                #items
+                let next = if items.is_empty() #is_short {
+                    None
+                } else {
+                    #set
+                    Some(self.#page_function(params))
+                };
+                Ok(Page { items, next })
+            });
+            quote! {
+                #[doc = #doc]
+                /// The next page is requested with the offset moved past the items of the previous one,
+                /// until a page is empty or shorter than the limit.
+                pub fn #all_function(&self, #params_argument) -> Paginated<'_, #item_type> #where_sync {
+                    Paginated::new(self.#page_function(#params))
+                }
+
+                fn #page_function(&self, mut #params_argument) -> NextPage<'_, #item_type> #where_sync {
+                    #body
+                }
+            }
+        }
+    }
+}
+
+/// Returns the statement that sets a parameter of the `params` variable to a value.
+fn set_parameter(parameter: &PaginationParameterAsRust, value: TokenStream) -> TokenStream {
+    let field_name = field(&parameter.field_name);
+    match parameter.is_required {
+        true => quote! { params.#field_name = #value; },
+        false => quote! { params.#field_name = Some(#value); },
+    }
+}
+
+fn field(field_name: &str) -> TokenStream {
+    match field_name.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    }
+}
+
+fn rust_type(parameter: &PaginationParameterAsRust) -> TokenStream {
+    match parameter.rust_type.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn rust type to tokens: {}", e)),
+    }
+}
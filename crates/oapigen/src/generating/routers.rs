@@ -0,0 +1,338 @@
+//! Generation of the routers that connect the server trait to the web frameworks.
+
+use crate::generating::client::has_streaming;
+use crate::generating::server::call_function_name;
+use crate::models;
+use crate::models::ServerLibraries;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Returns the router of the server for a library.
+pub(crate) fn router(library: &ServerLibraries, spec: &models::SpecAsRust) -> TokenStream {
+    match library {
+        ServerLibraries::Axum => axum(spec),
+        ServerLibraries::ActixWeb => actix_web(spec),
+    }
+}
+
+/// Returns the paths of the spec in the order in which they are declared,
+/// together with the operations that they serve.
+fn group_by_path(spec: &models::SpecAsRust) -> Vec<(&str, Vec<&models::OperationAsRust>)> {
+    let mut routes: Vec<(&str, Vec<&models::OperationAsRust>)> = Vec::new();
+    for operation in &spec.operations {
+        match routes.iter_mut().find(|(path, _)| *path == operation.path) {
+            Some((_, operations)) => operations.push(operation),
+            None => routes.push((&operation.path, vec![operation])),
+        }
+    }
+    routes
+}
+
+/// Returns the [axum](https://docs.rs/axum) router, with one handler for each operation.
+///
+/// The template expressions of the paths, like `{petId}`, are already in the syntax of axum,
+/// since the paths whose templates do not fill a whole segment are rejected while parsing.
+fn axum(spec: &models::SpecAsRust) -> TokenStream {
+    let handlers = spec.operations.iter().map(axum_handler);
+    let routes = group_by_path(spec).into_iter().map(|(path, operations)| {
+        let methods = operations.iter().map(|operation| {
+            let routing = format_ident!("{}", operation.method.to_lowercase());
+            let handler = axum_handler_name(operation);
+            quote! { #routing(#handler::<S>) }
+        });
+        quote! { .route(#path, axum::routing::#(#methods).*) }
+    });
+    let responses = spec
+        .operations
+        .iter()
+        .filter(|o| !o.responses.is_empty())
+        .map(|operation| {
+            let enum_name = format_ident!("{}", operation.response_enum_name());
+            quote! {
+                impl axum::response::IntoResponse for #enum_name {
+                    fn into_response(self) -> axum::response::Response {
+                        let response = self
+                            .into_parts()
+                            .unwrap_or_else(OutgoingResponse::internal_error);
+                        axum::response::IntoResponse::into_response(response)
+                    }
+                }
+            }
+        });
+
+    let body = match has_streaming(spec) {
+        true => quote! {
+            match self.stream {
+                Some(stream) => axum::body::Body::from_stream(stream),
+                None => axum::body::Body::from(self.body),
+            }
+        },
+        false => quote! { axum::body::Body::from(self.body) },
+    };
+
+    quote! {
+        impl axum::response::IntoResponse for OutgoingResponse {
+            fn into_response(self) -> axum::response::Response {
+                let mut builder = http::Response::builder().status(self.status);
+                if let Some(content_type) = self.content_type {
+                    builder = builder.header(http::header::CONTENT_TYPE, content_type);
+                }
+                if let Some(headers) = builder.headers_mut() {
+                    headers.extend(self.headers);
+                }
+                match builder.body(#body) {
+                    Ok(response) => response,
+                    Err(_) => axum::response::IntoResponse::into_response(
+                        http::StatusCode::INTERNAL_SERVER_ERROR,
+                    ),
+                }
+            }
+        }
+
+        impl axum::response::IntoResponse for RequestError {
+            fn into_response(self) -> axum::response::Response {
+                axum::response::IntoResponse::into_response(OutgoingResponse::from(self))
+            }
+        }
+
+        #(#responses)*
+
+        /// Returns the axum router that serves every operation of the spec with `server`.
+        pub fn axum_router<S: Server>(server: S) -> axum::Router {
+            axum::Router::new()
+                #(#routes)*
+                .with_state(std::sync::Arc::new(server))
+        }
+
+        #(#handlers)*
+    }
+}
+
+fn axum_handler_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!("axum_{}", operation.method_name().trim_start_matches("r#"))
+}
+
+/// Renders the axum handler of an operation, that extracts the parts of the request
+/// and passes them to the method of the server trait.
+fn axum_handler(operation: &models::OperationAsRust) -> TokenStream {
+    let handler = axum_handler_name(operation);
+    let call = call_function_name(operation);
+
+    // the path extractor fails on the routes without template expressions
+    let has_path = operation
+        .parameters
+        .iter()
+        .any(|p| p.location == models::ParameterLocation::Path);
+    let (path_extractor, path) = match has_path {
+        true => (
+            quote! {
+                axum::extract::Path(path): axum::extract::Path<std::collections::HashMap<String, String>>,
+            },
+            quote! { &path },
+        ),
+        false => (
+            TokenStream::new(),
+            quote! { &std::collections::HashMap::new() },
+        ),
+    };
+
+    quote! {
+        async fn #handler<S: Server>(
+            axum::extract::State(server): axum::extract::State<std::sync::Arc<S>>,
+            #path_extractor
+            axum::extract::RawQuery(query): axum::extract::RawQuery,
+            headers: http::HeaderMap,
+            body: axum::body::Bytes,
+        ) -> Result<OutgoingResponse, RequestError> {
+            let request = IncomingRequest {
+                path: #path,
+                query: query.as_deref(),
+                headers: &headers,
+                body: &body,
+            };
+            #call(server.as_ref(), request).await
+        }
+    }
+}
+
+/// Returns the [actix-web](https://docs.rs/actix-web) configuration, with one handler for each operation.
+///
+/// The template expressions of the paths, like `{petId}`, are already in the syntax of actix-web,
+/// since the paths whose templates do not fill a whole segment are rejected while parsing.
+fn actix_web(spec: &models::SpecAsRust) -> TokenStream {
+    let handlers = spec.operations.iter().map(actix_web_handler);
+    let resources = group_by_path(spec).into_iter().map(|(path, operations)| {
+        let routes = operations.iter().map(|operation| {
+            let method = format_ident!("{}", operation.method);
+            let handler = actix_web_handler_name(operation);
+            quote! {
+                .route(actix_web::web::method(actix_web::http::Method::#method).to(#handler::<S>))
+            }
+        });
+        quote! { .service(actix_web::web::resource(#path)#(#routes)*) }
+    });
+    let responses = spec
+        .operations
+        .iter()
+        .filter(|o| !o.responses.is_empty())
+        .map(|operation| {
+            let enum_name = format_ident!("{}", operation.response_enum_name());
+            quote! {
+                impl actix_web::Responder for #enum_name {
+                    type Body = actix_web::body::BoxBody;
+
+                    fn respond_to(self, request: &actix_web::HttpRequest) -> actix_web::HttpResponse {
+                        let response = self
+                            .into_parts()
+                            .unwrap_or_else(OutgoingResponse::internal_error);
+                        actix_web::Responder::respond_to(response, request)
+                    }
+                }
+            }
+        });
+
+    let body = match has_streaming(spec) {
+        true => quote! {
+            match response.stream {
+                Some(stream) => builder.streaming(MapStream {
+                    stream,
+                    map: |chunk: Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>| {
+                        chunk
+                            .map(actix_web::web::Bytes::from)
+                            .map_err(|e| e as Box<dyn std::error::Error>)
+                    },
+                }),
+                None => builder.body(response.body),
+            }
+        },
+        false => quote! { builder.body(response.body) },
+    };
+
+    quote! {
+        /// Turns a response of the server into the one of actix-web.
+        fn actix_web_response(response: OutgoingResponse) -> actix_web::HttpResponse {
+            let status = actix_web::http::StatusCode::from_u16(response.status)
+                .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+            let mut builder = actix_web::HttpResponse::build(status);
+            if let Some(content_type) = response.content_type {
+                builder.insert_header((actix_web::http::header::CONTENT_TYPE, content_type));
+            }
+            // actix-web has its own header types, so the headers are copied by name and bytes
+            for (name, value) in &response.headers {
+                builder.append_header((name.as_str(), value.as_bytes()));
+            }
+            #body
+        }
+
+        impl actix_web::Responder for OutgoingResponse {
+            type Body = actix_web::body::BoxBody;
+
+            fn respond_to(self, _request: &actix_web::HttpRequest) -> actix_web::HttpResponse {
+                actix_web_response(self)
+            }
+        }
+
+        impl actix_web::ResponseError for RequestError {
+            fn status_code(&self) -> actix_web::http::StatusCode {
+                actix_web::http::StatusCode::from_u16(self.status())
+                    .unwrap_or(actix_web::http::StatusCode::BAD_REQUEST)
+            }
+
+            fn error_response(&self) -> actix_web::HttpResponse {
+                actix_web_response(OutgoingResponse::from(self))
+            }
+        }
+
+        #(#responses)*
+
+        /// The parts of an actix-web request needed to extract the arguments of the operations.
+        #[derive(Debug)]
+        pub struct ActixWebRequest {
+            path: std::collections::HashMap<String, String>,
+            query: Option<String>,
+            headers: http::HeaderMap,
+        }
+
+        impl ActixWebRequest {
+            /// Returns the request passed to the functions that call the methods of the server.
+            fn incoming<'a>(&'a self, body: &'a [u8]) -> IncomingRequest<'a> {
+                IncomingRequest {
+                    path: &self.path,
+                    query: self.query.as_deref(),
+                    headers: &self.headers,
+                    body,
+                }
+            }
+        }
+
+        impl actix_web::FromRequest for ActixWebRequest {
+            type Error = actix_web::Error;
+            type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+            fn from_request(
+                request: &actix_web::HttpRequest,
+                _payload: &mut actix_web::dev::Payload,
+            ) -> Self::Future {
+                let path = request
+                    .match_info()
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+                let query = Some(request.query_string())
+                    .filter(|query| !query.is_empty())
+                    .map(ToString::to_string);
+                // actix-web uses its own header types, so the headers are copied one by one
+                let mut headers = http::HeaderMap::new();
+                for (name, value) in request.headers() {
+                    let name = http::HeaderName::from_bytes(name.as_str().as_bytes());
+                    let value = http::HeaderValue::from_bytes(value.as_bytes());
+                    if let (Ok(name), Ok(value)) = (name, value) {
+                        headers.append(name, value);
+                    }
+                }
+                std::future::ready(Ok(Self {
+                    path,
+                    query,
+                    headers,
+                }))
+            }
+        }
+
+        /// Registers every operation of the spec on an actix-web application, served with `server`,
+        /// for example with `App::new().configure(|config| actix_web_configure(config, server.clone()))`.
+        pub fn actix_web_configure<S: Server>(
+            config: &mut actix_web::web::ServiceConfig,
+            server: actix_web::web::Data<S>,
+        ) {
+            config
+                .app_data(server)
+                #(#resources)*;
+        }
+
+        #(#handlers)*
+    }
+}
+
+fn actix_web_handler_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!(
+        "actix_web_{}",
+        operation.method_name().trim_start_matches("r#")
+    )
+}
+
+/// Renders the actix-web handler of an operation, that extracts the parts of the request
+/// and passes them to the method of the server trait.
+fn actix_web_handler(operation: &models::OperationAsRust) -> TokenStream {
+    let handler = actix_web_handler_name(operation);
+    let call = call_function_name(operation);
+
+    quote! {
+        async fn #handler<S: Server>(
+            server: actix_web::web::Data<S>,
+            request: ActixWebRequest,
+            body: actix_web::web::Bytes,
+        ) -> Result<OutgoingResponse, RequestError> {
+            #call(server.get_ref(), request.incoming(&body)).await
+        }
+    }
+}
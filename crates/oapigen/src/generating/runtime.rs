@@ -0,0 +1,875 @@
+//! Items that are shared by all the generated operations and are rendered only once.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Returns the error produced when a response cannot be decoded into the response enum
+/// of an operation, that can also fail to receive the body when the responses are streamed
+/// or to parse the headers when the responses declare some.
+pub(crate) fn response_error(has_streaming: bool, has_headers: bool) -> TokenStream {
+    let (body_error, display_body_error) = match has_streaming {
+        true => (
+            quote! {
+                /// The body could not be received.
+                Body(Box<dyn std::error::Error + Send + Sync>),
+            },
+            quote! { Self::Body(e) => write!(f, "cannot receive the body: {}", e), },
+        ),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+    let (header_error, display_header_error) = match has_headers {
+        true => (
+            quote! {
+                /// The headers could not be parsed.
+                Header(HeaderError),
+            },
+            quote! { Self::Header(e) => write!(f, "cannot parse the headers: {}", e), },
+        ),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+
+    quote! {
+        /// Errors that can happen while decoding a response.
+        #[derive(Debug)]
+        pub enum ResponseError {
+            /// The status code is not declared by the operation and there is no default response.
+            UnexpectedStatus(u16),
+            /// The content type does not match any of the media types declared by the response.
+            UnexpectedContentType(Option<String>),
+            /// The body could not be deserialized.
+            Deserialize(Box<dyn std::error::Error + Send + Sync>),
+            #body_error
+            #header_error
+        }
+
+        impl std::fmt::Display for ResponseError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::UnexpectedStatus(status) => write!(f, "unexpected status code {}", status),
+                    Self::UnexpectedContentType(content_type) => {
+                        write!(f, "unexpected content type {:?}", content_type)
+                    }
+                    Self::Deserialize(e) => write!(f, "cannot deserialize the body: {}", e),
+                    #display_body_error
+                    #display_header_error
+                }
+            }
+        }
+
+        impl std::error::Error for ResponseError {}
+
+        impl From<serde_json::Error> for ResponseError {
+            fn from(e: serde_json::Error) -> Self {
+                Self::Deserialize(Box::new(e))
+            }
+        }
+    }
+}
+
+/// Returns the helper used to select the type of a body from the content type of a response.
+pub(crate) fn media_type_matches() -> TokenStream {
+    quote! {
+        /// Checks if a content type, like `application/json; charset=utf-8`, matches one of the
+        /// media types declared in the spec, that can use wildcards like `text/*`.
+        fn media_type_matches(content_type: Option<&str>, media_types: &[&str]) -> bool {
+            let Some(content_type) = content_type else {
+                return false;
+            };
+            let essence = content_type.split(';').next().unwrap_or_default().trim();
+
+            media_types.iter().any(|media_type| {
+                let media_type = media_type.split(';').next().unwrap_or_default().trim();
+                match media_type.split_once('/') {
+                    Some(("*", "*")) => true,
+                    Some((kind, "*")) => essence
+                        .split_once('/')
+                        .is_some_and(|(essence_kind, _)| essence_kind.eq_ignore_ascii_case(kind)),
+                    _ => essence.eq_ignore_ascii_case(media_type),
+                }
+            })
+        }
+    }
+}
+
+/// Returns the error produced when the headers of a response cannot be parsed or encoded,
+/// together with the helpers used by the generated headers structs for a single header.
+pub(crate) fn header_error() -> TokenStream {
+    quote! {
+        /// Errors that can happen while parsing or encoding the headers of a response or of a part.
+        #[derive(Debug)]
+        pub enum HeaderError {
+            /// A required header is missing.
+            Missing(&'static str),
+            /// A header cannot be parsed to its type, or its value cannot be sent in a header.
+            Invalid(&'static str),
+        }
+
+        impl std::fmt::Display for HeaderError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Missing(name) => write!(f, "missing required header {}", name),
+                    Self::Invalid(name) => write!(f, "invalid value for header {}", name),
+                }
+            }
+        }
+
+        impl std::error::Error for HeaderError {}
+
+        fn parse_header<T: std::str::FromStr>(
+            headers: &http::HeaderMap,
+            name: &'static str,
+        ) -> Result<Option<T>, HeaderError> {
+            match headers.get(name) {
+                None => Ok(None),
+                Some(value) => value
+                    .to_str()
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .map(Some)
+                    .ok_or(HeaderError::Invalid(name)),
+            }
+        }
+
+        fn insert_header<T: std::fmt::Display>(
+            headers: &mut http::HeaderMap,
+            name: &'static str,
+            value: &T,
+        ) -> Result<(), HeaderError> {
+            let header_name =
+                http::HeaderName::try_from(name).map_err(|_| HeaderError::Invalid(name))?;
+            let header_value = http::HeaderValue::try_from(value.to_string())
+                .map_err(|_| HeaderError::Invalid(name))?;
+            headers.insert(header_name, header_value);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the types used to build `multipart/form-data` payloads: the parts themselves
+/// and the files that can be sent in them.
+pub(crate) fn multipart() -> TokenStream {
+    quote! {
+        /// A file sent in a part of a multipart payload.
+        #[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+        pub struct FilePart {
+            /// The name of the file, sent in the `Content-Disposition` of the part
+            pub file_name: Option<String>,
+            /// The content type of the file, overriding the one declared by the encoding of the part
+            pub content_type: Option<String>,
+            /// The additional headers of the part
+            pub headers: Vec<(String, String)>,
+            /// The content of the file
+            pub bytes: Vec<u8>,
+        }
+
+        impl FilePart {
+            /// Creates a file from its content.
+            pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+                Self {
+                    bytes: bytes.into(),
+                    ..Self::default()
+                }
+            }
+
+            /// Sets the name of the file.
+            pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+                self.file_name = Some(file_name.into());
+                self
+            }
+
+            /// Sets the content type of the file.
+            pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+                self.content_type = Some(content_type.into());
+                self
+            }
+
+            /// Adds a header to the part of the file.
+            pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+                self.headers.push((name.into(), value.into()));
+                self
+            }
+        }
+
+        /// A part of a multipart payload, ready to be sent by an HTTP client.
+        #[derive(Debug, Clone)]
+        pub struct MultipartPart {
+            /// The name of the part
+            pub name: String,
+            /// The name of the file, if the part is a file
+            pub file_name: Option<String>,
+            /// The content type of the part
+            pub content_type: Option<String>,
+            /// The additional headers of the part
+            pub headers: Vec<(String, String)>,
+            /// The content of the part
+            pub body: Vec<u8>,
+        }
+
+        impl MultipartPart {
+            /// Creates a part that contains a simple value as text.
+            pub fn text(name: &str, value: impl ToString) -> Self {
+                Self {
+                    name: name.to_string(),
+                    file_name: None,
+                    content_type: None,
+                    headers: Vec::new(),
+                    body: value.to_string().into_bytes(),
+                }
+            }
+
+            /// Creates a part that contains a value serialized as JSON.
+            pub fn json<T: serde::Serialize>(name: &str, value: &T) -> Result<Self, serde_json::Error> {
+                Ok(Self {
+                    name: name.to_string(),
+                    file_name: None,
+                    content_type: Some("application/json".to_string()),
+                    headers: Vec::new(),
+                    body: serde_json::to_vec(value)?,
+                })
+            }
+
+            /// Creates a part that contains a file.
+            pub fn file(name: &str, file: FilePart) -> Self {
+                Self {
+                    name: name.to_string(),
+                    file_name: file.file_name,
+                    content_type: file.content_type,
+                    headers: file.headers,
+                    body: file.bytes,
+                }
+            }
+
+            /// Sets the content type of the part, unless it already has one.
+            pub fn default_content_type(mut self, content_type: &str) -> Self {
+                self.content_type.get_or_insert_with(|| content_type.to_string());
+                self
+            }
+
+            /// Adds a header to the part.
+            pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+                self.headers.push((name.into(), value.into()));
+                self
+            }
+
+            /// Adds the headers declared by the encoding of the part.
+            pub fn with_headers(mut self, headers: &http::HeaderMap) -> Self {
+                for (name, value) in headers {
+                    if let Ok(value) = value.to_str() {
+                        self.headers.push((name.to_string(), value.to_string()));
+                    }
+                }
+                self
+            }
+
+            /// Returns the headers of the part, skipping the ones that are not valid HTTP headers.
+            pub fn header_map(&self) -> http::HeaderMap {
+                let mut headers = http::HeaderMap::new();
+                for (name, value) in &self.headers {
+                    let name = http::HeaderName::try_from(name.as_str());
+                    let value = http::HeaderValue::try_from(value.as_str());
+                    if let (Ok(name), Ok(value)) = (name, value) {
+                        headers.append(name, value);
+                    }
+                }
+                headers
+            }
+
+            /// Checks that the content type of the part, if it has one, is one of the `allowed` media types
+            /// declared by its encoding, that can use wildcards like `image/*`.
+            pub fn check_content_type(
+                &self,
+                allowed: &[&str],
+            ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                let Some(content_type) = &self.content_type else {
+                    return Ok(());
+                };
+                let essence = content_type.split(';').next().unwrap_or_default().trim();
+                let is_allowed = allowed.iter().any(|media_type| match media_type.split_once('/') {
+                    Some(("*", "*")) => true,
+                    Some((kind, "*")) => essence
+                        .split_once('/')
+                        .is_some_and(|(essence_kind, _)| essence_kind.eq_ignore_ascii_case(kind)),
+                    _ => essence.eq_ignore_ascii_case(media_type),
+                });
+                match is_allowed {
+                    true => Ok(()),
+                    false => Err(format!(
+                        "the part {} has the content type {}, but it must be one of {}",
+                        self.name,
+                        content_type,
+                        allowed.join(", ")
+                    )
+                    .into()),
+                }
+            }
+
+            /// Parses the content of a part that contains a simple value as text.
+            pub fn text_value<T: std::str::FromStr>(
+                &self,
+            ) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+            where
+                T::Err: std::fmt::Display,
+            {
+                let text = std::str::from_utf8(&self.body)?;
+                text.parse()
+                    .map_err(|e| format!("the part {} is not valid: {}", self.name, e).into())
+            }
+
+            /// Deserializes the content of a part that contains a value as JSON.
+            pub fn json_value<T: serde::de::DeserializeOwned>(
+                &self,
+            ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(serde_json::from_slice(&self.body)?)
+            }
+
+            /// Turns a part that contains a file into the file.
+            pub fn into_file(self) -> FilePart {
+                FilePart {
+                    file_name: self.file_name,
+                    content_type: self.content_type,
+                    headers: self.headers,
+                    bytes: self.body,
+                }
+            }
+        }
+    }
+}
+
+/// Returns the helper that encodes the parts of a multipart body, used when a multipart body
+/// is sent, either by the client or by the server.
+pub(crate) fn encode_multipart() -> TokenStream {
+    quote! {
+        /// Encodes the parts of a multipart body, returning its content type and its bytes.
+        /// The boundary is random, and it is drawn again in the unlikely case that a part contains it.
+        fn encode_multipart(parts: Vec<MultipartPart>) -> (String, Vec<u8>) {
+            let boundary = loop {
+                let boundary = random_boundary();
+                let contains = |bytes: &[u8]| {
+                    bytes.windows(boundary.len()).any(|w| w == boundary.as_bytes())
+                };
+                let is_contained = parts.iter().any(|part| {
+                    contains(&part.body)
+                        || contains(part.name.as_bytes())
+                        || part.file_name.as_ref().is_some_and(|f| contains(f.as_bytes()))
+                        || part.headers.iter().any(|(_, value)| contains(value.as_bytes()))
+                });
+                if !is_contained {
+                    break boundary;
+                }
+            };
+
+            let mut body = Vec::new();
+            for part in parts {
+                body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+                let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", part.name);
+                if let Some(file_name) = &part.file_name {
+                    disposition.push_str(&format!("; filename=\"{}\"", file_name));
+                }
+                body.extend_from_slice(disposition.as_bytes());
+                body.extend_from_slice(b"\r\n");
+                if let Some(content_type) = &part.content_type {
+                    body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+                }
+                for (name, value) in &part.headers {
+                    body.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+                }
+                body.extend_from_slice(b"\r\n");
+                body.extend_from_slice(&part.body);
+                body.extend_from_slice(b"\r\n");
+            }
+            body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+            (format!("multipart/form-data; boundary={}", boundary), body)
+        }
+
+        /// Returns a random boundary for a multipart body. The hashers of the standard library
+        /// are seeded with random keys, so their hashes are random enough for a boundary.
+        fn random_boundary() -> String {
+            use std::hash::BuildHasher;
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default();
+            let random = |i: u8| std::collections::hash_map::RandomState::new().hash_one((i, nanos));
+            format!("oapigen-{:016x}{:016x}", random(0), random(1))
+        }
+    }
+}
+
+/// Returns the helper that decodes the parts of a multipart body, used when a multipart body
+/// is received, either by the client or by the server.
+pub(crate) fn decode_multipart() -> TokenStream {
+    quote! {
+        /// Decodes the parts of a multipart body, delimited by the boundary of its content type.
+        /// The preamble before the first part and the epilogue after the last one are ignored.
+        fn decode_multipart(
+            content_type: Option<&str>,
+            body: &[u8],
+        ) -> Result<Vec<MultipartPart>, Box<dyn std::error::Error + Send + Sync>> {
+            let boundary = content_type
+                .into_iter()
+                .flat_map(|c| c.split(';').skip(1))
+                .find_map(|param| {
+                    let (name, value) = param.split_once('=')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("boundary")
+                        .then(|| value.trim().trim_matches('"'))
+                })
+                .ok_or("the content type of the multipart body has no boundary")?;
+            let delimiter = format!("\r\n--{}", boundary);
+            let delimiter = delimiter.as_bytes();
+
+            // the first delimiter can start the body, without the line break before it
+            let start = match body.starts_with(&delimiter[2..]) {
+                true => 0,
+                false => find_bytes(body, delimiter).ok_or("the multipart body has no parts")? + 2,
+            };
+            let mut rest = &body[start + delimiter.len() - 2..];
+            let mut parts = Vec::new();
+            while !rest.starts_with(b"--") {
+                // the rest of the line of a delimiter is padding
+                let line_end = find_bytes(rest, b"\r\n").ok_or("the multipart body is truncated")?;
+                rest = &rest[line_end + 2..];
+                let end = find_bytes(rest, delimiter).ok_or("the multipart body is truncated")?;
+                parts.push(decode_part(&rest[..end])?);
+                rest = &rest[end + delimiter.len()..];
+            }
+            Ok(parts)
+        }
+
+        /// Decodes a part of a multipart body: its headers, up to the first empty line, and its content.
+        fn decode_part(part: &[u8]) -> Result<MultipartPart, Box<dyn std::error::Error + Send + Sync>> {
+            let (head, body) = match part.starts_with(b"\r\n") {
+                true => (&part[..0], &part[2..]),
+                false => {
+                    let end = find_bytes(part, b"\r\n\r\n")
+                        .ok_or("a part of the multipart body has no empty line after its headers")?;
+                    (&part[..end], &part[end + 4..])
+                }
+            };
+            let mut decoded = MultipartPart {
+                name: String::new(),
+                file_name: None,
+                content_type: None,
+                headers: Vec::new(),
+                body: body.to_vec(),
+            };
+            for line in std::str::from_utf8(head)?.split("\r\n") {
+                let Some((name, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let (name, value) = (name.trim(), value.trim());
+                if name.eq_ignore_ascii_case("Content-Disposition") {
+                    for param in value.split(';').skip(1) {
+                        let Some((key, value)) = param.split_once('=') else {
+                            continue;
+                        };
+                        let value = value.trim().trim_matches('"').to_string();
+                        match key.trim().to_ascii_lowercase().as_str() {
+                            "name" => decoded.name = value,
+                            "filename" => decoded.file_name = Some(value),
+                            _ => {}
+                        }
+                    }
+                } else if name.eq_ignore_ascii_case("Content-Type") {
+                    decoded.content_type = Some(value.to_string());
+                } else {
+                    decoded.headers.push((name.to_string(), value.to_string()));
+                }
+            }
+            Ok(decoded)
+        }
+
+        /// Returns the position of the first occurrence of `needle` in `bytes`.
+        fn find_bytes(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+            bytes.windows(needle.len()).position(|w| w == needle)
+        }
+    }
+}
+
+/// Returns the opaque wrapper used for the bodies whose media type is unknown.
+pub(crate) fn raw_payload() -> TokenStream {
+    quote! {
+        /// A body whose media type is not known, kept as bytes together with its content type.
+        #[derive(Debug, Clone, Default)]
+        pub struct RawPayload {
+            /// The content type of the body
+            pub content_type: Option<String>,
+            /// The content of the body
+            pub bytes: Vec<u8>,
+        }
+    }
+}
+
+/// Returns the types of the streaming bodies: the stream of bytes received or sent, the stream of
+/// items decoded from it and the helpers that split it in lines or in `text/event-stream` events.
+/// The streams implement [futures_core::Stream](https://docs.rs/futures-core), so the generated code
+/// needs the futures-core crate.
+pub(crate) fn streaming(has_lines: bool, has_events: bool) -> TokenStream {
+    let lines = match has_lines {
+        true => quote! {
+            /// Parses a line of JSON, skipping the blank ones.
+            fn parse_json_line<T: serde::de::DeserializeOwned>(
+                line: &[u8],
+            ) -> Option<Result<T, Box<dyn std::error::Error + Send + Sync>>> {
+                match line.trim_ascii().is_empty() {
+                    true => None,
+                    false => Some(serde_json::from_slice(line).map_err(Into::into)),
+                }
+            }
+
+            /// Decodes the items of a body made of lines of JSON, like `application/x-ndjson`, while it is received.
+            fn decode_json_lines<T: serde::de::DeserializeOwned + Send + 'static>(
+                bytes: ByteStream,
+            ) -> Streaming<T> {
+                Streaming::new(Frames {
+                    bytes,
+                    buffer: Vec::new(),
+                    is_done: false,
+                    end: |buffer: &[u8]| buffer.iter().position(|b| *b == b'\n').map(|end| (end, end + 1)),
+                    parse: parse_json_line::<T>,
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let events = match has_events {
+        true => quote! {
+            /// An event of a `text/event-stream` body, whose data is an item of the schema.
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct ServerSentEvent<T> {
+                /// The type of the event, `message` when it is missing
+                pub event: Option<String>,
+                /// The id of the event
+                pub id: Option<String>,
+                /// The time to wait before reconnecting, in milliseconds
+                pub retry: Option<u64>,
+                /// The data of the event
+                pub data: T,
+            }
+
+            impl<T> ServerSentEvent<T> {
+                /// Creates an event with its data, without type, id or retry time.
+                pub fn new(data: T) -> Self {
+                    Self {
+                        event: None,
+                        id: None,
+                        retry: None,
+                        data,
+                    }
+                }
+            }
+
+            /// Returns the start and the end of the blank line that ends the first event of a buffer.
+            fn event_end(buffer: &[u8]) -> Option<(usize, usize)> {
+                let end = |separator: &[u8]| {
+                    buffer
+                        .windows(separator.len())
+                        .position(|w| w == separator)
+                        .map(|start| (start, start + separator.len()))
+                };
+                match (end(b"\n\n"), end(b"\r\n\r\n")) {
+                    (Some(lf), Some(crlf)) => Some(std::cmp::min(lf, crlf)),
+                    (lf, crlf) => lf.or(crlf),
+                }
+            }
+
+            /// Parses an event, skipping the ones without data like the comments used as keep-alive.
+            /// The data is decoded as JSON, or as a JSON string when it is plain text.
+            fn parse_event<T: serde::de::DeserializeOwned>(
+                frame: &[u8],
+            ) -> Option<Result<ServerSentEvent<T>, Box<dyn std::error::Error + Send + Sync>>> {
+                let frame = String::from_utf8_lossy(frame);
+                let mut event = ServerSentEvent::new(());
+                let mut data: Option<String> = None;
+                for line in frame.lines() {
+                    if line.starts_with(':') {
+                        continue;
+                    }
+                    let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                    let value = value.strip_prefix(' ').unwrap_or(value);
+                    match field {
+                        "data" => match &mut data {
+                            Some(data) => {
+                                data.push('\n');
+                                data.push_str(value);
+                            }
+                            None => data = Some(value.to_string()),
+                        },
+                        "event" => event.event = Some(value.to_string()),
+                        "id" => event.id = Some(value.to_string()),
+                        "retry" => event.retry = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+
+                let data = data?;
+                let decoded = match serde_json::from_str(&data) {
+                    Ok(decoded) => Ok(decoded),
+                    Err(e) => serde_json::from_value(serde_json::Value::String(data)).map_err(|_| e.into()),
+                };
+                Some(decoded.map(|data| ServerSentEvent {
+                    event: event.event,
+                    id: event.id,
+                    retry: event.retry,
+                    data,
+                }))
+            }
+
+            /// Decodes the events of a `text/event-stream` body while it is received.
+            fn decode_events<T: serde::de::DeserializeOwned + Send + 'static>(
+                bytes: ByteStream,
+            ) -> Streaming<ServerSentEvent<T>> {
+                Streaming::new(Frames {
+                    bytes,
+                    buffer: Vec::new(),
+                    is_done: false,
+                    end: event_end,
+                    parse: parse_event::<T>,
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// A boxed stream of items that can fail, shared by the streams of bytes and of items.
+        type BoxedStream<T> = std::pin::Pin<
+            Box<dyn futures_core::Stream<Item = Result<T, Box<dyn std::error::Error + Send + Sync>>> + Send>,
+        >;
+
+        /// A stream of chunks of bytes, like the body of a streaming response.
+        pub struct ByteStream(BoxedStream<Vec<u8>>);
+
+        impl ByteStream {
+            /// Creates a stream from another one, like the body of a response of an HTTP library.
+            pub fn new<S, B, E>(stream: S) -> Self
+            where
+                S: futures_core::Stream<Item = Result<B, E>> + Send + 'static,
+                B: Into<Vec<u8>>,
+                E: Into<Box<dyn std::error::Error + Send + Sync>>,
+            {
+                Self(Box::pin(MapStream {
+                    stream: Box::pin(stream),
+                    map: |chunk: Result<B, E>| chunk.map(Into::into).map_err(Into::into),
+                }))
+            }
+
+            /// Creates a stream with a single chunk, used for the bodies that are received all at once.
+            pub fn from_bytes(bytes: Vec<u8>) -> Self {
+                Self(Box::pin(IterStream(std::iter::once(Ok(bytes)))))
+            }
+
+            /// Creates a stream that reads the chunks from a reader, blocking the thread while it waits for them.
+            pub fn from_reader(reader: impl std::io::Read + Send + 'static) -> Self {
+                let mut reader = Box::new(reader);
+                let chunks = std::iter::from_fn(move || {
+                    let mut chunk = vec![0; 8192];
+                    loop {
+                        match reader.read(&mut chunk) {
+                            Ok(0) => return None,
+                            Ok(read) => {
+                                chunk.truncate(read);
+                                return Some(Ok(chunk));
+                            }
+                            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                            Err(e) => return Some(Err(e.into())),
+                        }
+                    }
+                });
+                Self(Box::pin(IterStream(chunks)))
+            }
+
+            /// Waits for all the chunks and returns them joined together.
+            pub async fn into_bytes(mut self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+                let mut bytes = Vec::new();
+                while let Some(chunk) = std::future::poll_fn(|cx| self.0.as_mut().poll_next(cx)).await {
+                    bytes.extend(chunk?);
+                }
+                Ok(bytes)
+            }
+        }
+
+        impl futures_core::Stream for ByteStream {
+            type Item = Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                self.0.as_mut().poll_next(cx)
+            }
+        }
+
+        impl std::fmt::Debug for ByteStream {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("ByteStream").finish_non_exhaustive()
+            }
+        }
+
+        /// A stream of the items of a streaming body, like the events of `text/event-stream`
+        /// or the lines of `application/x-ndjson`.
+        ///
+        /// It is a [futures_core::Stream], and an [Iterator] that blocks the thread until the next item
+        /// for the programs that cannot use an async runtime.
+        pub struct Streaming<T>(BoxedStream<T>);
+
+        impl<T> Streaming<T> {
+            /// Creates a stream of items from another one, like the items produced by a server.
+            pub fn new(
+                stream: impl futures_core::Stream<Item = Result<T, Box<dyn std::error::Error + Send + Sync>>>
+                    + Send
+                    + 'static,
+            ) -> Self {
+                Self(Box::pin(stream))
+            }
+        }
+
+        impl<T: Send + 'static> FromIterator<T> for Streaming<T> {
+            fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+                let items: Vec<_> = items.into_iter().collect();
+                Self::new(IterStream(items.into_iter().map(Ok)))
+            }
+        }
+
+        impl<T> futures_core::Stream for Streaming<T> {
+            type Item = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                self.0.as_mut().poll_next(cx)
+            }
+        }
+
+        impl<T> Iterator for Streaming<T> {
+            type Item = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                block_on(std::future::poll_fn(|cx| self.0.as_mut().poll_next(cx)))
+            }
+        }
+
+        impl<T> std::fmt::Debug for Streaming<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("Streaming").finish_non_exhaustive()
+            }
+        }
+
+        /// Runs a future to completion on the current thread, parking it while the future is pending.
+        fn block_on<F: std::future::Future>(future: F) -> F::Output {
+            struct ThreadWaker(std::thread::Thread);
+
+            impl std::task::Wake for ThreadWaker {
+                fn wake(self: std::sync::Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            let waker = std::task::Waker::from(std::sync::Arc::new(ThreadWaker(std::thread::current())));
+            let mut cx = std::task::Context::from_waker(&waker);
+            let mut future = std::pin::pin!(future);
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    std::task::Poll::Ready(output) => return output,
+                    std::task::Poll::Pending => std::thread::park(),
+                }
+            }
+        }
+
+        /// A stream that yields the items of an iterator, which are always ready.
+        struct IterStream<I>(I);
+
+        // the iterator is never pinned, so the items do not need to be `Unpin`
+        impl<I> Unpin for IterStream<I> {}
+
+        impl<I: Iterator> futures_core::Stream for IterStream<I> {
+            type Item = I::Item;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                std::task::Poll::Ready(self.0.next())
+            }
+        }
+
+        /// A stream that maps the items of another one.
+        struct MapStream<S, F> {
+            stream: S,
+            map: F,
+        }
+
+        impl<S, F, T> futures_core::Stream for MapStream<S, F>
+        where
+            S: futures_core::Stream + Unpin,
+            F: FnMut(S::Item) -> T + Unpin,
+        {
+            type Item = T;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                let this = &mut *self;
+                std::pin::Pin::new(&mut this.stream)
+                    .poll_next(cx)
+                    .map(|item| item.map(&mut this.map))
+            }
+        }
+
+        /// Parses a frame, returning nothing if it carries no item.
+        type ParseFrame<T> = fn(&[u8]) -> Option<Result<T, Box<dyn std::error::Error + Send + Sync>>>;
+
+        /// A stream that splits a stream of bytes in frames, like lines or events,
+        /// and parses each one of them, skipping the ones that carry no item.
+        struct Frames<T> {
+            bytes: ByteStream,
+            buffer: Vec<u8>,
+            is_done: bool,
+            /// Returns the start and the end of the separator that ends the first frame of the buffer
+            end: fn(&[u8]) -> Option<(usize, usize)>,
+            parse: ParseFrame<T>,
+        }
+
+        impl<T> futures_core::Stream for Frames<T> {
+            type Item = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                let this = &mut *self;
+                loop {
+                    let frame = match (this.end)(&this.buffer) {
+                        Some((start, end)) => {
+                            let frame: Vec<u8> = this.buffer.drain(..end).collect();
+                            frame[..start].to_vec()
+                        }
+                        // the last frame does not need a separator
+                        None if this.is_done && !this.buffer.is_empty() => std::mem::take(&mut this.buffer),
+                        None if this.is_done => return std::task::Poll::Ready(None),
+                        None => {
+                            match std::pin::Pin::new(&mut this.bytes).poll_next(cx) {
+                                std::task::Poll::Ready(Some(Ok(chunk))) => this.buffer.extend(chunk),
+                                std::task::Poll::Ready(Some(Err(e))) => {
+                                    return std::task::Poll::Ready(Some(Err(e)));
+                                }
+                                std::task::Poll::Ready(None) => this.is_done = true,
+                                std::task::Poll::Pending => return std::task::Poll::Pending,
+                            }
+                            continue;
+                        }
+                    };
+                    if let Some(item) = (this.parse)(&frame) {
+                        return std::task::Poll::Ready(Some(item));
+                    }
+                }
+            }
+        }
+
+        #lines
+        #events
+    }
+}
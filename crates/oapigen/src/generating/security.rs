@@ -0,0 +1,718 @@
+//! Generation of the security schemes of a spec: the credentials attached by the client
+//! to the operations and the ones extracted by the server to authenticate the requests.
+
+use crate::models;
+use crate::models::{ParameterLocation, SecuritySchemeAsRust, SecuritySchemeKind};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+fn field_tokens(scheme: &SecuritySchemeAsRust) -> TokenStream {
+    match scheme.field_name().parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    }
+}
+
+/// Returns the schemes of a security requirement, skipping the ones that are not declared.
+fn requirement_schemes<'a>(
+    spec: &'a models::SpecAsRust,
+    requirement: &models::SecurityRequirementAsRust,
+) -> Vec<&'a SecuritySchemeAsRust> {
+    requirement
+        .schemes
+        .iter()
+        .filter_map(|(name, _)| spec.security_schemes.iter().find(|s| &s.name == name))
+        .collect()
+}
+
+/// Returns true if one of the schemes is an API key sent in `location`.
+pub(crate) fn has_api_key_in(
+    schemes: &[&SecuritySchemeAsRust],
+    location: ParameterLocation,
+) -> bool {
+    schemes.iter().any(
+        |s| matches!(&s.kind, SecuritySchemeKind::ApiKey { location: l, .. } if *l == location),
+    )
+}
+
+/// Returns the schemes used by the security requirements of an operation.
+pub(crate) fn operation_schemes<'a>(
+    spec: &'a models::SpecAsRust,
+    operation: &models::OperationAsRust,
+) -> Vec<&'a SecuritySchemeAsRust> {
+    operation
+        .security
+        .iter()
+        .flat_map(|r| requirement_schemes(spec, r))
+        .collect()
+}
+
+/// Returns true if the client requests OAuth2 tokens with the client credentials flow.
+pub(crate) fn has_client_credentials(spec: &models::SpecAsRust) -> bool {
+    spec.security_schemes
+        .iter()
+        .any(|s| matches!(s.kind, SecuritySchemeKind::ClientCredentials { .. }))
+}
+
+/// Returns a sentence that describes where the credentials of a scheme are sent.
+fn describe_location(scheme: &SecuritySchemeAsRust) -> String {
+    match &scheme.kind {
+        SecuritySchemeKind::ApiKey { name, location } => {
+            let location = match location {
+                ParameterLocation::Header => "header",
+                ParameterLocation::Query => "query parameter",
+                ParameterLocation::Cookie => "cookie",
+                ParameterLocation::Path => "path parameter",
+            };
+            format!("sent in the `{name}` {location}")
+        }
+        _ => "sent in the `Authorization` header".to_string(),
+    }
+}
+
+/// Returns the credentials of the client, with one field for each security scheme,
+/// and the helpers used to send them.
+pub(crate) fn client_credentials(spec: &models::SpecAsRust) -> TokenStream {
+    let fields = spec.security_schemes.iter().map(|scheme| {
+        let field = field_tokens(scheme);
+        let comment = format!(" The credentials of the `{}` scheme", scheme.name);
+        let credentials_type = match scheme.kind {
+            SecuritySchemeKind::ApiKey { .. } | SecuritySchemeKind::Bearer => quote! { String },
+            SecuritySchemeKind::Basic => quote! { (String, String) },
+            SecuritySchemeKind::ClientCredentials { .. } => quote! { ClientCredentials },
+        };
+        quote! {
+            #[doc = #comment]
+            #field: Option<#credentials_type>
+        }
+    });
+
+    let needs_base64 = spec.security_schemes.iter().any(|s| {
+        matches!(
+            s.kind,
+            SecuritySchemeKind::Basic | SecuritySchemeKind::ClientCredentials { .. }
+        )
+    });
+    let base64_encode = match needs_base64 {
+        true => quote! {
+            /// Encodes bytes as base64, with padding, like the `Basic` scheme of the `Authorization` header expects.
+            fn base64_encode(bytes: &[u8]) -> String {
+                const ALPHABET: &[u8; 64] =
+                    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+                let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+                for chunk in bytes.chunks(3) {
+                    let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+                    let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+                    for i in 0..4 {
+                        match i <= chunk.len() {
+                            true => encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i)) as usize & 63])),
+                            false => encoded.push('='),
+                        }
+                    }
+                }
+                encoded
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let client_credentials = match has_client_credentials(spec) {
+        true => client_credentials_flow(),
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// The credentials sent by the client, one for each security scheme of the spec.
+        /// Each operation sends the ones of its first security requirement whose credentials are all set.
+        #[derive(Clone, Default)]
+        pub struct Credentials {
+            #(#fields,)*
+        }
+
+        impl std::fmt::Debug for Credentials {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("Credentials").finish_non_exhaustive()
+            }
+        }
+
+        #base64_encode
+        #client_credentials
+    }
+}
+
+/// Returns the credentials of the OAuth2 client credentials flow, that cache the token obtained with them.
+fn client_credentials_flow() -> TokenStream {
+    quote! {
+        /// The client id and secret of an OAuth2 client credentials flow, together with the access
+        /// token obtained with them, which is shared by the clones of the client.
+        #[derive(Clone)]
+        pub struct ClientCredentials {
+            client_id: String,
+            client_secret: String,
+            token: std::sync::Arc<std::sync::Mutex<Option<AccessToken>>>,
+        }
+
+        /// An access token cached by the client.
+        #[derive(Clone)]
+        struct AccessToken {
+            value: String,
+            expires_at: Option<std::time::Instant>,
+        }
+
+        impl ClientCredentials {
+            /// Creates the credentials of a client, that requests its first token when it is needed.
+            pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+                Self {
+                    client_id: client_id.into(),
+                    client_secret: client_secret.into(),
+                    token: std::sync::Arc::default(),
+                }
+            }
+
+            fn lock(&self) -> std::sync::MutexGuard<'_, Option<AccessToken>> {
+                match self.token.lock() {
+                    Ok(token) => token,
+                    Err(poisoned) => poisoned.into_inner(),
+                }
+            }
+
+            /// Returns the cached access token, unless it is missing or expired.
+            fn cached_token(&self) -> Option<String> {
+                let now = std::time::Instant::now();
+                self.lock()
+                    .as_ref()
+                    .filter(|token| token.expires_at.is_none_or(|expires_at| expires_at > now))
+                    .map(|token| token.value.clone())
+            }
+
+            /// Returns the request that asks `token_url` for a new access token with `scopes`.
+            fn token_request(&self, token_url: &str, scopes: &[&str]) -> HttpRequest {
+                let mut body = "grant_type=client_credentials".to_string();
+                if !scopes.is_empty() {
+                    body.push_str("&scope=");
+                    body.push_str(&encode_component(&scopes.join(" ")));
+                }
+                let secret = format!(
+                    "{}:{}",
+                    encode_component(&self.client_id),
+                    encode_component(&self.client_secret)
+                );
+                HttpRequest {
+                    method: http::Method::POST,
+                    url: token_url.to_string(),
+                    headers: vec![
+                        (
+                            "Content-Type".to_string(),
+                            "application/x-www-form-urlencoded".to_string(),
+                        ),
+                        (
+                            "Authorization".to_string(),
+                            format!("Basic {}", base64_encode(secret.as_bytes())),
+                        ),
+                    ],
+                    body: body.into_bytes(),
+                }
+            }
+
+            /// Caches the access token received from the token endpoint and returns it.
+            fn store_token(&self, response: &HttpResponse) -> Result<String, ClientError> {
+                #[derive(serde::Deserialize)]
+                struct TokenResponse {
+                    access_token: String,
+                    expires_in: Option<u64>,
+                }
+
+                if !(200..300).contains(&response.status) {
+                    let message = format!("the token endpoint answered with status {}", response.status);
+                    return Err(ClientError::Token(message.into()));
+                }
+                let token: TokenResponse = serde_json::from_slice(&response.body)
+                    .map_err(|e| ClientError::Token(Box::new(e)))?;
+                // the token is renewed a little earlier, so that it does not expire while a request is sent
+                let expires_at = token.expires_in.map(|seconds| {
+                    std::time::Instant::now() + std::time::Duration::from_secs(seconds.saturating_sub(30))
+                });
+                *self.lock() = Some(AccessToken {
+                    value: token.access_token.clone(),
+                    expires_at,
+                });
+                Ok(token.access_token)
+            }
+        }
+
+        impl std::fmt::Debug for ClientCredentials {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("ClientCredentials")
+                    .field("client_id", &self.client_id)
+                    .finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+/// Returns the methods of the client that set the credentials of each security scheme,
+/// together with the ones that request the OAuth2 tokens.
+pub(crate) fn client_methods(spec: &models::SpecAsRust, is_async: bool) -> TokenStream {
+    let setters = spec.security_schemes.iter().map(|scheme| {
+        let field = field_tokens(scheme);
+        let setter = format_ident!("with_{}", scheme.field_name().trim_start_matches("r#"));
+        let name = &scheme.name;
+        let location = describe_location(scheme);
+        let (comment, arguments, value) = match scheme.kind {
+            SecuritySchemeKind::ApiKey { .. } => (
+                format!(" Sets the API key of the `{name}` scheme, {location}."),
+                quote! { api_key: impl Into<String> },
+                quote! { api_key.into() },
+            ),
+            SecuritySchemeKind::Bearer => (
+                format!(" Sets the token of the `{name}` scheme, {location}."),
+                quote! { token: impl Into<String> },
+                quote! { token.into() },
+            ),
+            SecuritySchemeKind::Basic => (
+                format!(" Sets the username and the password of the `{name}` scheme, {location}."),
+                quote! { username: impl Into<String>, password: impl Into<String> },
+                quote! { (username.into(), password.into()) },
+            ),
+            SecuritySchemeKind::ClientCredentials { .. } => (
+                format!(
+                    " Sets the client id and secret used to request the access tokens of the `{name}` scheme, {location}."
+                ),
+                quote! { client_id: impl Into<String>, client_secret: impl Into<String> },
+                quote! { ClientCredentials::new(client_id, client_secret) },
+            ),
+        };
+        quote! {
+            #[doc = #comment]
+            pub fn #setter(mut self, #arguments) -> Self {
+                self.credentials.#field = Some(#value);
+                self
+            }
+        }
+    });
+
+    let token_methods = spec.security_schemes.iter().filter_map(|scheme| {
+        let SecuritySchemeKind::ClientCredentials { token_url } = &scheme.kind else {
+            return None;
+        };
+        let method = token_method_name(scheme);
+        // the token is requested with all the scopes required by the operations, so that it can be shared
+        let mut scopes: Vec<&String> = spec
+            .operations
+            .iter()
+            .flat_map(|o| &o.security)
+            .flat_map(|r| &r.schemes)
+            .filter(|(name, _)| *name == scheme.name)
+            .flat_map(|(_, scopes)| scopes)
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+        let comment = format!(
+            " Returns the access token of the `{}` scheme, requesting a new one when the cached one is missing or expired.",
+            scheme.name
+        );
+        let (asyncness, awaited) = match is_async {
+            true => (quote! { async }, quote! { .await }),
+            false => (TokenStream::new(), TokenStream::new()),
+        };
+        Some(quote! {
+            #[doc = #comment]
+            #asyncness fn #method(&self, credentials: &ClientCredentials) -> Result<String, ClientError> {
+                if let Some(token) = credentials.cached_token() {
+                    return Ok(token);
+                }
+                let request = credentials.token_request(#token_url, &[#(#scopes),*]);
+                let response = self
+                    .transport
+                    .send(request)
+                    #awaited
+                    .map_err(|e| ClientError::Transport(Box::new(e)))?;
+                credentials.store_token(&response)
+            }
+        })
+    });
+
+    quote! {
+        #(#setters)*
+        #(#token_methods)*
+    }
+}
+
+fn token_method_name(scheme: &SecuritySchemeAsRust) -> proc_macro2::Ident {
+    format_ident!("{}_token", scheme.field_name().trim_start_matches("r#"))
+}
+
+/// Returns the statements that attach to the `url` and `headers` variables the credentials of the
+/// first security requirement of an operation whose credentials are all set.
+/// The request is sent without credentials when none of the requirements is satisfied.
+pub(crate) fn tokenize_client_authentication(
+    spec: &models::SpecAsRust,
+    operation: &models::OperationAsRust,
+    is_async: bool,
+) -> TokenStream {
+    let branches = operation
+        .security
+        .iter()
+        .map(|r| requirement_schemes(spec, r))
+        .filter(|schemes| !schemes.is_empty())
+        .map(|schemes| {
+            let fields: Vec<_> = schemes.iter().map(|s| field_tokens(s)).collect();
+            let (pattern, value) = match fields.as_slice() {
+                [field] => (quote! { Some(#field) }, quote! { &self.credentials.#field }),
+                _ => (
+                    quote! { (#(Some(#fields)),*) },
+                    quote! { (#(&self.credentials.#fields),*) },
+                ),
+            };
+            let attachments = schemes.iter().map(|scheme| {
+                let binding = field_tokens(scheme);
+                match &scheme.kind {
+                    SecuritySchemeKind::ApiKey { name, location } => match location {
+                        ParameterLocation::Query => quote! {
+                            url.push(if url.contains('?') { '&' } else { '?' });
+                            url.push_str(&format!("{}={}", encode_component(#name), encode_component(#binding)));
+                        },
+                        // the key joins the cookie parameters, since a request carries a single Cookie header
+                        ParameterLocation::Cookie => quote! {
+                            let cookie = format!("{}={}", #name, encode_component(#binding));
+                            match headers.iter_mut().find(|(name, _)| name == "Cookie") {
+                                Some((_, cookies)) => {
+                                    cookies.push_str("; ");
+                                    cookies.push_str(&cookie);
+                                }
+                                None => headers.push(("Cookie".to_string(), cookie)),
+                            }
+                        },
+                        _ => quote! { headers.push((#name.to_string(), #binding.clone())); },
+                    },
+                    SecuritySchemeKind::Bearer => quote! {
+                        headers.push(("Authorization".to_string(), format!("Bearer {}", #binding)));
+                    },
+                    SecuritySchemeKind::Basic => quote! {
+                        let secret = format!("{}:{}", #binding.0, #binding.1);
+                        headers.push((
+                            "Authorization".to_string(),
+                            format!("Basic {}", base64_encode(secret.as_bytes())),
+                        ));
+                    },
+                    SecuritySchemeKind::ClientCredentials { .. } => {
+                        let method = token_method_name(scheme);
+                        let awaited = match is_async {
+                            true => quote! { .await },
+                            false => TokenStream::new(),
+                        };
+                        quote! {
+                            let token = self.#method(#binding)#awaited?;
+                            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+                        }
+                    }
+                }
+            });
+            quote! {
+                if let #pattern = #value {
+                    #(#attachments)*
+                }
+            }
+        });
+
+    quote! { #(#branches)else* }
+}
+
+/// Returns the credentials extracted by the server from the requests, with one field for each
+/// security scheme, and the helpers used to extract them.
+pub(crate) fn server_credentials(spec: &models::SpecAsRust) -> TokenStream {
+    let schemes: Vec<_> = spec.security_schemes.iter().collect();
+
+    let fields = schemes.iter().map(|scheme| {
+        let field = field_tokens(scheme);
+        let location = describe_location(scheme);
+        let (comment, credentials_type) = match scheme.kind {
+            SecuritySchemeKind::ApiKey { .. } => (
+                format!(" The API key of the `{}` scheme, {location}", scheme.name),
+                quote! { String },
+            ),
+            SecuritySchemeKind::Bearer | SecuritySchemeKind::ClientCredentials { .. } => (
+                format!(" The token of the `{}` scheme, {location}", scheme.name),
+                quote! { String },
+            ),
+            SecuritySchemeKind::Basic => (
+                format!(
+                    " The username and the password of the `{}` scheme, {location}",
+                    scheme.name
+                ),
+                quote! { (String, String) },
+            ),
+        };
+        quote! {
+            #[doc = #comment]
+            pub #field: Option<#credentials_type>
+        }
+    });
+    let extracted = schemes.iter().map(|scheme| {
+        let field = field_tokens(scheme);
+        let value = match &scheme.kind {
+            SecuritySchemeKind::ApiKey { name, location } => match location {
+                ParameterLocation::Query => quote! {
+                    query.iter().find(|(name, _)| name == #name).map(|(_, value)| value.clone())
+                },
+                ParameterLocation::Cookie => quote! {
+                    cookies.iter().find(|(name, _)| name == #name).map(|(_, value)| value.clone())
+                },
+                _ => quote! {
+                    request.headers.get(#name).and_then(|v| v.to_str().ok()).map(ToString::to_string)
+                },
+            },
+            SecuritySchemeKind::Bearer | SecuritySchemeKind::ClientCredentials { .. } => {
+                quote! { authorization(request, "Bearer").map(ToString::to_string) }
+            }
+            SecuritySchemeKind::Basic => {
+                quote! { authorization(request, "Basic").and_then(decode_basic) }
+            }
+        };
+        quote! { #field: #value }
+    });
+    let restricted = schemes.iter().map(|scheme| {
+        let field = field_tokens(scheme);
+        let name = &scheme.name;
+        quote! {
+            #field: match schemes.contains(&#name) {
+                true => Some(self.#field.clone()?),
+                false => None,
+            }
+        }
+    });
+
+    let query = match has_api_key_in(&schemes, ParameterLocation::Query) {
+        true => quote! { let query = query_pairs(request.query)?; },
+        false => TokenStream::new(),
+    };
+    let cookies = match has_api_key_in(&schemes, ParameterLocation::Cookie) {
+        true => quote! { let cookies = cookie_pairs(request.headers); },
+        false => TokenStream::new(),
+    };
+    let uses_authorization = schemes
+        .iter()
+        .any(|s| !matches!(s.kind, SecuritySchemeKind::ApiKey { .. }));
+    let authorization = match uses_authorization {
+        true => quote! {
+            /// Returns the credentials sent in the `Authorization` header with `scheme`, like `Bearer`.
+            fn authorization<'a>(request: &IncomingRequest<'a>, scheme: &str) -> Option<&'a str> {
+                let value = request.headers.get("Authorization")?.to_str().ok()?;
+                let (name, credentials) = value.split_once(' ')?;
+                name.eq_ignore_ascii_case(scheme).then(|| credentials.trim())
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let has_basic = schemes.iter().any(|s| s.kind == SecuritySchemeKind::Basic);
+    let decode_basic = match has_basic {
+        true => quote! {
+            /// Decodes the username and the password sent with the `Basic` scheme, separated by `:` and encoded as base64.
+            fn decode_basic(credentials: &str) -> Option<(String, String)> {
+                let mut bytes = Vec::with_capacity(credentials.len() / 4 * 3);
+                let mut n = 0u32;
+                let mut bits = 0;
+                for c in credentials.trim_end_matches('=').bytes() {
+                    let value = match c {
+                        b'A'..=b'Z' => c - b'A',
+                        b'a'..=b'z' => c - b'a' + 26,
+                        b'0'..=b'9' => c - b'0' + 52,
+                        b'+' => 62,
+                        b'/' => 63,
+                        _ => return None,
+                    };
+                    n = (n << 6) | u32::from(value);
+                    bits += 6;
+                    if bits >= 8 {
+                        bits -= 8;
+                        bytes.push((n >> bits) as u8);
+                    }
+                }
+                let decoded = String::from_utf8(bytes).ok()?;
+                let (username, password) = decoded.split_once(':')?;
+                Some((username.to_string(), password.to_string()))
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// The credentials sent with a request, one for each security scheme of the spec.
+        /// Only the ones of the security requirement used to authenticate the request are set.
+        #[derive(Clone, Default)]
+        pub struct Credentials {
+            #(#fields,)*
+        }
+
+        impl std::fmt::Debug for Credentials {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("Credentials").finish_non_exhaustive()
+            }
+        }
+
+        impl Credentials {
+            /// Extracts from a request the credentials of every scheme.
+            pub fn from_request(request: &IncomingRequest<'_>) -> Result<Self, RequestError> {
+                #query
+                #cookies
+                Ok(Self {
+                    #(#extracted,)*
+                })
+            }
+
+            /// Returns the credentials of `schemes`, named like in the spec, if all of them are set.
+            fn only(&self, schemes: &[&str]) -> Option<Self> {
+                Some(Self {
+                    #(#restricted,)*
+                })
+            }
+        }
+
+        /// A security requirement of an operation, passed to the server together with the credentials
+        /// that satisfy it, so that it can check the scopes that the operation requires.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct SecurityRequirement {
+            /// The name of the method of the server that implements the operation, like `add_pet`
+            pub operation: &'static str,
+            /// The security schemes of the requirement, named like in the spec
+            pub schemes: &'static [&'static str],
+            /// The scopes required by the OAuth2 and OpenID Connect schemes of the requirement
+            pub scopes: &'static [&'static str],
+        }
+
+        /// Returns the first of `requirements` whose credentials are all sent with a request,
+        /// together with these credentials.
+        fn find_credentials(
+            request: &IncomingRequest<'_>,
+            requirements: &[SecurityRequirement],
+        ) -> Result<Option<(Credentials, SecurityRequirement)>, RequestError> {
+            let credentials = Credentials::from_request(request)?;
+            Ok(requirements
+                .iter()
+                .find_map(|requirement| Some((credentials.only(requirement.schemes)?, *requirement))))
+        }
+
+        #authorization
+        #decode_basic
+    }
+}
+
+/// Returns the statements that authenticate a request in the `principal` variable, failing with
+/// `401 Unauthorized` when the credentials are missing or rejected by the server.
+/// The server receives the requirement satisfied by the credentials, with the scopes it requires.
+/// When the authentication of the operation is optional, the principal is `None` if no credentials are sent.
+pub(crate) fn tokenize_server_authentication(
+    spec: &models::SpecAsRust,
+    operation: &models::OperationAsRust,
+) -> TokenStream {
+    let method_name = operation.method_name();
+    let method_name = method_name.trim_start_matches("r#");
+    let requirements = operation
+        .security
+        .iter()
+        .filter(|r| !r.schemes.is_empty())
+        .map(|requirement| {
+            let schemes = requirement_schemes(spec, requirement);
+            let names = schemes.iter().map(|s| &s.name);
+            let scopes = requirement_scopes(&schemes, requirement);
+            quote! {
+                SecurityRequirement {
+                    operation: #method_name,
+                    schemes: &[#(#names),*],
+                    scopes: &[#(#scopes),*],
+                }
+            }
+        });
+    let found = quote! { find_credentials(&request, &[#(#requirements),*])? };
+    let challenge = authentication_challenge(spec, operation);
+    let unauthorized = quote! { RequestError::Unauthorized(#challenge) };
+
+    match is_authentication_optional(operation) {
+        true => quote! {
+            let principal = match #found {
+                Some((credentials, requirement)) => Some(
+                    server
+                        .authenticate(credentials, requirement)
+                        .await
+                        .ok_or(#unauthorized)?,
+                ),
+                None => None,
+            };
+        },
+        false => quote! {
+            let (credentials, requirement) = #found.ok_or(#unauthorized)?;
+            let principal = server
+                .authenticate(credentials, requirement)
+                .await
+                .ok_or(#unauthorized)?;
+        },
+    }
+}
+
+/// Returns the scopes of a requirement, in the order of the spec and without duplicates,
+/// for the schemes that are declared.
+fn requirement_scopes<'a>(
+    schemes: &[&SecuritySchemeAsRust],
+    requirement: &'a models::SecurityRequirementAsRust,
+) -> Vec<&'a String> {
+    let mut scopes: Vec<&String> = Vec::new();
+    for (_, required) in requirement
+        .schemes
+        .iter()
+        .filter(|(name, _)| schemes.iter().any(|s| &s.name == name))
+    {
+        for scope in required {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+    }
+    scopes
+}
+
+/// Returns the value of the `WWW-Authenticate` header sent with the `401 Unauthorized` responses
+/// of an operation: one challenge for each scheme of its requirements, like `Bearer scope="pets:write"`
+/// as RFC 6750 describes it for the tokens, or `Basic realm="basic_auth"` named after the scheme.
+/// The API keys have no standard challenge, so they are named `ApiKey` with the name of the key.
+fn authentication_challenge(
+    spec: &models::SpecAsRust,
+    operation: &models::OperationAsRust,
+) -> String {
+    let mut challenges: Vec<String> = Vec::new();
+    for requirement in &operation.security {
+        let schemes = requirement_schemes(spec, requirement);
+        for scheme in &schemes {
+            let challenge = match &scheme.kind {
+                SecuritySchemeKind::Bearer | SecuritySchemeKind::ClientCredentials { .. } => {
+                    let scopes = requirement
+                        .schemes
+                        .iter()
+                        .filter(|(name, _)| *name == scheme.name)
+                        .flat_map(|(_, scopes)| scopes)
+                        .map(String::as_str)
+                        .collect::<Vec<_>>();
+                    match scopes.is_empty() {
+                        true => "Bearer".to_string(),
+                        false => format!("Bearer scope=\"{}\"", scopes.join(" ")),
+                    }
+                }
+                SecuritySchemeKind::Basic => format!("Basic realm=\"{}\"", scheme.name),
+                SecuritySchemeKind::ApiKey { name, .. } => format!("ApiKey realm=\"{name}\""),
+            };
+            if !challenges.contains(&challenge) {
+                challenges.push(challenge);
+            }
+        }
+    }
+    challenges.join(", ")
+}
+
+/// Returns true if an operation requires credentials, unless its authentication is optional.
+pub(crate) fn is_authenticated(operation: &models::OperationAsRust) -> bool {
+    operation.security.iter().any(|r| !r.schemes.is_empty())
+}
+
+/// Returns true if an operation can be called without credentials,
+/// because one of its security requirements has no schemes.
+pub(crate) fn is_authentication_optional(operation: &models::OperationAsRust) -> bool {
+    operation.security.iter().any(|r| r.schemes.is_empty())
+}
@@ -0,0 +1,848 @@
+//! Generation of the server side of the operations of a spec: the trait implemented by the services
+//! and the code that extracts the requests and encodes the responses, shared by all the routers.
+
+use crate::generating::security;
+use crate::generating::validation::location_token;
+use crate::models;
+use crate::models::{
+    ParameterAsRust, ParameterLocation, PayloadKind, StatusCode, pointer_token,
+    tokenize_decode_body, tokenize_encode_body,
+};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+
+/// Returns the `server` module, with the trait that has one method for each operation,
+/// the code that calls it from the requests and the router of a framework.
+pub(crate) fn server(spec: &models::SpecAsRust, router: TokenStream) -> TokenStream {
+    let runtime = server_runtime(spec);
+    let is_authenticated = spec.operations.iter().any(security::is_authenticated);
+    let (credentials, authenticate) = match is_authenticated {
+        true => (
+            security::server_credentials(spec),
+            quote! {
+                /// The principal authenticated from the credentials of a request, like a user,
+                /// passed to the methods of the operations that require authentication.
+                type Principal: Send + 'static;
+
+                /// Authenticates the credentials sent with a request, that satisfy `requirement`, one of the
+                /// security requirements of the operation. The requirement tells the operation and the scopes
+                /// that the principal must be granted. Returning `None` rejects them with `401 Unauthorized`.
+                fn authenticate(
+                    &self,
+                    credentials: Credentials,
+                    requirement: SecurityRequirement,
+                ) -> impl std::future::Future<Output = Option<Self::Principal>> + Send;
+            },
+        ),
+        false => (TokenStream::new(), TokenStream::new()),
+    };
+    let methods = spec.operations.iter().map(tokenize_trait_method);
+    let parameters = spec
+        .operations
+        .iter()
+        .filter(|o| !o.parameters.is_empty())
+        .map(tokenize_from_request);
+    let bodies = spec.operations.iter().map(tokenize_decode_request_body);
+    let responses = spec
+        .operations
+        .iter()
+        .filter(|o| !o.responses.is_empty())
+        .map(tokenize_into_parts);
+    let calls = spec.operations.iter().map(|o| tokenize_call(spec, o));
+
+    quote! {
+        /// The server side of the spec: the trait implemented by the service and the router that calls it.
+        pub mod server {
+            use super::*;
+
+            #runtime
+            #credentials
+
+            /// The operations of the spec, implemented by the service and called by the router.
+            ///
+            /// Each method receives the parameters and the body already extracted from the request,
+            /// and returns the response of the operation.
+            pub trait Server: Send + Sync + 'static {
+                #authenticate
+                #(#methods)*
+            }
+
+            #(#parameters)*
+            #(#bodies)*
+            #(#responses)*
+            #(#calls)*
+
+            #router
+        }
+    }
+}
+
+/// Returns the items used by every router: the request and the response exchanged with the
+/// framework, the error returned when a request cannot be extracted and the helpers to extract it.
+fn server_runtime(spec: &models::SpecAsRust) -> TokenStream {
+    let parameters: Vec<_> = spec.operations.iter().flat_map(|o| &o.parameters).collect();
+    // the API keys are extracted like the parameters in the same location
+    let api_keys: Vec<_> = match spec.operations.iter().any(security::is_authenticated) {
+        true => spec.security_schemes.iter().collect(),
+        false => vec![],
+    };
+    let has_location = |location| {
+        parameters.iter().any(|p| p.location == location)
+            || security::has_api_key_in(&api_keys, location)
+    };
+
+    let parse_one = match parameters.iter().any(|p| !p.is_array) {
+        true => quote! {
+            /// Parses the first value of a parameter, if there is any.
+            fn parse_one<'a, T: std::str::FromStr>(
+                pointer: &'static str,
+                mut values: impl Iterator<Item = &'a str>,
+            ) -> Result<Option<T>, RequestError> {
+                match values.next() {
+                    None => Ok(None),
+                    Some(value) => value
+                        .parse()
+                        .map(Some)
+                        .map_err(|_| RequestError::InvalidParameter(pointer)),
+                }
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let parse_many = match parameters.iter().any(|p| p.is_array) {
+        true => quote! {
+            /// Parses all the values of an array parameter, if there is any.
+            fn parse_many<'a, T: std::str::FromStr>(
+                pointer: &'static str,
+                values: impl Iterator<Item = &'a str>,
+            ) -> Result<Option<Vec<T>>, RequestError> {
+                let values = values
+                    .map(|value| value.parse().map_err(|_| RequestError::InvalidParameter(pointer)))
+                    .collect::<Result<Vec<T>, _>>()?;
+                Ok((!values.is_empty()).then_some(values))
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let query_pairs = match has_location(ParameterLocation::Query) {
+        true => quote! {
+            /// Splits the query string in its decoded names and values.
+            fn query_pairs(query: Option<&str>) -> Result<Vec<(String, String)>, RequestError> {
+                serde_urlencoded::from_str(query.unwrap_or_default())
+                    .map_err(|_| RequestError::InvalidQuery)
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let cookie_pairs = match has_location(ParameterLocation::Cookie) {
+        true => quote! {
+            /// Splits the `Cookie` headers in the names and the decoded values of the cookies.
+            fn cookie_pairs(headers: &http::HeaderMap) -> Vec<(String, String)> {
+                headers
+                    .get_all("Cookie")
+                    .iter()
+                    .filter_map(|v| v.to_str().ok())
+                    .flat_map(|v| v.split(';'))
+                    .filter_map(|cookie| cookie.trim().split_once('='))
+                    .map(|(name, value)| (name.to_string(), decode_component(value)))
+                    .collect()
+            }
+
+            /// Decodes a percent-encoded value, keeping as they are the sequences that are not valid.
+            fn decode_component(value: &str) -> String {
+                let bytes = value.as_bytes();
+                let mut decoded = Vec::with_capacity(bytes.len());
+                let mut i = 0;
+                while i < bytes.len() {
+                    let escaped = bytes
+                        .get(i + 1..i + 3)
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match (bytes[i], escaped) {
+                        (b'%', Some(byte)) => {
+                            decoded.push(byte);
+                            i += 3;
+                        }
+                        (byte, _) => {
+                            decoded.push(byte);
+                            i += 1;
+                        }
+                    }
+                }
+                String::from_utf8_lossy(&decoded).into_owned()
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let has_body = spec.operations.iter().any(|o| o.request_body.is_some());
+    let validate = match has_body || !parameters.is_empty() {
+        true => quote! {
+            /// Validates an argument extracted from a request, failing with every constraint that it does not satisfy.
+            fn validate<T: Validate>(value: &T, pointer: &str) -> Result<(), RequestError> {
+                let mut errors = Vec::new();
+                value.validate(pointer, &mut errors);
+                match errors.is_empty() {
+                    true => Ok(()),
+                    false => Err(RequestError::Invalid(errors)),
+                }
+            }
+        },
+        false => TokenStream::new(),
+    };
+    // the required properties are looked for in the payloads that the request bodies can have
+    let request_kinds: Vec<_> = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.request_body)
+        .flat_map(|r| &r.body.content)
+        .map(|c| &c.kind)
+        .collect();
+    let form_properties = match request_kinds.contains(&&PayloadKind::Form) {
+        true => quote! {
+            "application/x-www-form-urlencoded" => {
+                serde_urlencoded::from_bytes::<Vec<(String, String)>>(body)
+                    .ok()
+                    .map(|pairs| pairs.into_iter().map(|(name, _)| name).collect())
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let multipart_properties = match request_kinds
+        .iter()
+        .any(|k| matches!(k, PayloadKind::Multipart(_)))
+    {
+        true => quote! {
+            "multipart/form-data" => decode_multipart(content_type, body)
+                .ok()
+                .map(|parts| parts.into_iter().map(|part| part.name).collect()),
+        },
+        false => TokenStream::new(),
+    };
+    let decode_body = match has_body {
+        true => quote! {
+            /// Returns the names of the properties of a body, read as the payload of its content type,
+            /// or None if the body cannot be read. The properties that are null in JSON are left out.
+            fn body_properties(content_type: Option<&str>, body: &[u8]) -> Option<Vec<String>> {
+                let essence = content_type
+                    .and_then(|c| c.split(';').next())
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase();
+                match essence.as_str() {
+                    #form_properties
+                    #multipart_properties
+                    _ => serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(body)
+                        .ok()
+                        .map(|properties| {
+                            properties
+                                .into_iter()
+                                .filter(|(_, value)| !value.is_null())
+                                .map(|(name, _)| name)
+                                .collect()
+                        }),
+                }
+            }
+
+            /// Decodes the body of a request with `decode` and validates it, unless the request has no body.
+            fn decode_body<T: Validate>(
+                request: &IncomingRequest<'_>,
+                decode: fn(Option<&str>, &[u8]) -> Result<T, ResponseError>,
+            ) -> Result<Option<T>, RequestError> {
+                if request.body.is_empty() {
+                    return Ok(None);
+                }
+                let content_type = request
+                    .headers
+                    .get("Content-Type")
+                    .and_then(|v| v.to_str().ok());
+                // serde stops at the first missing property, so they are all looked for before decoding
+                let properties = match T::REQUIRED.is_empty() {
+                    true => None,
+                    false => body_properties(content_type, request.body),
+                };
+                if let Some(properties) = properties {
+                    let errors: Vec<_> = T::REQUIRED
+                        .iter()
+                        .filter(|name| !properties.iter().any(|property| property == *name))
+                        .map(|name| ValidationError {
+                            pointer: format!("/body/{}", name.replace('~', "~0").replace('/', "~1")),
+                            message: "is required".to_string(),
+                        })
+                        .collect();
+                    if !errors.is_empty() {
+                        return Err(RequestError::Invalid(errors));
+                    }
+                }
+
+                let decoded = decode(content_type, request.body).map_err(RequestError::InvalidBody)?;
+                validate(&decoded, "/body")?;
+                Ok(Some(decoded))
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    let streamed: Vec<_> = spec
+        .operations
+        .iter()
+        .flat_map(|o| &o.responses)
+        .flat_map(|r| &r.body)
+        .flat_map(|b| &b.content)
+        .map(|c| &c.kind)
+        .filter(|k| k.is_streaming())
+        .collect();
+    let encode_json_lines = match streamed.contains(&&PayloadKind::JsonLines) {
+        true => quote! {
+            /// Encodes a stream of items to JSON lines, one item serialized as JSON for each line.
+            fn encode_json_lines<T: serde::Serialize + Send + 'static>(items: Streaming<T>) -> ByteStream {
+                ByteStream::new(MapStream {
+                    stream: items,
+                    map: |item: Result<T, Box<dyn std::error::Error + Send + Sync>>| {
+                        let mut line = serde_json::to_vec(&item?)?;
+                        line.push(b'\n');
+                        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(line)
+                    },
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+    let encode_events = match streamed.contains(&&PayloadKind::EventStream) {
+        true => quote! {
+            /// Encodes a stream of events to server-sent events. The data is serialized as JSON,
+            /// unless it is a string that is sent as it is, and split in one `data` field for each line.
+            fn encode_events<T: serde::Serialize + Send + 'static>(
+                events: Streaming<ServerSentEvent<T>>,
+            ) -> ByteStream {
+                ByteStream::new(MapStream {
+                    stream: events,
+                    map: |event: Result<ServerSentEvent<T>, Box<dyn std::error::Error + Send + Sync>>| {
+                        let event = event?;
+                        let mut frame = String::new();
+                        if let Some(name) = &event.event {
+                            frame.push_str(&format!("event: {}\n", name));
+                        }
+                        if let Some(id) = &event.id {
+                            frame.push_str(&format!("id: {}\n", id));
+                        }
+                        if let Some(retry) = event.retry {
+                            frame.push_str(&format!("retry: {}\n", retry));
+                        }
+                        let data = match serde_json::to_value(&event.data)? {
+                            serde_json::Value::String(data) => data,
+                            data => data.to_string(),
+                        };
+                        for line in data.lines() {
+                            frame.push_str(&format!("data: {}\n", line));
+                        }
+                        frame.push('\n');
+                        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(frame.into_bytes())
+                    },
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+    // the streamed bodies cannot be cloned
+    let (outgoing_derive, stream_field) = match streamed.is_empty() {
+        true => (
+            quote! { #[derive(Debug, Clone, Default)] },
+            TokenStream::new(),
+        ),
+        false => (
+            quote! { #[derive(Debug, Default)] },
+            quote! {
+                /// The body of the response when it is streamed, sent instead of `body`
+                pub stream: Option<ByteStream>,
+            },
+        ),
+    };
+
+    quote! {
+        /// Errors that can happen while extracting the arguments of an operation from a request.
+        /// They are answered with `400 Bad Request`, or `401 Unauthorized` for the missing credentials,
+        /// and the list of the errors as JSON.
+        #[derive(Debug)]
+        pub enum RequestError {
+            /// A required parameter is missing, the value is its JSON pointer like `/query/limit`.
+            MissingParameter(&'static str),
+            /// A parameter cannot be parsed to its type, the value is its JSON pointer like `/query/limit`.
+            InvalidParameter(&'static str),
+            /// The query string cannot be decoded.
+            InvalidQuery,
+            /// A required body is missing.
+            MissingBody,
+            /// The body cannot be decoded.
+            InvalidBody(ResponseError),
+            /// The arguments do not satisfy the constraints of the spec.
+            Invalid(Vec<ValidationError>),
+            /// The credentials required by the operation are missing or rejected by the server,
+            /// the value is the challenge sent in the `WWW-Authenticate` header, like `Bearer scope="pets:write"`.
+            Unauthorized(&'static str),
+        }
+
+        impl RequestError {
+            /// Returns the status code of the response sent for the error.
+            pub fn status(&self) -> u16 {
+                match self {
+                    Self::Unauthorized(_) => 401,
+                    _ => 400,
+                }
+            }
+
+            /// Returns the errors sent in the body of the response, addressed by JSON pointers.
+            pub fn errors(&self) -> Vec<ValidationError> {
+                let error = |pointer: &str, message: String| ValidationError {
+                    pointer: pointer.to_string(),
+                    message,
+                };
+                match self {
+                    Self::MissingParameter(pointer) => vec![error(pointer, "is required".to_string())],
+                    Self::InvalidParameter(pointer) => {
+                        vec![error(pointer, "cannot be parsed to its type".to_string())]
+                    }
+                    Self::InvalidQuery => vec![error("/query", "cannot be decoded".to_string())],
+                    Self::MissingBody => vec![error("/body", "is required".to_string())],
+                    Self::InvalidBody(e) => vec![error("/body", e.to_string())],
+                    Self::Invalid(errors) => errors.clone(),
+                    Self::Unauthorized(_) => vec![error("", "missing or invalid credentials".to_string())],
+                }
+            }
+        }
+
+        impl std::fmt::Display for RequestError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::MissingParameter(pointer) => write!(f, "missing required parameter {}", pointer),
+                    Self::InvalidParameter(pointer) => write!(f, "invalid value for parameter {}", pointer),
+                    Self::InvalidQuery => write!(f, "cannot decode the query string"),
+                    Self::MissingBody => write!(f, "missing required body"),
+                    Self::InvalidBody(e) => write!(f, "cannot decode the body: {}", e),
+                    Self::Invalid(errors) => {
+                        let errors: Vec<_> = errors
+                            .iter()
+                            .map(|e| format!("{} {}", e.pointer, e.message))
+                            .collect();
+                        write!(f, "invalid request: {}", errors.join(", "))
+                    }
+                    Self::Unauthorized(_) => write!(f, "missing or invalid credentials"),
+                }
+            }
+        }
+
+        impl std::error::Error for RequestError {}
+
+        /// A request received by a router, with everything needed to extract the arguments of an operation.
+        #[derive(Debug, Clone, Copy)]
+        pub struct IncomingRequest<'a> {
+            /// The values of the template expressions of the path, keyed by their name in the spec
+            pub path: &'a std::collections::HashMap<String, String>,
+            /// The query string, without the leading `?`
+            pub query: Option<&'a str>,
+            /// The headers of the request
+            pub headers: &'a http::HeaderMap,
+            /// The body of the request, empty if the request has no body
+            pub body: &'a [u8],
+        }
+
+        /// A response produced by the server, ready to be sent by a router.
+        #outgoing_derive
+        pub struct OutgoingResponse {
+            /// The status code of the response
+            pub status: u16,
+            /// The content type of the body, if the response has one
+            pub content_type: Option<String>,
+            /// The headers declared by the response in the spec, sent together with the content type
+            pub headers: http::HeaderMap,
+            /// The body of the response
+            pub body: Vec<u8>,
+            #stream_field
+        }
+
+        impl OutgoingResponse {
+            /// Returns a response without body.
+            pub fn empty(status: u16) -> Self {
+                Self {
+                    status,
+                    ..Self::default()
+                }
+            }
+
+            /// Returns the `500 Internal Server Error` sent when the body of a response cannot be serialized.
+            pub fn internal_error(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+                Self {
+                    status: 500,
+                    content_type: Some("text/plain".to_string()),
+                    body: format!("cannot serialize the body of the response: {}", error).into_bytes(),
+                    ..Self::default()
+                }
+            }
+
+            /// Returns the `400 Bad Request` sent when a request is not valid, with the errors as JSON
+            /// like `{"errors": [{"pointer": "/body/name", "message": "is required"}]}`.
+            pub fn bad_request(errors: Vec<ValidationError>) -> Self {
+                Self {
+                    status: 400,
+                    content_type: Some("application/json".to_string()),
+                    body: serde_json::to_vec(&serde_json::json!({ "errors": errors }))
+                        .unwrap_or_default(),
+                    ..Self::default()
+                }
+            }
+        }
+
+        impl From<&RequestError> for OutgoingResponse {
+            fn from(e: &RequestError) -> Self {
+                let mut response = Self {
+                    status: e.status(),
+                    ..Self::bad_request(e.errors())
+                };
+                // the 401 responses tell how to authenticate, as RFC 9110 requires
+                if let RequestError::Unauthorized(challenge) = e {
+                    if let Ok(value) = http::HeaderValue::from_str(challenge) {
+                        response.headers.insert(http::header::WWW_AUTHENTICATE, value);
+                    }
+                }
+                response
+            }
+        }
+
+        impl From<RequestError> for OutgoingResponse {
+            fn from(e: RequestError) -> Self {
+                Self::from(&e)
+            }
+        }
+
+        #parse_one
+        #parse_many
+        #query_pairs
+        #cookie_pairs
+        #validate
+        #decode_body
+        #encode_json_lines
+        #encode_events
+    }
+}
+
+/// Returns the type of the body received by the method of an operation, if it has one.
+fn body_argument(operation: &models::OperationAsRust) -> Option<TokenStream> {
+    let request_body = operation.request_body.as_ref()?;
+    let body_type = format_ident!("{}", request_body.body.name);
+    match request_body.is_required {
+        true => Some(quote! { #body_type }),
+        false => Some(quote! { Option<#body_type> }),
+    }
+}
+
+/// Returns the type returned by the method of an operation: its response enum,
+/// or the status code when the operation declares no responses.
+fn return_type(operation: &models::OperationAsRust) -> TokenStream {
+    match operation.responses.is_empty() {
+        true => quote! { u16 },
+        false => {
+            let response_enum = format_ident!("{}", operation.response_enum_name());
+            quote! { #response_enum }
+        }
+    }
+}
+
+/// Renders the method of the server trait that handles an operation.
+fn tokenize_trait_method(operation: &models::OperationAsRust) -> TokenStream {
+    let method_name = match operation.method_name().parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn method name to tokens: {}", e)),
+    };
+    let comment = match &operation.description {
+        Some(c) if !c.is_empty() => quote! { #[doc = #c] },
+        _ => TokenStream::new(),
+    };
+
+    let mut arguments = Vec::new();
+    if security::is_authenticated(operation) {
+        arguments.push(match security::is_authentication_optional(operation) {
+            true => quote! { principal: Option<Self::Principal> },
+            false => quote! { principal: Self::Principal },
+        });
+    }
+    if !operation.parameters.is_empty() {
+        let params_type = format_ident!("{}", operation.parameters_struct_name());
+        arguments.push(quote! { params: #params_type });
+    }
+    if let Some(body_type) = body_argument(operation) {
+        arguments.push(quote! { body: #body_type });
+    }
+    let return_type = return_type(operation);
+
+    quote! {
+        #comment
+        fn #method_name(
+            &self,
+            #(#arguments),*
+        ) -> impl std::future::Future<Output = #return_type> + Send;
+    }
+}
+
+/// Renders the function that extracts the parameters struct of an operation from a request.
+fn tokenize_from_request(operation: &models::OperationAsRust) -> TokenStream {
+    let params_type = format_ident!("{}", operation.parameters_struct_name());
+    let has_location = |location| operation.parameters.iter().any(|p| p.location == location);
+
+    let query = match has_location(ParameterLocation::Query) {
+        true => quote! { let query = query_pairs(request.query)?; },
+        false => TokenStream::new(),
+    };
+    let cookies = match has_location(ParameterLocation::Cookie) {
+        true => quote! { let cookies = cookie_pairs(request.headers); },
+        false => TokenStream::new(),
+    };
+    let fields = operation.parameters.iter().map(tokenize_parameter_field);
+
+    quote! {
+        impl #params_type {
+            /// Extracts the parameters from a request, failing if a required parameter is missing
+            /// or if a parameter cannot be parsed to its type.
+            pub fn from_request(request: &IncomingRequest<'_>) -> Result<Self, RequestError> {
+                #query
+                #cookies
+                Ok(Self {
+                    #(#fields,)*
+                })
+            }
+        }
+    }
+}
+
+/// Returns the field of the parameters struct initialized with the value extracted from the request.
+/// Arrays that are not exploded, and the ones sent in the path or in the headers,
+/// are received as a single comma separated value.
+fn tokenize_parameter_field(parameter: &ParameterAsRust) -> TokenStream {
+    let field_name = match parameter.field_name.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    };
+    let name = &parameter.name;
+
+    let values = match parameter.location {
+        ParameterLocation::Path => {
+            quote! { request.path.get(#name).map(String::as_str).into_iter() }
+        }
+        ParameterLocation::Query => quote! {
+            query.iter().filter(|(name, _)| name == #name).map(|(_, value)| value.as_str())
+        },
+        ParameterLocation::Header => quote! {
+            request.headers.get_all(#name).iter().filter_map(|v| v.to_str().ok())
+        },
+        ParameterLocation::Cookie => quote! {
+            cookies.iter().filter(|(name, _)| name == #name).map(|(_, value)| value.as_str())
+        },
+    };
+    let is_exploded = parameter.explode
+        && matches!(
+            parameter.location,
+            ParameterLocation::Query | ParameterLocation::Cookie
+        );
+
+    let pointer = format!(
+        "/{}/{}",
+        location_token(parameter.location),
+        pointer_token(name)
+    );
+    let parsed = match (parameter.is_array, is_exploded) {
+        (false, _) => quote! { parse_one(#pointer, #values)? },
+        (true, true) => quote! { parse_many(#pointer, #values)? },
+        (true, false) => quote! { parse_many(#pointer, #values.flat_map(|v| v.split(',')))? },
+    };
+    match parameter.is_required {
+        true => quote! { #field_name: #parsed.ok_or(RequestError::MissingParameter(#pointer))? },
+        false => quote! { #field_name: #parsed },
+    }
+}
+
+/// Returns the name of the function that decodes the body of the request of an operation.
+fn decode_function_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!(
+        "decode_{}_body",
+        operation.method_name().trim_start_matches("r#")
+    )
+}
+
+/// Renders the function that decodes the body of the request of an operation, if it has one.
+fn tokenize_decode_request_body(operation: &models::OperationAsRust) -> TokenStream {
+    let Some(request_body) = &operation.request_body else {
+        return TokenStream::new();
+    };
+    let function_name = decode_function_name(operation);
+    let body_type = format_ident!("{}", request_body.body.name);
+    let decode_body = tokenize_decode_body(&request_body.body);
+    let content_type = match request_body.body.reads_content_type() {
+        true => quote! { content_type },
+        false => quote! { _content_type },
+    };
+
+    quote! {
+        fn #function_name(
+            #content_type: Option<&str>,
+            body: &[u8],
+        ) -> Result<#body_type, ResponseError> {
+            let decoded = #decode_body;
+            Ok(decoded)
+        }
+    }
+}
+
+/// Renders the function that encodes the response enum of an operation to the response sent by the router.
+fn tokenize_into_parts(operation: &models::OperationAsRust) -> TokenStream {
+    let enum_name = format_ident!("{}", operation.response_enum_name());
+    let map_error = quote! { Box::<dyn std::error::Error + Send + Sync>::from };
+
+    let arms = operation.responses.iter().map(|response| {
+        let variant = format_ident!("{}", response.status.variant_name());
+        // the fields of the variants are the status code when it is not exact, the body and the headers
+        let mut fields = Vec::new();
+        let status = match response.status {
+            StatusCode::Exact(code) => {
+                let code = Literal::u16_unsuffixed(code);
+                quote! { #code }
+            }
+            _ => {
+                fields.push(quote! { status });
+                quote! { status }
+            }
+        };
+        if response.body.is_some() {
+            fields.push(quote! { body });
+        }
+        let headers = match response.headers.is_empty() {
+            true => TokenStream::new(),
+            false => {
+                fields.push(quote! { headers });
+                quote! { headers: headers.to_headers()?, }
+            }
+        };
+        let pattern = match fields.is_empty() {
+            true => quote! { Self::#variant },
+            false => quote! { Self::#variant(#(#fields),*) },
+        };
+        match &response.body {
+            Some(body) if body.is_streaming() => {
+                let encoded = tokenize_encode_body(body, &map_error);
+                quote! {
+                    #pattern => {
+                        let (content_type, stream) = #encoded;
+                        OutgoingResponse {
+                            content_type: Some(content_type),
+                            #headers
+                            stream: Some(stream),
+                            ..OutgoingResponse::empty(#status)
+                        }
+                    }
+                }
+            }
+            Some(body) => {
+                let encoded = tokenize_encode_body(body, &map_error);
+                quote! {
+                    #pattern => {
+                        let (content_type, body) = #encoded;
+                        OutgoingResponse {
+                            content_type: Some(content_type),
+                            #headers
+                            body,
+                            ..OutgoingResponse::empty(#status)
+                        }
+                    }
+                }
+            }
+            None if response.headers.is_empty() => {
+                quote! { #pattern => OutgoingResponse::empty(#status) }
+            }
+            None => quote! {
+                #pattern => OutgoingResponse {
+                    #headers
+                    ..OutgoingResponse::empty(#status)
+                }
+            },
+        }
+    });
+
+    quote! {
+        impl #enum_name {
+            /// Encodes the response to its status code, content type, headers and body.
+            pub fn into_parts(
+                self,
+            ) -> Result<OutgoingResponse, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(match self {
+                    #(#arms,)*
+                })
+            }
+        }
+    }
+}
+
+/// Returns the name of the function that calls the method of the server trait of an operation.
+pub(crate) fn call_function_name(operation: &models::OperationAsRust) -> proc_macro2::Ident {
+    format_ident!("call_{}", operation.method_name().trim_start_matches("r#"))
+}
+
+/// Renders the function that extracts the arguments of an operation from a request,
+/// calls the method of the server trait and encodes its response.
+fn tokenize_call(spec: &models::SpecAsRust, operation: &models::OperationAsRust) -> TokenStream {
+    let function_name = call_function_name(operation);
+    let method_name = match operation.method_name().parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn method name to tokens: {}", e)),
+    };
+
+    let mut extracted = Vec::new();
+    let mut arguments = Vec::new();
+    // the credentials are checked first, so that the requests without them learn nothing else
+    if security::is_authenticated(operation) {
+        extracted.push(security::tokenize_server_authentication(spec, operation));
+        arguments.push(quote! { principal });
+    }
+    if !operation.parameters.is_empty() {
+        let params_type = format_ident!("{}", operation.parameters_struct_name());
+        extracted.push(quote! {
+            let params = #params_type::from_request(&request)?;
+            validate(&params, "")?;
+        });
+        arguments.push(quote! { params });
+    }
+    if let Some(request_body) = &operation.request_body {
+        let decode_function = decode_function_name(operation);
+        let decoded = quote! { decode_body(&request, #decode_function)? };
+        extracted.push(match request_body.is_required {
+            true => quote! { let body = #decoded.ok_or(RequestError::MissingBody)?; },
+            false => quote! { let body = #decoded; },
+        });
+        arguments.push(quote! { body });
+    }
+    let request = match extracted.is_empty() {
+        true => quote! { _request },
+        false => quote! { request },
+    };
+
+    let response = match operation.responses.is_empty() {
+        true => quote! { OutgoingResponse::empty(response) },
+        false => quote! {
+            response
+                .into_parts()
+                .unwrap_or_else(OutgoingResponse::internal_error)
+        },
+    };
+    let comment = format!(
+        " Calls the method of the server for `{} {}`.",
+        operation.method, operation.path
+    );
+
+    quote! {
+        #[doc = #comment]
+        async fn #function_name<S: Server>(
+            server: &S,
+            #request: IncomingRequest<'_>,
+        ) -> Result<OutgoingResponse, RequestError> {
+            #(#extracted)*
+            let response = server.#method_name(#(#arguments),*).await;
+            Ok(#response)
+        }
+    }
+}
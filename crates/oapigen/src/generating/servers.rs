@@ -0,0 +1,240 @@
+//! Generation of the servers of a spec, that the client can use as its base URL.
+
+use crate::models;
+use crate::models::{ServerAsRust, ServerVariableAsRust};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+
+fn tokenize_comment(comment: &Option<String>) -> TokenStream {
+    match comment {
+        Some(c) if !c.is_empty() => quote! { #[doc = #c] },
+        _ => TokenStream::new(),
+    }
+}
+
+/// Returns the `Server` enum, with one variant for each server of the spec,
+/// together with the types of the variables of their URLs.
+/// The enum turns into the URL of the server, so that it can be passed to `Client::new`.
+pub(crate) fn servers(spec: &models::SpecAsRust) -> TokenStream {
+    let Some(first) = spec.servers.first() else {
+        return TokenStream::new();
+    };
+
+    let variants = spec.servers.iter().map(|server| {
+        let comment = tokenize_comment(&server.comment);
+        let variant = format_ident!("{}", server.name);
+        match server.variables.is_empty() {
+            true => quote! { #comment #variant },
+            false => {
+                let variables_struct = format_ident!("{}", server.variables_struct_name());
+                quote! { #comment #variant(#variables_struct) }
+            }
+        }
+    });
+    let urls = spec.servers.iter().map(|server| {
+        let variant = format_ident!("{}", server.name);
+        match server.variables.is_empty() {
+            true => {
+                let url = &server.url;
+                quote! { Self::#variant => #url.to_string() }
+            }
+            false => quote! { Self::#variant(server) => server.url() },
+        }
+    });
+    let first_variant = format_ident!("{}", first.name);
+    let default = match first.variables.is_empty() {
+        true => quote! { Self::#first_variant },
+        false => quote! { Self::#first_variant(Default::default()) },
+    };
+    let variables = spec
+        .servers
+        .iter()
+        .filter(|s| !s.variables.is_empty())
+        .map(tokenize_variables);
+
+    quote! {
+        /// The servers of the spec, accepted by the client as its base URL.
+        /// The default one is the first server of the spec, with the default values of its variables.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Server {
+            #(#variants,)*
+        }
+
+        impl Server {
+            /// Returns the URL of the server, with the values of its variables.
+            pub fn url(&self) -> String {
+                match self {
+                    #(#urls,)*
+                }
+            }
+        }
+
+        impl Default for Server {
+            fn default() -> Self {
+                #default
+            }
+        }
+
+        impl std::fmt::Display for Server {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.url())
+            }
+        }
+
+        impl From<Server> for String {
+            fn from(server: Server) -> Self {
+                server.url()
+            }
+        }
+
+        #(#variables)*
+    }
+}
+
+/// Returns the struct that holds the variables of a server, with their default values,
+/// and the enums of the variables that only allow some values.
+fn tokenize_variables(server: &ServerAsRust) -> TokenStream {
+    let variables_struct = format_ident!("{}", server.variables_struct_name());
+    let field = |variable: &ServerVariableAsRust| match variable.field_name().parse::<TokenStream>()
+    {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    };
+
+    let fields = server.variables.iter().map(|variable| {
+        let comment = tokenize_comment(&variable.comment);
+        let field_name = field(variable);
+        let field_type = match variable.values.is_empty() {
+            true => quote! { String },
+            false => {
+                let enum_name = format_ident!("{}", server.variable_enum_name(variable));
+                quote! { #enum_name }
+            }
+        };
+        quote! {
+            #comment
+            pub #field_name: #field_type
+        }
+    });
+    let defaults = server.variables.iter().map(|variable| {
+        let field_name = field(variable);
+        match variable.values.is_empty() {
+            true => {
+                let default = &variable.default;
+                quote! { #field_name: #default.to_string() }
+            }
+            false => quote! { #field_name: Default::default() },
+        }
+    });
+
+    // the variables are replaced by their values, while the rest of the URL is kept as it is
+    let mut template = String::new();
+    let mut values = Vec::new();
+    let mut rest = server.url.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        template.push_str(&escape_braces(&rest[..start]));
+        let name = &rest[start + 1..end];
+        match server.variables.iter().find(|v| v.name == name) {
+            Some(variable) => {
+                let field_name = field(variable);
+                template.push_str("{}");
+                values.push(quote! { self.#field_name });
+            }
+            None => template.push_str(&escape_braces(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    template.push_str(&escape_braces(rest));
+    let template = Literal::string(&template);
+
+    let enums = server
+        .variables
+        .iter()
+        .filter(|v| !v.values.is_empty())
+        .map(|variable| tokenize_variable_enum(server, variable));
+    let comment = format!(
+        " The variables of the `{}` server, `{}`.",
+        server.name, server.url
+    );
+
+    quote! {
+        #[doc = #comment]
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #variables_struct {
+            #(#fields,)*
+        }
+
+        impl Default for #variables_struct {
+            fn default() -> Self {
+                Self {
+                    #(#defaults,)*
+                }
+            }
+        }
+
+        impl #variables_struct {
+            /// Returns the URL of the server, with the values of its variables.
+            pub fn url(&self) -> String {
+                format!(#template, #(#values),*)
+            }
+        }
+
+        #(#enums)*
+    }
+}
+
+/// Returns the enum of the values allowed for a variable of a server,
+/// whose default variant is the default value of the variable.
+fn tokenize_variable_enum(server: &ServerAsRust, variable: &ServerVariableAsRust) -> TokenStream {
+    let enum_name = format_ident!("{}", server.variable_enum_name(variable));
+    let default = variable
+        .values
+        .iter()
+        .position(|(_, value)| *value == variable.default)
+        .unwrap_or_default();
+    let variants = variable.values.iter().enumerate().map(|(i, (variant, _))| {
+        let variant = format_ident!("{}", variant);
+        match i == default {
+            true => quote! { #[default] #variant },
+            false => quote! { #variant },
+        }
+    });
+    let values = variable.values.iter().map(|(variant, value)| {
+        let variant = format_ident!("{}", variant);
+        quote! { Self::#variant => #value }
+    });
+    let comment = format!(
+        " The values of the `{}` variable of the `{}` server.",
+        variable.name, server.name
+    );
+
+    quote! {
+        #[doc = #comment]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub enum #enum_name {
+            #(#variants,)*
+        }
+
+        impl #enum_name {
+            /// Returns the value of the variable in the URL of the server.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#values,)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for #enum_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    }
+}
+
+fn escape_braces(s: &str) -> String {
+    s.replace('{', "{{").replace('}', "}}")
+}
@@ -0,0 +1,162 @@
+//! Generation of the adapters that implement the transport of the client with common HTTP libraries.
+
+use crate::models::TransportLibraries;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Returns the adapter that implements the transport of the async client with a library,
+/// if the library has an async API.
+/// When some responses are streamed, the adapter receives their bodies while they arrive.
+pub(crate) fn async_adapter(library: &TransportLibraries, has_streaming: bool) -> TokenStream {
+    match library {
+        TransportLibraries::Reqwest => reqwest(has_streaming),
+        TransportLibraries::Ureq => TokenStream::new(),
+    }
+}
+
+/// Returns the adapter that implements the transport of the blocking client with a library,
+/// if the library has a blocking API.
+/// When some responses are streamed, the adapter receives their bodies while they arrive.
+pub(crate) fn blocking_adapter(library: &TransportLibraries, has_streaming: bool) -> TokenStream {
+    match library {
+        TransportLibraries::Reqwest => TokenStream::new(),
+        TransportLibraries::Ureq => ureq(has_streaming),
+    }
+}
+
+/// The streaming responses need the `stream` feature of reqwest.
+fn reqwest(has_streaming: bool) -> TokenStream {
+    let send_streaming = match has_streaming {
+        true => quote! {
+            async fn send_streaming(
+                &self,
+                request: HttpRequest,
+            ) -> Result<StreamingHttpResponse, Self::Error> {
+                let response = self.request(request).send().await?;
+                Ok(StreamingHttpResponse {
+                    status: response.status().as_u16(),
+                    headers: response.headers().clone(),
+                    body: ByteStream::new(response.bytes_stream()),
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// A transport that sends the requests with [reqwest](https://docs.rs/reqwest).
+        #[derive(Debug, Clone, Default)]
+        pub struct ReqwestTransport {
+            client: reqwest::Client,
+        }
+
+        impl ReqwestTransport {
+            /// Creates a transport that sends the requests with an existing client.
+            pub fn new(client: reqwest::Client) -> Self {
+                Self { client }
+            }
+
+            /// Builds the request of reqwest, shared by the buffered and the streamed responses.
+            fn request(&self, request: HttpRequest) -> reqwest::RequestBuilder {
+                let mut builder = self.client.request(request.method, request.url);
+                for (name, value) in request.headers {
+                    builder = builder.header(name, value);
+                }
+                if !request.body.is_empty() {
+                    builder = builder.body(request.body);
+                }
+                builder
+            }
+        }
+
+        impl HttpTransport for ReqwestTransport {
+            type Error = reqwest::Error;
+
+            async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+                let response = self.request(request).send().await?;
+                let status = response.status().as_u16();
+                let headers = response.headers().clone();
+                let body = response.bytes().await?.to_vec();
+                Ok(HttpResponse {
+                    status,
+                    headers,
+                    body,
+                })
+            }
+
+            #send_streaming
+        }
+    }
+}
+
+fn ureq(has_streaming: bool) -> TokenStream {
+    let send_streaming = match has_streaming {
+        true => quote! {
+            fn send_streaming(&self, request: HttpRequest) -> Result<StreamingHttpResponse, Self::Error> {
+                let response = self.run(request)?;
+                Ok(StreamingHttpResponse {
+                    status: response.status().as_u16(),
+                    headers: response.headers().clone(),
+                    body: ByteStream::from_reader(response.into_body().into_reader()),
+                })
+            }
+        },
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        /// A blocking transport that sends the requests with [ureq](https://docs.rs/ureq).
+        #[derive(Debug, Clone)]
+        pub struct UreqTransport {
+            agent: ureq::Agent,
+        }
+
+        impl UreqTransport {
+            /// Creates a transport that sends the requests with an existing agent.
+            /// The agent must not turn the error status codes into errors,
+            /// since they are decoded by the client like the other responses.
+            pub fn new(agent: ureq::Agent) -> Self {
+                Self { agent }
+            }
+
+            /// Sends the request with the agent, shared by the buffered and the streamed responses.
+            fn run(&self, request: HttpRequest) -> Result<http::Response<ureq::Body>, ureq::Error> {
+                let mut builder = http::Request::builder()
+                    .method(request.method)
+                    .uri(request.url);
+                for (name, value) in request.headers {
+                    builder = builder.header(name, value);
+                }
+                self.agent.run(builder.body(request.body)?)
+            }
+        }
+
+        impl Default for UreqTransport {
+            fn default() -> Self {
+                let agent = ureq::Agent::config_builder()
+                    .http_status_as_error(false)
+                    .build()
+                    .new_agent();
+                Self { agent }
+            }
+        }
+
+        impl HttpTransport for UreqTransport {
+            type Error = ureq::Error;
+
+            fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+                let mut response = self.run(request)?;
+                let status = response.status().as_u16();
+                let headers = response.headers().clone();
+                let body = response.body_mut().read_to_vec()?;
+                Ok(HttpResponse {
+                    status,
+                    headers,
+                    body,
+                })
+            }
+
+            #send_streaming
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Generation of the validation of the models against the constraints declared by their schemas,
+//! used by the server to reject the requests before they reach the service.
+
+use crate::models;
+use crate::models::{ConstraintsAsRust, CurrentType, pointer_token, tokenize_checks};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Returns the error that describes a constraint that is not satisfied and the trait implemented by the models.
+pub(crate) fn validation_runtime() -> TokenStream {
+    quote! {
+        /// A constraint of the spec that is not satisfied by a value.
+        #[derive(Debug, Clone, PartialEq, serde::Serialize)]
+        pub struct ValidationError {
+            /// The JSON pointer of the value in the request, like `/body/name` or `/query/limit`
+            pub pointer: String,
+            /// What is wrong with the value
+            pub message: String,
+        }
+
+        /// Checks a value against the constraints declared in the spec,
+        /// like `minLength`, `maximum` or `enum`.
+        pub trait Validate {
+            /// The names of the properties that must be present, and not null, in the serialized value.
+            const REQUIRED: &'static [&'static str] = &[];
+
+            /// Pushes to `errors` one error for each constraint that is not satisfied,
+            /// addressed by a JSON pointer that starts with `pointer`.
+            fn validate(&self, pointer: &str, errors: &mut Vec<ValidationError>);
+        }
+    }
+}
+
+/// Returns the implementation of the validation of a type, whose arguments are ignored when there is nothing to check.
+fn tokenize_impl(type_name: &str, required: TokenStream, checks: Vec<FieldChecks>) -> TokenStream {
+    let type_name = format_ident!("{}", type_name);
+    let arguments = match checks.iter().all(|c| c.tokens.is_empty()) {
+        true => quote! { _pointer: &str, _errors: &mut Vec<ValidationError> },
+        false => quote! { pointer: &str, errors: &mut Vec<ValidationError> },
+    };
+    // clippy suggests to check the optional values with let chains, that need the 2024 edition
+    let allow = match checks.iter().any(|c| c.is_optional) {
+        true => quote! { #[allow(clippy::collapsible_if)] },
+        false => TokenStream::new(),
+    };
+    let checks = checks.into_iter().map(|c| c.tokens);
+
+    quote! {
+        impl Validate for #type_name {
+            #required
+
+            #allow
+            fn validate(&self, #arguments) {
+                #(#checks)*
+            }
+        }
+    }
+}
+
+/// The checks on a field of a type.
+struct FieldChecks {
+    tokens: TokenStream,
+    /// Are the checks skipped when the value is missing?
+    is_optional: bool,
+}
+
+/// Returns the checks on a value that can be optional, skipped when the value is missing.
+fn tokenize_optional_checks(
+    constraints: &ConstraintsAsRust,
+    rust_type: &str,
+    field: &TokenStream,
+    is_optional: bool,
+    pointer: &TokenStream,
+) -> FieldChecks {
+    let value = quote! { value };
+    let checks = tokenize_checks(constraints, rust_type, &value, pointer);
+    let tokens = match (checks.is_empty(), is_optional) {
+        (true, _) => TokenStream::new(),
+        (false, true) => quote! {
+            if let Some(value) = &self.#field {
+                #checks
+            }
+        },
+        (false, false) => quote! {
+            let value = &self.#field;
+            #checks
+        },
+    };
+    FieldChecks {
+        is_optional: is_optional && !tokens.is_empty(),
+        tokens,
+    }
+}
+
+fn field_tokens(field_name: &str) -> TokenStream {
+    match field_name.parse::<TokenStream>() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+    }
+}
+
+/// Renders the validation of a schema: structs check each field and list their required properties,
+/// the types that wrap another one check the wrapped value.
+pub(crate) fn tokenize_validate_schema(schema: &models::SchemaAsRust) -> TokenStream {
+    match schema.current_type {
+        CurrentType::Struct => {
+            let required: Vec<_> = schema
+                .fields
+                .iter()
+                .filter(|f| !f.is_optional)
+                .map(|f| &f.original_name)
+                .collect();
+            let required = match required.is_empty() {
+                true => TokenStream::new(),
+                false => quote! { const REQUIRED: &'static [&'static str] = &[#(#required),*]; },
+            };
+            let checks = schema.fields.iter().map(|field| {
+                let token = format!("{{}}/{}", pointer_token(&field.original_name));
+                tokenize_optional_checks(
+                    &field.constraints,
+                    &field.rust_type,
+                    &field_tokens(&field.name),
+                    field.is_optional,
+                    &quote! { format!(#token, pointer) },
+                )
+            });
+            tokenize_impl(&schema.name, required, checks.collect())
+        }
+        _ => {
+            let checks = tokenize_optional_checks(
+                &schema.constraints,
+                &schema.rust_type,
+                &quote! { 0 },
+                false,
+                &quote! { pointer.to_string() },
+            );
+            tokenize_impl(&schema.name, TokenStream::new(), vec![checks])
+        }
+    }
+}
+
+/// Renders the validation of the parameters struct of an operation,
+/// whose values are addressed by their location and name, like `/query/limit`.
+pub(crate) fn tokenize_validate_parameters(operation: &models::OperationAsRust) -> TokenStream {
+    let checks = operation.parameters.iter().map(|parameter| {
+        let token = format!(
+            "{{}}/{}/{}",
+            location_token(parameter.location),
+            pointer_token(&parameter.name)
+        );
+        tokenize_optional_checks(
+            &parameter.constraints,
+            &parameter.rust_type,
+            &field_tokens(&parameter.field_name),
+            !parameter.is_required,
+            &quote! { format!(#token, pointer) },
+        )
+    });
+    tokenize_impl(
+        &operation.parameters_struct_name(),
+        TokenStream::new(),
+        checks.collect(),
+    )
+}
+
+/// Returns the token of the JSON pointer of the parameters sent in a location.
+pub(crate) fn location_token(location: models::ParameterLocation) -> &'static str {
+    match location {
+        models::ParameterLocation::Path => "path",
+        models::ParameterLocation::Query => "query",
+        models::ParameterLocation::Header => "header",
+        models::ParameterLocation::Cookie => "cookie",
+    }
+}
+
+/// Renders the validation of a body with many contents, that validates the content of the variant.
+/// The required properties are not listed, since they depend on the content.
+pub(crate) fn tokenize_validate_body(body: &models::BodyAsRust) -> TokenStream {
+    let enum_name = format_ident!("{}", body.name);
+    let arms = body.content.iter().map(|content| {
+        let variant = format_ident!("{}", body.variant_name(content));
+        quote! { #enum_name::#variant(value) => value.validate(pointer, errors) }
+    });
+
+    quote! {
+        impl Validate for #enum_name {
+            fn validate(&self, pointer: &str, errors: &mut Vec<ValidationError>) {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FieldAsRust, Imports, SchemaAsRust};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_validate_struct() {
+        let schema = SchemaAsRust {
+            name: "NewPet".to_string(),
+            rust_type: String::new(),
+            macros: BTreeSet::new(),
+            imports: Imports::new(),
+            comment: None,
+            is_optional: false,
+            fields: vec![
+                FieldAsRust {
+                    name: "name".to_string(),
+                    original_name: "name".to_string(),
+                    rust_type: "String".to_string(),
+                    comment: None,
+                    is_optional: false,
+                    is_skipped: false,
+                    constraints: ConstraintsAsRust {
+                        min_length: Some(1),
+                        ..Default::default()
+                    },
+                },
+                FieldAsRust {
+                    name: "age".to_string(),
+                    original_name: "age/years".to_string(),
+                    rust_type: "i32".to_string(),
+                    comment: None,
+                    is_optional: true,
+                    is_skipped: false,
+                    constraints: ConstraintsAsRust {
+                        maximum: Some(30.0),
+                        ..Default::default()
+                    },
+                },
+            ],
+            constraints: ConstraintsAsRust::default(),
+            current_type: CurrentType::Struct,
+        };
+
+        let tokens = tokenize_validate_schema(&schema);
+        let b = syn::parse_file(&tokens.to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+}
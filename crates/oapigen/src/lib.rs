@@ -1,9 +1,14 @@
 pub mod config;
 pub(crate) mod format;
 pub mod generating;
+pub mod linting;
 mod models;
 pub mod parsing;
 
 pub use config::*;
-pub use generating::spec_to_rust;
+pub use generating::{
+    Builder, GeneratedFile, GeneratingError, StaleFile, check_spec, read_spec, spec_to_crate,
+    spec_to_files, spec_to_rust, write_spec,
+};
+pub use linting::{LintIssue, LintSeverity, lint_spec};
 pub use models::*;
@@ -0,0 +1,806 @@
+use crate::format::{numbers, strings};
+use crate::generating::GeneratingError;
+use crate::models;
+use crate::models::constraints::pattern_error;
+use crate::models::field_name;
+use crate::parsing::routes::{
+    is_identifier, parse_status_code, payload_kind, to_upper_camel_identifier, type_name,
+};
+use convert_case::{Case, Casing};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The operations of a path item, in the order in which they are generated.
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The keywords of a schema that are not generated, with how much of the schema is lost.
+const UNSUPPORTED_KEYWORDS: &[(&str, LintSeverity, &str)] = &[
+    (
+        "allOf",
+        LintSeverity::Error,
+        "the compositions with allOf are not supported yet",
+    ),
+    (
+        "anyOf",
+        LintSeverity::Error,
+        "the compositions with anyOf are not supported yet",
+    ),
+    (
+        "oneOf",
+        LintSeverity::Error,
+        "the compositions with oneOf are not supported yet",
+    ),
+    (
+        "not",
+        LintSeverity::Error,
+        "the negated schemas are not supported",
+    ),
+    (
+        "if",
+        LintSeverity::Error,
+        "the conditional schemas are not supported",
+    ),
+    (
+        "then",
+        LintSeverity::Error,
+        "the conditional schemas are not supported",
+    ),
+    (
+        "else",
+        LintSeverity::Error,
+        "the conditional schemas are not supported",
+    ),
+    (
+        "prefixItems",
+        LintSeverity::Error,
+        "the tuples are not supported yet",
+    ),
+    (
+        "patternProperties",
+        LintSeverity::Warning,
+        "the properties that match a pattern are ignored",
+    ),
+    (
+        "dependentSchemas",
+        LintSeverity::Warning,
+        "the dependent schemas are ignored",
+    ),
+    (
+        "const",
+        LintSeverity::Warning,
+        "the constant value is not checked",
+    ),
+];
+
+/// LintSeverity tells if a construct stops the generation or if it is only partially generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintSeverity {
+    /// The code cannot be generated, or it does not compile
+    Error,
+    /// The code is generated, but part of the construct is ignored
+    Warning,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintSeverity::Error => write!(f, "error"),
+            LintSeverity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// LintIssue is a construct of a spec that oapigen cannot generate, or can generate only in part.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct LintIssue {
+    /// The JSON pointer of the construct in the spec, like `/paths/~1pets/get/requestBody`
+    pub pointer: String,
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.pointer, self.message)
+    }
+}
+
+/// Reads an OpenAPI spec from a file and returns the constructs that oapigen cannot generate yet,
+/// like the compositions of schemas, the unknown formats, the media types that are kept as raw bytes,
+/// and the names that collide in the generated code. The issues are sorted by their JSON pointer.
+///
+/// The whole spec is checked, so the issues are found without generating the code,
+/// that stops at the first of them.
+pub fn lint_spec(path: &Path) -> Result<Vec<LintIssue>, GeneratingError> {
+    let content = std::fs::read_to_string(path).map_err(GeneratingError::ReadSpecError)?;
+    // the constructs are checked only once the spec is known to be a valid document
+    oas3::from_yaml(content.clone())?;
+    let document: Value = serde_yaml::from_str(&content)?;
+
+    let mut linter = Linter {
+        document: &document,
+        issues: Vec::new(),
+    };
+    linter.lint_document();
+
+    let mut issues = linter.issues;
+    issues.sort();
+    issues.dedup();
+    Ok(issues)
+}
+
+struct Linter<'a> {
+    document: &'a Value,
+    issues: Vec<LintIssue>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, pointer: &str, severity: LintSeverity, message: String) {
+        self.issues.push(LintIssue {
+            pointer: pointer.to_string(),
+            severity,
+            message,
+        });
+    }
+
+    /// Follows the references of the value to the value they point to, returning it with its pointer.
+    /// The references that cannot be followed are reported.
+    fn resolve(&mut self, pointer: &str, value: &'a Value) -> Option<(String, &'a Value)> {
+        let mut resolved = (pointer.to_string(), value);
+        // a limit on the references that are followed, so that a cycle of references ends
+        for _ in 0..32 {
+            let Some(reference) = resolved.1.get("$ref").and_then(Value::as_str) else {
+                return Some(resolved);
+            };
+            let Some(target) = reference.strip_prefix('#') else {
+                self.report(
+                    &resolved.0,
+                    LintSeverity::Error,
+                    format!("the reference {reference} is to another document, that is not supported yet"),
+                );
+                return None;
+            };
+            match self.document.pointer(target) {
+                Some(value) => resolved = (target.to_string(), value),
+                None => {
+                    self.report(
+                        &resolved.0,
+                        LintSeverity::Error,
+                        format!("the reference {reference} does not point to anything"),
+                    );
+                    return None;
+                }
+            }
+        }
+        self.report(
+            pointer,
+            LintSeverity::Error,
+            "the references are a cycle".to_string(),
+        );
+        None
+    }
+
+    fn lint_document(&mut self) {
+        let document = self.document;
+        let mut operation_names: BTreeMap<String, String> = BTreeMap::new();
+        // the types generated for the operations, with the pointer of what they are generated for
+        let mut operation_types: BTreeMap<String, String> = BTreeMap::new();
+        for (route, path_item) in entries(document.get("paths")) {
+            let pointer = child("/paths", route);
+            self.lint_route(&pointer, route);
+
+            let path_parameters: Vec<_> = array(path_item.get("parameters"))
+                .into_iter()
+                .map(|(i, p)| (child(&child(&pointer, "parameters"), &i), p))
+                .collect();
+            for method in METHODS {
+                let Some(operation) = path_item.get(method) else {
+                    continue;
+                };
+                let pointer = child(&pointer, method);
+                let name = match operation.get("operationId").and_then(Value::as_str) {
+                    Some(operation_id) => type_name(operation_id),
+                    None => format!(
+                        "{}{}",
+                        method.to_case(Case::UpperCamel),
+                        to_upper_camel_identifier(route)
+                    ),
+                };
+                if !is_identifier(&name) {
+                    let operation_id = operation.get("operationId").and_then(Value::as_str);
+                    self.report(
+                        &pointer,
+                        LintSeverity::Error,
+                        format!(
+                            "the operation id {} cannot be turned into a Rust identifier",
+                            operation_id.unwrap_or_default()
+                        ),
+                    );
+                } else if let Some(other) = operation_names.get(&name) {
+                    self.report(
+                        &pointer,
+                        LintSeverity::Error,
+                        format!("the operation is named {name} like the one at {other}, so their types collide"),
+                    );
+                } else {
+                    operation_names.insert(name.clone(), pointer.clone());
+                    let has_parameters = !path_parameters.is_empty()
+                        || !array(operation.get("parameters")).is_empty();
+                    operation_types.extend(generated_types(
+                        &pointer,
+                        &name,
+                        has_parameters,
+                        operation,
+                    ));
+                }
+                self.lint_operation(&pointer, &path_parameters, operation);
+            }
+        }
+
+        let components = document.get("components");
+        for (name, schema) in entries(components.and_then(|c| c.get("schemas"))) {
+            let pointer = child("/components/schemas", name);
+            let type_name = type_name(name);
+            if !is_identifier(&type_name) {
+                self.report(
+                    &pointer,
+                    LintSeverity::Error,
+                    format!("the schema name {name} cannot be turned into a Rust identifier"),
+                );
+            } else if let Some(other) = operation_types.get(&type_name) {
+                self.report(
+                    &pointer,
+                    LintSeverity::Error,
+                    format!("the schema is named {type_name} like the type generated for {other}, so their types collide"),
+                );
+            }
+            self.lint_keywords(&pointer, schema);
+            if let Some((pointer, schema)) = self.resolve(&pointer, schema) {
+                self.lint_body_schema(&pointer, schema, &models::PayloadKind::Json);
+            }
+        }
+        for (name, scheme) in entries(components.and_then(|c| c.get("securitySchemes"))) {
+            let pointer = child("/components/securitySchemes", name);
+            if let Some((pointer, scheme)) = self.resolve(&pointer, scheme) {
+                self.lint_security_scheme(&pointer, scheme);
+            }
+        }
+    }
+
+    /// Reports the segments of a path that the routers of the servers cannot match.
+    fn lint_route(&mut self, pointer: &str, route: &str) {
+        for segment in route.split('/').filter(|s| s.contains(['{', '}'])) {
+            let is_template = segment.starts_with('{')
+                && segment.ends_with('}')
+                && segment.matches('{').count() == 1
+                && segment.matches('}').count() == 1;
+            if !is_template {
+                self.report(
+                    pointer,
+                    LintSeverity::Warning,
+                    format!("the segment {segment} is not a single template expression, so the server cannot be generated"),
+                );
+            }
+        }
+    }
+
+    fn lint_operation(
+        &mut self,
+        pointer: &str,
+        path_parameters: &[(String, &'a Value)],
+        operation: &'a Value,
+    ) {
+        let parameters_pointer = child(pointer, "parameters");
+        let operation_parameters: Vec<_> = array(operation.get("parameters"))
+            .into_iter()
+            .map(|(i, p)| (child(&parameters_pointer, &i), p))
+            .collect();
+
+        // the parameters of the operation override the ones of the path with the same name and location
+        let mut parameters: Vec<(String, &Value)> = Vec::new();
+        for (pointer, parameter) in path_parameters.iter().cloned().chain(operation_parameters) {
+            let Some((pointer, parameter)) = self.resolve(&pointer, parameter) else {
+                continue;
+            };
+            let key = |p: &Value| (p.get("name").cloned(), p.get("in").cloned());
+            parameters.retain(|(_, p)| key(p) != key(parameter));
+            parameters.push((pointer, parameter));
+        }
+
+        let mut field_names: BTreeMap<String, String> = BTreeMap::new();
+        for (pointer, parameter) in parameters {
+            let name = parameter
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let location = parameter
+                .get("in")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let is_reserved_header = ["accept", "content-type", "authorization"]
+                .iter()
+                .any(|h| name.eq_ignore_ascii_case(h));
+            if location == "header" && is_reserved_header {
+                continue;
+            }
+
+            self.check_field_name(
+                &mut field_names,
+                &pointer,
+                "parameter",
+                name,
+                field_name(name),
+            );
+            match (parameter.get("schema"), parameter.get("content")) {
+                (Some(schema), _) => {
+                    let pointer = child(&pointer, "schema");
+                    if let Some((pointer, schema)) = self.resolve(&pointer, schema) {
+                        self.lint_keywords(&pointer, schema);
+                        self.lint_property(&pointer, schema);
+                    }
+                }
+                (None, Some(_)) => self.report(
+                    &child(&pointer, "content"),
+                    LintSeverity::Warning,
+                    "the content of a parameter is ignored, so its value is a String".to_string(),
+                ),
+                (None, None) => {}
+            }
+        }
+
+        if let Some(request_body) = operation.get("requestBody") {
+            let pointer = child(pointer, "requestBody");
+            if let Some((pointer, request_body)) = self.resolve(&pointer, request_body) {
+                self.lint_content(
+                    &child(&pointer, "content"),
+                    request_body.get("content"),
+                    false,
+                );
+            }
+        }
+
+        for (status, response) in entries(operation.get("responses")) {
+            let pointer = child(&child(pointer, "responses"), status);
+            if parse_status_code(status).is_err() {
+                self.report(
+                    &pointer,
+                    LintSeverity::Error,
+                    format!("{status} is not a status code, a range of status codes or default"),
+                );
+            }
+            let Some((pointer, response)) = self.resolve(&pointer, response) else {
+                continue;
+            };
+            self.lint_content(&child(&pointer, "content"), response.get("content"), true);
+            self.lint_headers(&child(&pointer, "headers"), response.get("headers"));
+        }
+    }
+
+    fn lint_headers(&mut self, pointer: &str, headers: Option<&'a Value>) {
+        let mut field_names: BTreeMap<String, String> = BTreeMap::new();
+        for (name, header) in entries(headers) {
+            // the spec says that a Content-Type header must be ignored
+            if name.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+            let pointer = child(pointer, name);
+            self.check_field_name(&mut field_names, &pointer, "header", name, field_name(name));
+
+            let Some((pointer, header)) = self.resolve(&pointer, header) else {
+                continue;
+            };
+            if let Some(schema) = header.get("schema") {
+                let pointer = child(&pointer, "schema");
+                if let Some((pointer, schema)) = self.resolve(&pointer, schema) {
+                    self.lint_keywords(&pointer, schema);
+                    self.lint_simple_value(&pointer, schema);
+                }
+            }
+        }
+    }
+
+    fn lint_content(&mut self, pointer: &str, content: Option<&'a Value>, is_response: bool) {
+        for (media_type, media_type_object) in entries(content) {
+            let pointer = child(pointer, media_type);
+            let schema = match media_type_object.get("schema") {
+                Some(schema) => self.resolve(&child(&pointer, "schema"), schema),
+                None => None,
+            };
+            let object_schema: Option<oas3::spec::ObjectSchema> = schema
+                .as_ref()
+                .and_then(|(_, s)| serde_json::from_value((*s).clone()).ok());
+
+            let kind = payload_kind(media_type, object_schema.as_ref(), is_response);
+            match (&kind, schema) {
+                (models::PayloadKind::Raw, _) => self.report(
+                    &pointer,
+                    LintSeverity::Warning,
+                    format!("the media type {media_type} is not supported, so the body is kept as raw bytes"),
+                ),
+                (models::PayloadKind::Text | models::PayloadKind::Binary, _) => {}
+                (_, None) => self.report(
+                    &pointer,
+                    LintSeverity::Warning,
+                    "the body has no schema, so it is skipped".to_string(),
+                ),
+                (_, Some((pointer, schema))) => {
+                    self.lint_keywords(&pointer, schema);
+                    self.lint_body_schema(&pointer, schema, &kind);
+                }
+            }
+        }
+    }
+
+    /// Reports the schemas of a body that cannot be turned into a type.
+    fn lint_body_schema(&mut self, pointer: &str, schema: &'a Value, kind: &models::PayloadKind) {
+        let Some(types) = schema_types(schema) else {
+            self.report(
+                pointer,
+                LintSeverity::Error,
+                "the schemas without a type cannot be generated yet".to_string(),
+            );
+            return;
+        };
+        let non_null_types: Vec<&str> = types.into_iter().filter(|t| *t != "null").collect();
+        if non_null_types.len() > 1 {
+            self.report(
+                pointer,
+                LintSeverity::Error,
+                format!("the unions of the types {non_null_types:?} cannot be generated yet"),
+            );
+            return;
+        }
+
+        let body = match kind {
+            models::PayloadKind::Form => Some("form"),
+            models::PayloadKind::Multipart(_) => Some("multipart"),
+            _ => None,
+        };
+        match (non_null_types.first().copied(), body) {
+            (Some("object"), _) => self.lint_object(pointer, schema),
+            (_, Some(body)) => self.report(
+                pointer,
+                LintSeverity::Error,
+                format!("the {body} bodies must be objects"),
+            ),
+            (Some("array"), None) => self.lint_field(pointer, schema),
+            (schema_type, None) => self.lint_format(pointer, schema, schema_type),
+        }
+    }
+
+    /// Reports the objects that cannot be turned into a struct, or into a map when they only
+    /// describe their `additionalProperties`.
+    fn lint_object(&mut self, pointer: &str, schema: &'a Value) {
+        let has_properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|p| !p.is_empty());
+        match schema.get("additionalProperties") {
+            Some(values) if !has_properties && values.is_object() => {
+                self.lint_field(&child(pointer, "additionalProperties"), values)
+            }
+            _ => self.lint_properties(pointer, schema),
+        }
+    }
+
+    fn lint_properties(&mut self, pointer: &str, schema: &'a Value) {
+        let mut field_names: BTreeMap<String, String> = BTreeMap::new();
+        for (name, property) in entries(schema.get("properties")) {
+            let pointer = child(&child(pointer, "properties"), name);
+            self.check_field_name(
+                &mut field_names,
+                &pointer,
+                "property",
+                name,
+                field_name(name),
+            );
+            self.lint_field(&pointer, property);
+        }
+    }
+
+    /// Reports the references that are not to a schema of the components, the only ones
+    /// that are turned into the name of a type.
+    fn lint_reference(&mut self, pointer: &str, reference: &str) {
+        let is_component = reference
+            .strip_prefix("#/components/schemas/")
+            .is_some_and(|name| !name.contains('/'));
+        if !is_component {
+            self.report(
+                pointer,
+                LintSeverity::Error,
+                format!("the reference {reference} is not to a schema of the components, that is not supported yet"),
+            );
+        }
+    }
+
+    /// Reports the properties of an object, or the items of an array body, that are neither a reference
+    /// to a schema of the components, nor an array of them, nor a value allowed by [Self::lint_property].
+    fn lint_field(&mut self, pointer: &str, property: &'a Value) {
+        let referencing = match property.get("items") {
+            Some(items) if items.get("$ref").is_some() => items,
+            _ => property,
+        };
+        match referencing.get("$ref").and_then(Value::as_str) {
+            Some(reference) => {
+                self.lint_reference(pointer, reference);
+                self.resolve(pointer, referencing);
+            }
+            None => self.lint_property(pointer, property),
+        }
+    }
+
+    /// Reports the properties, or the parameters, that are neither a simple value nor an array of simple values.
+    fn lint_property(&mut self, pointer: &str, property: &'a Value) {
+        let is_array = schema_types(property).is_some_and(|t| t.contains(&"array"));
+        if !is_array {
+            self.lint_simple_value(pointer, property);
+            return;
+        }
+
+        match property.get("items") {
+            Some(items) if items.get("$ref").is_some() => self.report(
+                pointer,
+                LintSeverity::Error,
+                "the arrays of references are not supported yet".to_string(),
+            ),
+            Some(items) if items.is_object() => {
+                self.lint_simple_value(&child(pointer, "items"), items)
+            }
+            _ => self.report(
+                pointer,
+                LintSeverity::Error,
+                "the arrays without a schema for their items are not supported yet".to_string(),
+            ),
+        }
+    }
+
+    /// Reports the schemas that are not a simple value, where only simple values are supported.
+    fn lint_simple_value(&mut self, pointer: &str, schema: &'a Value) {
+        let types = schema_types(schema).unwrap_or_default();
+        let non_null_types: Vec<_> = types.into_iter().filter(|t| *t != "null").collect();
+        match non_null_types.as_slice() {
+            [] => {}
+            [t @ ("boolean" | "integer" | "number" | "string")] => {
+                self.lint_format(pointer, schema, Some(t))
+            }
+            other => self.report(
+                pointer,
+                LintSeverity::Error,
+                format!(
+                    "only simple values are supported here, but the schema has the types {other:?}"
+                ),
+            ),
+        }
+    }
+
+    /// Reports the formats that are not turned into their own type.
+    fn lint_format(&mut self, pointer: &str, schema: &'a Value, schema_type: Option<&str>) {
+        let Some(format) = schema.get("format").and_then(Value::as_str) else {
+            return;
+        };
+        let (known_formats, rust_type) = match schema_type {
+            Some("integer" | "number") => (numbers::KNOWN_FORMATS, "f32"),
+            Some("string") => (strings::KNOWN_FORMATS, "String"),
+            _ => return,
+        };
+        if !known_formats.contains(&format) {
+            self.report(
+                &child(pointer, "format"),
+                LintSeverity::Warning,
+                format!("the format {format} is unknown, so the value is a {rust_type}"),
+            );
+        }
+    }
+
+    /// Reports the keywords that are not generated, in the schema and in the schemas it contains.
+    /// The references are not followed, since their schemas are reported where they are.
+    fn lint_keywords(&mut self, pointer: &str, schema: &'a Value) {
+        if !schema.is_object() || schema.get("$ref").is_some() {
+            return;
+        }
+        for (keyword, severity, message) in UNSUPPORTED_KEYWORDS {
+            if schema.get(keyword).is_some() {
+                self.report(&child(pointer, keyword), *severity, message.to_string());
+            }
+        }
+        if let Some(reason) = schema
+            .get("pattern")
+            .and_then(Value::as_str)
+            .and_then(pattern_error)
+        {
+            self.report(
+                &child(pointer, "pattern"),
+                LintSeverity::Error,
+                format!("the pattern cannot be compiled by the generated validation: {reason}"),
+            );
+        }
+        // the objects without properties are maps of their additional properties
+        let has_properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|p| !p.is_empty());
+        if has_properties
+            && schema
+                .get("additionalProperties")
+                .is_some_and(|a| a != &Value::Bool(false))
+        {
+            self.report(
+                &child(pointer, "additionalProperties"),
+                LintSeverity::Warning,
+                "the additional properties are ignored".to_string(),
+            );
+        }
+
+        for keyword in ["items", "additionalProperties", "not", "if", "then", "else"] {
+            if let Some(subschema) = schema.get(keyword) {
+                self.lint_keywords(&child(pointer, keyword), subschema);
+            }
+        }
+        for keyword in [
+            "properties",
+            "patternProperties",
+            "dependentSchemas",
+            "$defs",
+        ] {
+            for (name, subschema) in entries(schema.get(keyword)) {
+                self.lint_keywords(&child(&child(pointer, keyword), name), subschema);
+            }
+        }
+        for keyword in ["allOf", "anyOf", "oneOf", "prefixItems"] {
+            for (i, subschema) in array(schema.get(keyword)) {
+                self.lint_keywords(&child(&child(pointer, keyword), &i), subschema);
+            }
+        }
+    }
+
+    /// Reports the security schemes that the client skips and with which the server cannot be generated.
+    fn lint_security_scheme(&mut self, pointer: &str, scheme: &'a Value) {
+        let scheme_type = scheme
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let http_scheme = scheme
+            .get("scheme")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let location = scheme.get("in").and_then(Value::as_str).unwrap_or_default();
+        let unsupported = match scheme_type {
+            "mutualTLS" => Some("the mutualTLS schemes are".to_string()),
+            "http"
+                if !matches!(
+                    http_scheme.to_ascii_lowercase().as_str(),
+                    "bearer" | "basic"
+                ) =>
+            {
+                Some(format!("the HTTP {http_scheme} schemes are"))
+            }
+            "apiKey" if !matches!(location, "header" | "query" | "cookie") => {
+                Some(format!("the API keys in {location} are"))
+            }
+            _ => None,
+        };
+        if let Some(unsupported) = unsupported {
+            self.report(
+                pointer,
+                LintSeverity::Warning,
+                format!("{unsupported} not supported, so the client skips them and the server cannot be generated"),
+            );
+        }
+    }
+
+    /// Reports the field whose name is not an identifier, or that has the same name of a previous one,
+    /// since the struct would not compile.
+    fn check_field_name(
+        &mut self,
+        field_names: &mut BTreeMap<String, String>,
+        pointer: &str,
+        what: &str,
+        name: &str,
+        field_name: String,
+    ) {
+        if !is_identifier(&field_name) {
+            self.report(
+                pointer,
+                LintSeverity::Error,
+                format!("the {what} {name} cannot be turned into a Rust identifier"),
+            );
+            return;
+        }
+        match field_names.get(&field_name) {
+            Some(other) => self.report(
+                pointer,
+                LintSeverity::Error,
+                format!(
+                    "the {what} {name} has the field name {field_name} like the one at {other}"
+                ),
+            ),
+            None => {
+                field_names.insert(field_name, pointer.to_string());
+            }
+        }
+    }
+}
+
+/// Returns the names of the types generated for an operation named `name`, with the pointer
+/// of the operation, of its request body or of the response they are generated for.
+/// The types of the bodies with many media types, named after these ones, are not included.
+fn generated_types(
+    pointer: &str,
+    name: &str,
+    has_parameters: bool,
+    operation: &Value,
+) -> Vec<(String, String)> {
+    let mut types = vec![(format!("{name}Response"), pointer.to_string())];
+    if has_parameters {
+        types.push((format!("{name}Params"), pointer.to_string()));
+    }
+    if operation.get("requestBody").is_some() {
+        types.push((format!("{name}RequestBody"), child(pointer, "requestBody")));
+    }
+    for (status, response) in entries(operation.get("responses")) {
+        let Ok(status_code) = parse_status_code(status) else {
+            continue;
+        };
+        let pointer = child(&child(pointer, "responses"), status);
+        let response_type = format!("{name}Response{}", status_code.name());
+        if response.get("headers").is_some() {
+            types.push((format!("{response_type}Headers"), pointer.clone()));
+        }
+        types.push((response_type, pointer));
+    }
+    types
+}
+
+/// Returns the pointer of a child of the value at `pointer`, escaping the `~` and `/` of its key.
+fn child(pointer: &str, key: &str) -> String {
+    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Returns the entries of a map, or none if the value is not a map.
+fn entries(value: Option<&Value>) -> Vec<(&String, &Value)> {
+    match value {
+        Some(Value::Object(map)) => map.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the items of an array with their index, or none if the value is not an array.
+fn array(value: Option<&Value>) -> Vec<(String, &Value)> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i.to_string(), item))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the types of a schema, that can be a single type or a list of them, or none if it has no type.
+fn schema_types(schema: &Value) -> Option<Vec<&str>> {
+    match schema.get("type")? {
+        Value::String(t) => Some(vec![t.as_str()]),
+        Value::Array(types) => Some(types.iter().filter_map(Value::as_str).collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::child;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("/paths", "/pets/{petId}", "/paths/~1pets~1{petId}")]
+    #[case("/components/schemas", "Pet~1", "/components/schemas/Pet~01")]
+    #[case("", "paths", "/paths")]
+    fn test_child(#[case] pointer: &str, #[case] key: &str, #[case] expected: &str) {
+        assert_eq!(child(pointer, key), expected);
+    }
+}
@@ -0,0 +1,3 @@
+mod lints;
+
+pub use lints::{LintIssue, LintSeverity, lint_spec};
@@ -1,68 +1,198 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::{Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use oapigen::{Config, GeneratingError, LintSeverity};
+
+/// The exit code of a check that finds generated files that are not up to date,
+/// or of a lint that finds constructs that cannot be generated.
+const EXIT_FAILED_CHECK: u8 = 1;
+/// The exit code of a spec that cannot be read as OpenAPI or turned into rust code, or of an invalid config.
+const EXIT_INVALID_SPEC: u8 = 65;
+/// The exit code of a spec or a config that cannot be read, or of an output that cannot be written.
+const EXIT_IO: u8 = 74;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
+#[command(
+    after_help = "Exit codes: 0 on success, 1 if --check finds stale files or lint finds errors, 65 if the spec cannot be parsed or generated \
+or the config is not valid, 74 if a file cannot be read or written."
+)]
 struct Cli {
-    /// Optional name to operate on
-    name: Option<String>,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Generates the rust code of an OpenAPI spec
+    Generate(GenerateArgs),
+    /// Reports the constructs of an OpenAPI spec that cannot be generated yet
+    Lint(LintArgs),
+}
 
-    /// Sets a custom config file
+#[derive(Args)]
+struct GenerateArgs {
+    /// The OpenAPI spec, in YAML or JSON
+    #[arg(short, long, value_name = "FILE")]
+    spec: PathBuf,
+
+    /// The config, in TOML or in YAML if the extension is yaml or yml. Its options can be
+    /// overridden with OAPIGEN_ environment variables, like OAPIGEN_LIBRARIES__DATETIME=jiff
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
-    /// Turn debugging information on
-    #[arg(short, long, action = clap::ArgAction::Count)]
-    debug: u8,
+    /// The file where the generated code is written, or the directory of the module tree
+    /// or of the crate if the layout of the config has them, instead of the output_path of the config.
+    /// The files are left untouched if they already have the generated code
+    #[arg(short, long, value_name = "FILE")]
+    out: Option<PathBuf>,
 
-    #[command(subcommand)]
-    command: Option<Commands>,
+    /// Generate an async client on top of the models
+    #[arg(long)]
+    client: bool,
+
+    /// Generate a blocking client on top of the models
+    #[arg(long)]
+    blocking_client: bool,
+
+    /// Generate a server trait and its router on top of the models
+    #[arg(long)]
+    server: bool,
+
+    /// Generate a standalone crate in the output directory, with a Cargo.toml that has the
+    /// dependencies of the generated code. Its name and version are the title and the version
+    /// of the spec, unless the package of the config sets them
+    #[arg(long = "crate")]
+    standalone_crate: bool,
+
+    /// Check that the files at the output path have the generated code, without writing them.
+    /// Prints a unified diff of the files that do not, or that generate would remove, and exits with 1
+    #[arg(long)]
+    check: bool,
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    /// does testing things
-    Test {
-        /// lists test values
-        #[arg(short, long)]
-        list: bool,
-    },
+#[derive(Args)]
+struct LintArgs {
+    /// The OpenAPI spec, in YAML or JSON
+    #[arg(value_name = "FILE")]
+    spec: PathBuf,
+
+    /// The format of the report
+    #[arg(short, long, value_enum, default_value_t = LintFormat::Human)]
+    format: LintFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LintFormat {
+    /// One line for each issue, like `error: /paths/~1pets/get: message`
+    Human,
+    /// A JSON array of the issues, with their pointer, severity and message
+    Json,
 }
 
-fn main() {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    // You can check the value provided by positional arguments, or option arguments
-    if let Some(name) = cli.name.as_deref() {
-        println!("Value for name: {name}");
+    let result = match cli.command {
+        Commands::Generate(args) => generate_command(args),
+        Commands::Lint(args) => lint_command(args),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", error_chain(&e));
+            match e.is_io() {
+                true => ExitCode::from(EXIT_IO),
+                false => ExitCode::from(EXIT_INVALID_SPEC),
+            }
+        }
     }
+}
 
-    if let Some(config_path) = cli.config.as_deref() {
-        println!("Value for config: {}", config_path.display());
+/// Builds the config of the generate command, where the flags are added to the targets of the config.
+fn generate_command(args: GenerateArgs) -> Result<ExitCode, GeneratingError> {
+    let mut config = oapigen::load_config(args.config.as_deref())?;
+    if let Some(out) = args.out {
+        config.output_path = out;
     }
+    if config.output_path.as_os_str().is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the output path is missing, pass --out or set output_path in the config",
+            )
+            .exit();
+    }
+    config.targets.client |= args.client;
+    config.targets.blocking_client |= args.blocking_client;
+    config.targets.server |= args.server;
+    config.layout.standalone_crate |= args.standalone_crate;
 
-    // You can see how many times a particular flag or argument occurred
-    // Note, only flags can have multiple occurrences
-    match cli.debug {
-        0 => println!("Debug mode is off"),
-        1 => println!("Debug mode is kind of on"),
-        2 => println!("Debug mode is on"),
-        _ => println!("Don't be crazy"),
+    match args.check {
+        true => check(&config, &args.spec),
+        false => generate(&config, &args.spec),
     }
+}
 
-    // You can check for the existence of subcommands, and if found use their
-    // matches just as you would the top level cmd
-    match &cli.command {
-        Some(Commands::Test { list }) => {
-            if *list {
-                println!("Printing testing lists...");
-            } else {
-                println!("Not printing testing lists...");
-            }
+/// Generates the code of the spec at `spec_path` and writes it to the output path of the config.
+fn generate(config: &Config, spec_path: &Path) -> Result<ExitCode, GeneratingError> {
+    let spec = oapigen::read_spec(spec_path)?;
+    oapigen::write_spec(config, spec)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Generates the code of the spec at `spec_path` and prints the diff of the files at the output path
+/// of the config that do not have it.
+fn check(config: &Config, spec_path: &Path) -> Result<ExitCode, GeneratingError> {
+    let spec = oapigen::read_spec(spec_path)?;
+    let stale_files = oapigen::check_spec(config, spec)?;
+    if stale_files.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    for file in &stale_files {
+        print!("{}", file.diff());
+    }
+    eprintln!(
+        "error: {} generated file(s) are not up to date, run oapigen generate without --check",
+        stale_files.len()
+    );
+    Ok(ExitCode::from(EXIT_FAILED_CHECK))
+}
+
+/// Prints the issues of the spec, and fails if any of them stops the generation.
+fn lint_command(args: LintArgs) -> Result<ExitCode, GeneratingError> {
+    let issues = oapigen::lint_spec(&args.spec)?;
+    match args.format {
+        LintFormat::Human => issues.iter().for_each(|issue| println!("{issue}")),
+        LintFormat::Json => {
+            let json = serde_json::to_string_pretty(&issues)
+                .map_err(GeneratingError::SerializeLintError)?;
+            println!("{json}");
         }
-        None => {}
     }
 
-    // Continued program logic goes here...
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == LintSeverity::Error)
+        .count();
+    eprintln!("{errors} error(s), {} warning(s)", issues.len() - errors);
+    match errors {
+        0 => Ok(ExitCode::SUCCESS),
+        _ => Ok(ExitCode::from(EXIT_FAILED_CHECK)),
+    }
+}
+
+/// Returns the message of an error followed by the ones of its sources, like `a: b: c`.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message = format!("{}: {}", message.trim_end_matches('.'), e);
+        source = e.source();
+    }
+    message
 }
@@ -0,0 +1,582 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+
+use crate::models::headers::{HeaderAsRust, tokenize_headers_struct};
+use crate::models::schema::SchemaAsRust;
+
+/// PartKind represents how a property of a multipart body is turned into parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartKind {
+    /// A simple value, sent as text.
+    Text,
+    /// An array of simple values, sent as one text part for each value.
+    Texts,
+    /// A value sent as JSON, because its encoding declares a JSON content type.
+    Json,
+    /// A binary value, sent as a file.
+    File,
+    /// An array of binary values, sent as one file part for each value.
+    Files,
+}
+
+/// PartAsRust represents a property of a multipart body, together with its encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartAsRust {
+    /// The name of the part, that is the name of the property in the spec
+    pub name: String,
+    /// The name of the field of the body struct in snake case
+    pub field_name: String,
+    /// How the value of the field is turned into parts
+    pub kind: PartKind,
+    /// The content types allowed by the encoding of the part, that can use wildcards like `image/*`
+    pub content_types: Vec<String>,
+    /// The headers declared by the encoding of the part, without the `Content-Type`
+    pub headers: Vec<HeaderAsRust>,
+    /// The name of the struct of the headers of the part, used when the part declares some
+    pub headers_struct_name: String,
+    /// Is the field optional?
+    pub is_optional: bool,
+}
+
+impl PartAsRust {
+    /// Returns the content type sent when the value does not set one: the first of the allowed ones
+    /// without wildcards, since a part is sent with a single content type.
+    pub fn default_content_type(&self) -> Option<&str> {
+        self.content_types
+            .iter()
+            .map(String::as_str)
+            .find(|c| !c.contains('*'))
+    }
+
+    /// Returns the name of the field of the body struct that holds the headers of the part.
+    pub fn headers_field_name(&self) -> String {
+        format!("{}_headers", self.field_name.trim_start_matches("r#"))
+    }
+}
+
+/// PayloadKind represents how a body is encoded for its media types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// The body is serialized as JSON.
+    Json,
+    /// The body is serialized as `application/x-www-form-urlencoded`.
+    Form,
+    /// The body is split in the parts of a `multipart/*` payload.
+    Multipart(Vec<PartAsRust>),
+    /// The body is text, like `text/plain`, and is kept as a string.
+    Text,
+    /// The body is binary, like `application/octet-stream`, and is kept as bytes.
+    Binary,
+    /// The media type is unknown, so the body is kept as bytes together with its content type.
+    Raw,
+    /// The body is a stream of `text/event-stream` events, whose data are items of the schema.
+    EventStream,
+    /// The body is a stream of lines, like `application/x-ndjson`, each one an item of the schema as JSON.
+    JsonLines,
+}
+
+impl PayloadKind {
+    /// Returns true if the body is handled as it is, without going through serde.
+    pub fn is_raw(&self) -> bool {
+        matches!(
+            self,
+            PayloadKind::Text | PayloadKind::Binary | PayloadKind::Raw
+        )
+    }
+
+    /// Returns true if the body is a stream of items, decoded while it is received.
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, PayloadKind::EventStream | PayloadKind::JsonLines)
+    }
+}
+
+/// ContentAsRust represents the media types of a body that share the same schema.
+#[derive(Debug)]
+pub struct ContentAsRust {
+    /// The media types, like `application/json`, sorted by name
+    pub media_types: Vec<String>,
+    /// How the body is encoded for these media types
+    pub kind: PayloadKind,
+    /// The type of the body for these media types
+    pub schema: SchemaAsRust,
+}
+
+impl ContentAsRust {
+    /// Returns the type of the body for these media types, that for the streaming bodies
+    /// is a stream of items of the schema.
+    pub fn rust_type(&self) -> TokenStream {
+        let schema_name = format_ident!("{}", self.schema.name);
+        match self.kind {
+            PayloadKind::EventStream => quote! { Streaming<ServerSentEvent<#schema_name>> },
+            PayloadKind::JsonLines => quote! { Streaming<#schema_name> },
+            _ => quote! { #schema_name },
+        }
+    }
+}
+
+/// BodyAsRust represents the body of a request or of a response.
+///
+/// When all the media types share the same schema the body is represented by the type of that schema,
+/// otherwise it is an enum with one variant for each of the schemas.
+#[derive(Debug)]
+pub struct BodyAsRust {
+    /// The name of the type of the body
+    pub name: String,
+    /// The content of the body, one entry for each distinct schema
+    pub content: Vec<ContentAsRust>,
+}
+
+impl BodyAsRust {
+    /// Returns all the media types of the body.
+    pub fn media_types(&self) -> Vec<&str> {
+        self.content
+            .iter()
+            .flat_map(|c| &c.media_types)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns true if decoding the body needs its content type, either to select the schema,
+    /// to find the boundary of the parts or because it is kept together with the body.
+    pub fn reads_content_type(&self) -> bool {
+        self.content.len() > 1
+            || self
+                .content
+                .iter()
+                .any(|c| matches!(c.kind, PayloadKind::Raw | PayloadKind::Multipart(_)))
+    }
+
+    /// Returns true if one of the contents of the body is streamed.
+    pub fn is_streaming(&self) -> bool {
+        self.content.iter().any(|c| c.kind.is_streaming())
+    }
+
+    /// Returns the name of the variant of the body enum associated with a content.
+    pub fn variant_name(&self, content: &ContentAsRust) -> String {
+        let name = &content.schema.name;
+        let name = name.strip_prefix(&self.name).unwrap_or(name);
+        match content.kind.is_streaming() {
+            true => name.strip_suffix("Item").unwrap_or(name).to_string(),
+            false => name.to_string(),
+        }
+    }
+}
+
+/// RequestBodyAsRust represents the body of the request of an operation.
+#[derive(Debug)]
+pub struct RequestBodyAsRust {
+    /// The body of the request
+    pub body: BodyAsRust,
+    /// The optional description of the request body
+    pub description: Option<String>,
+    /// Is the request body required?
+    pub is_required: bool,
+}
+
+impl ToTokens for BodyAsRust {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for content in &self.content {
+            let schema_name = format_ident!("{}", content.schema.name);
+            match &content.kind {
+                PayloadKind::Json
+                | PayloadKind::Text
+                | PayloadKind::Binary
+                | PayloadKind::Raw
+                | PayloadKind::EventStream
+                | PayloadKind::JsonLines => {}
+                PayloadKind::Form => tokens.extend(quote! {
+                    impl #schema_name {
+                        /// Serializes the body as `application/x-www-form-urlencoded`.
+                        pub fn to_form(&self) -> Result<String, serde_urlencoded::ser::Error> {
+                            serde_urlencoded::to_string(self)
+                        }
+                    }
+                }),
+                PayloadKind::Multipart(parts) => {
+                    // the headers structs derive the macros of the body that holds them
+                    let macros = content
+                        .schema
+                        .macros
+                        .iter()
+                        .map(|m| m.parse::<TokenStream>())
+                        .collect::<Result<TokenStream, _>>()
+                        .unwrap_or_else(|e| panic!("cannot turn macros to tokens: {e}"));
+                    for part in parts.iter().filter(|p| !p.headers.is_empty()) {
+                        tokens.extend(tokenize_headers_struct(
+                            &part.headers_struct_name,
+                            &part.headers,
+                            &macros,
+                        ));
+                    }
+                    tokens.extend(tokenize_into_parts(&content.schema.name, parts));
+                    tokens.extend(tokenize_from_parts(&content.schema.name, parts));
+                }
+            }
+        }
+
+        // a body with a single schema is represented directly by the type of the schema,
+        // or by a stream of its items
+        if let [content] = self.content.as_slice() {
+            if content.kind.is_streaming() {
+                let body_name = format_ident!("{}", self.name);
+                let rust_type = content.rust_type();
+                let comment = format!(" The stream of the {} body", content.media_types.join(", "));
+                tokens.extend(quote! {
+                    #[doc = #comment]
+                    pub type #body_name = #rust_type;
+                });
+            }
+            return;
+        }
+        if self.content.is_empty() {
+            return;
+        }
+
+        let enum_name = format_ident!("{}", self.name);
+        let variants = self.content.iter().map(|content| {
+            let variant = format_ident!("{}", self.variant_name(content));
+            let rust_type = content.rust_type();
+            let comment = format!("The body for {}", content.media_types.join(", "));
+            quote! {
+                #[doc = #comment]
+                #variant(#rust_type)
+            }
+        });
+
+        tokens.extend(quote! {
+            #[derive(Debug)]
+            pub enum #enum_name {
+                #(#variants,)*
+            }
+        });
+    }
+}
+
+/// Renders the function that splits a multipart body in its parts, following the encoding of each part.
+fn tokenize_into_parts(struct_name: &str, parts: &[PartAsRust]) -> TokenStream {
+    let struct_name = format_ident!("{}", struct_name);
+
+    let pushed_parts = parts.iter().map(|part| {
+        let name = &part.name;
+        let field_name = match part.field_name.parse::<TokenStream>() {
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+        };
+        let content_type = match part.default_content_type() {
+            Some(content_type) => quote! { .default_content_type(#content_type) },
+            None => TokenStream::new(),
+        };
+        // the headers are encoded once and sent with every part of the property
+        let (headers, with_headers) = match part.headers.is_empty() {
+            true => (TokenStream::new(), TokenStream::new()),
+            false => {
+                let headers_field = format_ident!("{}", part.headers_field_name());
+                (
+                    quote! {
+                        let headers = match &self.#headers_field {
+                            Some(headers) => headers.to_headers()?,
+                            None => http::HeaderMap::new(),
+                        };
+                    },
+                    quote! { .with_headers(&headers) },
+                )
+            }
+        };
+
+        let is_many = matches!(part.kind, PartKind::Texts | PartKind::Files);
+        let push = |value: TokenStream| {
+            let part_value = match part.kind {
+                PartKind::Text | PartKind::Texts => quote! { MultipartPart::text(#name, #value) },
+                PartKind::Json => quote! { MultipartPart::json(#name, &#value)? },
+                PartKind::File | PartKind::Files => quote! { MultipartPart::file(#name, #value) },
+            };
+            quote! { parts.push(#part_value #content_type #with_headers); }
+        };
+        let pushed = match (part.is_optional, is_many) {
+            (false, false) => push(quote! { self.#field_name }),
+            (true, false) => {
+                let push = push(quote! { value });
+                quote! { if let Some(value) = self.#field_name { #push } }
+            }
+            (false, true) => {
+                let push = push(quote! { value });
+                quote! { for value in self.#field_name { #push } }
+            }
+            (true, true) => {
+                let push = push(quote! { value });
+                quote! { for value in self.#field_name.into_iter().flatten() { #push } }
+            }
+        };
+        match headers.is_empty() {
+            true => pushed,
+            false => quote! {
+                {
+                    #headers
+                    #pushed
+                }
+            },
+        }
+    });
+    let pushed_parts: Vec<_> = pushed_parts.collect();
+
+    quote! {
+        impl #struct_name {
+            /// Splits the body in the parts of a `multipart/form-data` payload, setting the content type
+            /// declared by the encoding of each part when it is missing, and the headers of the parts.
+            pub fn into_parts(
+                self,
+            ) -> Result<Vec<MultipartPart>, Box<dyn std::error::Error + Send + Sync>> {
+                let mut parts = Vec::new();
+                #(#pushed_parts)*
+                Ok(parts)
+            }
+        }
+    }
+}
+
+/// Renders the function that builds a multipart body from its parts, the reverse of [tokenize_into_parts].
+/// The content types of the parts are checked against the ones allowed by their encoding.
+fn tokenize_from_parts(struct_name: &str, parts: &[PartAsRust]) -> TokenStream {
+    let struct_name = format_ident!("{}", struct_name);
+
+    let mut declarations = Vec::with_capacity(parts.len());
+    let mut arms = Vec::with_capacity(parts.len());
+    let mut fields = Vec::with_capacity(parts.len());
+    for part in parts {
+        let name = &part.name;
+        let field_name = match part.field_name.parse::<TokenStream>() {
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+        };
+        let value = match part.kind {
+            PartKind::Text | PartKind::Texts => quote! { part.text_value()? },
+            PartKind::Json => quote! { part.json_value()? },
+            PartKind::File | PartKind::Files => quote! { part.into_file() },
+        };
+        let is_many = matches!(part.kind, PartKind::Texts | PartKind::Files);
+        let missing = format!("the part {name} is missing");
+        let (declaration, assignment, field) = match (part.is_optional, is_many) {
+            (is_optional, false) => (
+                quote! { let mut #field_name = None; },
+                quote! { #field_name = Some(#value); },
+                match is_optional {
+                    true => quote! { #field_name },
+                    false => quote! { #field_name: #field_name.ok_or(#missing)? },
+                },
+            ),
+            (is_optional, true) => (
+                quote! { let mut #field_name = Vec::new(); },
+                quote! { #field_name.push(#value); },
+                match is_optional {
+                    true => {
+                        quote! { #field_name: (!#field_name.is_empty()).then_some(#field_name) }
+                    }
+                    false => quote! { #field_name },
+                },
+            ),
+        };
+        declarations.push(declaration);
+        fields.push(field);
+
+        // the checks come first, since the value can take the part
+        let content_types = &part.content_types;
+        let check = match content_types.is_empty() {
+            true => TokenStream::new(),
+            false => quote! { part.check_content_type(&[#(#content_types),*])?; },
+        };
+        let headers = match part.headers.is_empty() {
+            true => TokenStream::new(),
+            false => {
+                let headers_field = format_ident!("{}", part.headers_field_name());
+                let headers_struct = format_ident!("{}", part.headers_struct_name);
+                declarations.push(quote! { let mut #headers_field = None; });
+                fields.push(quote! { #headers_field });
+                quote! { #headers_field = Some(#headers_struct::from_headers(&part.header_map())?); }
+            }
+        };
+        arms.push(quote! {
+            #name => {
+                #check
+                #headers
+                #assignment
+            }
+        });
+    }
+    let parts_argument = match parts.is_empty() {
+        true => quote! { _parts },
+        false => quote! { parts },
+    };
+    let loop_over_parts = match parts.is_empty() {
+        true => TokenStream::new(),
+        false => quote! {
+            for part in parts {
+                match part.name.as_str() {
+                    #(#arms)*
+                    _ => {}
+                }
+            }
+        },
+    };
+
+    quote! {
+        impl #struct_name {
+            /// Builds the body from the parts of a `multipart/form-data` payload, ignoring the unknown parts.
+            /// The value of a part received many times is the last one, unless the field is an array.
+            pub fn from_parts(
+                #parts_argument: Vec<MultipartPart>,
+            ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+                #(#declarations)*
+                #loop_over_parts
+                Ok(Self {
+                    #(#fields,)*
+                })
+            }
+        }
+    }
+}
+
+/// Returns the expression that decodes a body, selecting the schema from the
+/// `content_type` variable when the media types use different schemas.
+pub(crate) fn tokenize_decode_body(body: &BodyAsRust) -> TokenStream {
+    if body.content.len() < 2 {
+        return tokenize_decode_content(&body.content[0]);
+    }
+
+    let enum_name = format_ident!("{}", body.name);
+    let branches = body.content.iter().map(|content| {
+        let variant = format_ident!("{}", body.variant_name(content));
+        let media_types = &content.media_types;
+        let decoded = tokenize_decode_content(content);
+        quote! {
+            if media_type_matches(content_type, &[#(#media_types),*]) {
+                #enum_name::#variant(#decoded)
+            }
+        }
+    });
+
+    quote! {
+        #(#branches else)* {
+            return Err(ResponseError::UnexpectedContentType(content_type.map(ToString::to_string)));
+        }
+    }
+}
+
+/// Returns the expression that decodes the `body` variable to the schema of a content.
+/// Raw bodies skip serde and are wrapped as they are in the type of the schema.
+fn tokenize_decode_content(content: &ContentAsRust) -> TokenStream {
+    let schema_name = format_ident!("{}", content.schema.name);
+    match content.kind {
+        PayloadKind::Json => quote! { serde_json::from_slice(body)? },
+        PayloadKind::Form => quote! {
+            serde_urlencoded::from_bytes(body).map_err(|e| ResponseError::Deserialize(Box::new(e)))?
+        },
+        PayloadKind::Multipart(_) => quote! {
+            #schema_name::from_parts(
+                decode_multipart(content_type, body).map_err(ResponseError::Deserialize)?,
+            )
+            .map_err(ResponseError::Deserialize)?
+        },
+        PayloadKind::Text => quote! {
+            #schema_name(
+                String::from_utf8(body.to_vec()).map_err(|e| ResponseError::Deserialize(Box::new(e)))?,
+            )
+        },
+        PayloadKind::Binary => quote! { #schema_name(body.to_vec()) },
+        PayloadKind::Raw => quote! {
+            #schema_name(RawPayload {
+                content_type: content_type.map(ToString::to_string),
+                bytes: body.to_vec(),
+            })
+        },
+        PayloadKind::EventStream => quote! { decode_events(ByteStream::from_bytes(body.to_vec())) },
+        PayloadKind::JsonLines => {
+            quote! { decode_json_lines(ByteStream::from_bytes(body.to_vec())) }
+        }
+    }
+}
+
+/// Returns the content type sent with a content, that is its first media type
+/// unless it is a wildcard like `image/*`.
+fn request_content_type(content: &ContentAsRust) -> String {
+    match content.media_types.first() {
+        Some(media_type) if !media_type.contains('*') => media_type.clone(),
+        _ => match content.kind {
+            PayloadKind::Json => "application/json",
+            PayloadKind::Form => "application/x-www-form-urlencoded",
+            PayloadKind::Multipart(_) => "multipart/form-data",
+            PayloadKind::Text => "text/plain",
+            PayloadKind::Binary | PayloadKind::Raw => "application/octet-stream",
+            PayloadKind::EventStream => "text/event-stream",
+            PayloadKind::JsonLines => "application/x-ndjson",
+        }
+        .to_string(),
+    }
+}
+
+/// Returns the expression that encodes the `body` variable to its content type and bytes,
+/// selecting the media type from the variant when the body is an enum.
+/// The serialization errors are turned by `map_error` into the error returned by the caller.
+pub(crate) fn tokenize_encode_body(body: &BodyAsRust, map_error: &TokenStream) -> TokenStream {
+    if body.content.len() < 2 {
+        return tokenize_encode_content(&body.content[0], map_error);
+    }
+
+    // when a content is streamed, the others are streamed too as a single chunk
+    let is_streaming = body.is_streaming();
+    let enum_name = format_ident!("{}", body.name);
+    let arms = body.content.iter().map(|content| {
+        let variant = format_ident!("{}", body.variant_name(content));
+        let encoded = tokenize_encode_content(content, map_error);
+        match is_streaming && !content.kind.is_streaming() {
+            true => quote! {
+                #enum_name::#variant(body) => {
+                    let (content_type, body) = #encoded;
+                    (content_type, ByteStream::from_bytes(body))
+                }
+            },
+            false => quote! { #enum_name::#variant(body) => #encoded },
+        }
+    });
+
+    quote! {
+        match body {
+            #(#arms,)*
+        }
+    }
+}
+
+/// Returns the expression that encodes the `body` variable, whose type is the schema of a content.
+/// The streaming bodies are encoded to a `ByteStream` instead of bytes, since only the server sends them.
+pub(crate) fn tokenize_encode_content(
+    content: &ContentAsRust,
+    map_error: &TokenStream,
+) -> TokenStream {
+    let content_type = request_content_type(content);
+    match content.kind {
+        PayloadKind::Json => quote! {
+            (
+                #content_type.to_string(),
+                serde_json::to_vec(&body).map_err(#map_error)?,
+            )
+        },
+        PayloadKind::Form => quote! {
+            (
+                #content_type.to_string(),
+                body.to_form().map_err(#map_error)?.into_bytes(),
+            )
+        },
+        PayloadKind::Multipart(_) => quote! {
+            encode_multipart(body.into_parts().map_err(#map_error)?)
+        },
+        PayloadKind::Text => quote! { (#content_type.to_string(), body.0.into_bytes()) },
+        PayloadKind::Binary => quote! { (#content_type.to_string(), body.0) },
+        PayloadKind::Raw => quote! {
+            (
+                body.0.content_type.unwrap_or_else(|| #content_type.to_string()),
+                body.0.bytes,
+            )
+        },
+        PayloadKind::EventStream => quote! { (#content_type.to_string(), encode_events(body)) },
+        PayloadKind::JsonLines => quote! { (#content_type.to_string(), encode_json_lines(body)) },
+    }
+}
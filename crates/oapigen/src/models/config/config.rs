@@ -1,32 +1,234 @@
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DateTimeLibraries {
     Chrono,
     Jiff,
 }
 
+/// The HTTP libraries for which an adapter of the transport of the client is generated.
+/// Each library is used by the client that matches its API, async or blocking.
+/// The streamed responses need the `stream` feature of reqwest.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransportLibraries {
+    Reqwest,
+    Ureq,
+}
+
+/// The web frameworks for which the router of the server is generated.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerLibraries {
+    Axum,
+    ActixWeb,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Libraries {
     pub datetime: DateTimeLibraries,
+    pub transports: Vec<TransportLibraries>,
+    pub server: ServerLibraries,
 }
 
 impl Default for Libraries {
     fn default() -> Self {
         Libraries {
             datetime: DateTimeLibraries::Chrono,
+            transports: vec![],
+            server: ServerLibraries::Axum,
         }
     }
 }
 
+/// Targets selects what is generated on top of the models.
+///
+/// By default only the models are generated.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Targets {
+    /// Generate an async client with one method for each operation.
+    /// The responses streamed as `text/event-stream` or JSON lines are decoded to a
+    /// [Stream](https://docs.rs/futures-core) of items, so they need the futures-core crate,
+    /// and so do the items of all the pages of the paginated operations
+    pub client: bool,
+    /// Generate a blocking client in the `blocking` module, with the same methods of the async one
+    pub blocking_client: bool,
+    /// Generate in the `server` module a trait with one method for each operation,
+    /// together with the router of the server library that calls its implementations.
+    /// The requests are validated against the constraints of the spec, and the `pattern`
+    /// constraints need the [regex](https://docs.rs/regex) crate.
+    /// The security requirements are checked too, so every scheme they use must be supported.
+    /// The streamed responses are encoded from a stream of items, as the client decodes them
+    pub server: bool,
+}
+
+/// Layout selects how the generated code is split in files.
+///
+/// By default it is a single file, that can be included in a module with `include!`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// Generate a module tree in the directory at the output path, with a `mod.rs` that
+    /// re-exports its items, so that the code has the same paths of the single file.
+    /// The models are in `models`, the types of the operations in `operations`,
+    /// the shared helpers in `runtime`, and each target in its own module
+    pub modules: bool,
+    /// Put each model in its own file of the `models` module
+    pub file_per_schema: bool,
+    /// Put the types of the operations in a file of the `operations` module named after their first tag.
+    /// The operations without tags are left in `operations/mod.rs`
+    pub file_per_tag: bool,
+    /// Generate a standalone crate in the directory at the output path, with the module tree in `src`,
+    /// whose root is `lib.rs`, and a `Cargo.toml` with the dependencies used by the generated code
+    pub standalone_crate: bool,
+}
+
+/// Package sets the `[package]` of the `Cargo.toml` of a standalone crate.
+///
+/// By default the name is the title of the info of the spec in kebab case,
+/// and the version is the version of the info when it is a semantic version.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Package {
+    pub name: Option<String>,
+    pub version: Option<String>,
+}
+
+/// Filter selects names with a list of the included ones and a list of the excluded ones.
+///
+/// By default every name is selected.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filter {
+    /// The names that are selected, or all of them if it is empty
+    pub include: Vec<String>,
+    /// The names that are not selected, even if they are included
+    pub exclude: Vec<String>,
+}
+
+impl Filter {
+    /// Returns true if the name is selected by the filter.
+    pub fn allows(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|n| n == name))
+            && !self.exclude.iter().any(|n| n == name)
+    }
+}
+
+/// Filters selects the parts of the spec that are generated.
+///
+/// By default everything is generated.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filters {
+    /// The operations, by their `operationId`, or by the name of their types like `GetPets`
+    /// when they do not have one
+    pub operations: Filter,
+    /// The schemas of the components, by their name in the spec. The excluded schemas are not generated,
+    /// so the code that refers to them uses the types with the same name that are in scope,
+    /// like the ones defined next to an `include!` of the generated code
+    pub schemas: Filter,
+}
+
+/// How the pages of a list operation are requested, so that the client can return the items of all of them.
+/// The names are the ones of the spec, and the items are a property of the body of the successful response
+/// that is an array.
+///
+/// It can also be declared in the spec with the `x-pagination` extension of the operation, like
+/// `x-pagination: {kind: cursor, parameter: cursor, nextCursor: next_cursor, items: data}`.
+/// In a config file the kind is a key of the table, like
+/// `listPets = { kind = "cursor", parameter = "cursor", next_cursor = "next_cursor", items = "data" }`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Pagination {
+    /// The next page is requested with the cursor returned by the previous one,
+    /// until the cursor is missing or empty
+    Cursor {
+        /// The query parameter that receives the cursor
+        parameter: String,
+        /// The property of the body that holds the cursor of the next page
+        next_cursor: String,
+        /// The property of the body that holds the items
+        items: String,
+    },
+    /// The next page is requested at the URL of the `Link` header with `rel="next"`,
+    /// until the header is missing
+    Link {
+        /// The property of the body that holds the items
+        items: String,
+    },
+    /// The next page is requested with an offset moved past the received items,
+    /// until a page is empty or shorter than the limit
+    Offset {
+        /// The query parameter that receives the offset of the first item
+        offset: String,
+        /// The query parameter that receives the number of items of each page, if any
+        limit: Option<String>,
+        /// The property of the body that holds the items
+        items: String,
+    },
+}
+
 /// Config represents all the configuration options that can be set in the crate.
 ///
 /// In particular, it allows to configure things like:
 /// - Output path
 /// - Which libraries to use to represent complex datatypes like datetime, etc
+/// - What to generate on top of the models, like a client or a server
+/// - Whether the code is a single file, a module tree or a standalone crate
+/// - How the list operations are paginated, keyed by their `operationId`
+/// - The rust types of the formats, the derives of the models and the parts of the spec that are generated
 ///
 /// Current defaults:
 /// - datetime: [Chrono](https://docs.rs/chrono/latest/chrono/)
-#[derive(Default)]
+/// - transports: none, only the in-memory transport is generated together with the client
+/// - server: [axum](https://docs.rs/axum)
+/// - targets: only the models
+/// - layout: a single file
+/// - types: the built-in types of the formats
+/// - derives: `Debug`, `Deserialize` and `Serialize` only
+/// - filters: every operation and every schema
+///
+/// It can be loaded from a TOML or YAML file with [load_config](crate::load_config), where the keys
+/// are the names of the fields, like
+///
+/// ```toml
+/// output_path = "src/api.rs"
+///
+/// [libraries]
+/// datetime = "jiff"
+/// transports = ["reqwest"]
+///
+/// [targets]
+/// client = true
+///
+/// [types]
+/// uuid = "uuid::Uuid"
+///
+/// [filters]
+/// operations = { exclude = ["deletePet"] }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
+    /// The file, or the directory of the module tree, where [write_spec](crate::write_spec) writes the generated code
     pub output_path: PathBuf,
     pub libraries: Libraries,
+    pub targets: Targets,
+    pub layout: Layout,
+    pub package: Package,
+    pub pagination: HashMap<String, Pagination>,
+    /// The rust types of the schemas with a format, keyed by the format, like `uuid = "uuid::Uuid"`.
+    /// They replace the built-in ones, like the date-time library for `date-time`, and they are written
+    /// as they are, so they should be full paths, and their crates are not added to the `Cargo.toml`
+    /// of a standalone crate. The parameters and the headers parse them with `FromStr`
+    pub types: HashMap<String, String>,
+    /// The derives added to the ones of every model, like `["Clone", "PartialEq"]`,
+    /// that must be implemented by the types of their fields
+    pub derives: Vec<String>,
+    pub filters: Filters,
 }
@@ -0,0 +1,314 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+/// ConstraintsAsRust represents the constraints that a schema declares on its values,
+/// like `minLength` or `maximum`, checked by the generated validation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConstraintsAsRust {
+    /// The minimum number of characters of a string
+    pub min_length: Option<u64>,
+    /// The maximum number of characters of a string
+    pub max_length: Option<u64>,
+    /// The regular expression that a string has to match
+    pub pattern: Option<String>,
+    /// The inclusive lower bound of a number
+    pub minimum: Option<f64>,
+    /// The inclusive upper bound of a number
+    pub maximum: Option<f64>,
+    /// The exclusive lower bound of a number
+    pub exclusive_minimum: Option<f64>,
+    /// The exclusive upper bound of a number
+    pub exclusive_maximum: Option<f64>,
+    /// The values allowed by the schema, empty if every value is allowed
+    pub enum_values: Vec<serde_json::Value>,
+    /// The minimum number of items of an array
+    pub min_items: Option<u64>,
+    /// The maximum number of items of an array
+    pub max_items: Option<u64>,
+    /// The constraints on each item of an array, if its items declare some
+    pub items: Option<Box<ConstraintsAsRust>>,
+}
+
+impl ConstraintsAsRust {
+    /// Builds the constraints declared by a schema.
+    pub fn from_schema(schema: &oas3::spec::ObjectSchema) -> Self {
+        Self {
+            min_length: schema.min_length,
+            max_length: schema.max_length,
+            pattern: schema.pattern.clone(),
+            minimum: schema.minimum.as_ref().and_then(serde_json::Number::as_f64),
+            maximum: schema.maximum.as_ref().and_then(serde_json::Number::as_f64),
+            exclusive_minimum: schema
+                .exclusive_minimum
+                .as_ref()
+                .and_then(serde_json::Number::as_f64),
+            exclusive_maximum: schema
+                .exclusive_maximum
+                .as_ref()
+                .and_then(serde_json::Number::as_f64),
+            enum_values: schema.enum_values.clone(),
+            min_items: schema.min_items,
+            max_items: schema.max_items,
+            items: match schema.items.as_deref() {
+                Some(oas3::spec::Schema::Object(items)) => match items.as_ref() {
+                    oas3::spec::ObjectOrReference::Object(items) => {
+                        Some(Box::new(Self::from_schema(items))).filter(|c| !c.is_empty())
+                    }
+                    // the schemas of the components validate themselves
+                    oas3::spec::ObjectOrReference::Ref { .. } => None,
+                },
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the reason why one of the patterns, the ones of the items included,
+    /// is not a regular expression that the generated validation can compile.
+    pub fn invalid_pattern(&self) -> Option<String> {
+        self.pattern
+            .as_deref()
+            .and_then(pattern_error)
+            .or_else(|| self.items.as_ref().and_then(|i| i.invalid_pattern()))
+    }
+
+    /// Returns true if the schema declares no constraints.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+const NUMBER_TYPES: [&str; 10] = [
+    "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64",
+];
+
+/// Returns the reason why a pattern cannot be compiled by the `regex` crate used by the generated validation,
+/// like a look-around, or None if it can.
+pub(crate) fn pattern_error(pattern: &str) -> Option<String> {
+    // the syntax errors span many lines to point at the pattern, the last one has the reason
+    regex::Regex::new(pattern).err().map(|e| {
+        let message = e.to_string();
+        let reason = message.lines().last().unwrap_or_default().trim();
+        reason.trim_start_matches("error: ").to_string()
+    })
+}
+
+/// Escapes a name so that it can be used as a token of a JSON pointer.
+pub(crate) fn pointer_token(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// Returns the statements that check the constraints on `value`, a reference to a value of `rust_type`,
+/// and push a `ValidationError` to `errors` for each constraint that is not satisfied.
+/// The JSON pointer of the error is built by `pointer`, an expression evaluated only when the check fails.
+///
+/// The constraints that do not apply to the type, like `minLength` on a number, are ignored.
+pub(crate) fn tokenize_checks(
+    constraints: &ConstraintsAsRust,
+    rust_type: &str,
+    value: &TokenStream,
+    pointer: &TokenStream,
+) -> TokenStream {
+    let error = |message: String| {
+        quote! {
+            errors.push(ValidationError {
+                pointer: #pointer,
+                message: #message.to_string(),
+            });
+        }
+    };
+    let check = |condition: TokenStream, message: String| {
+        let error = error(message);
+        quote! {
+            if #condition {
+                #error
+            }
+        }
+    };
+    let mut checks = Vec::new();
+
+    if rust_type.starts_with("Vec<") {
+        match constraints.min_items {
+            None | Some(0) => {}
+            Some(1) => checks.push(check(
+                quote! { #value.is_empty() },
+                "must have at least 1 items".to_string(),
+            )),
+            Some(min_items) => {
+                let bound = Literal::u64_unsuffixed(min_items);
+                checks.push(check(
+                    quote! { #value.len() < #bound },
+                    format!("must have at least {min_items} items"),
+                ));
+            }
+        }
+        if let Some(max_items) = constraints.max_items {
+            let bound = Literal::u64_unsuffixed(max_items);
+            checks.push(check(
+                quote! { #value.len() > #bound },
+                format!("must have at most {max_items} items"),
+            ));
+        }
+        // the items are checked one by one, their pointer ends with their index
+        let items_type = rust_type
+            .strip_prefix("Vec<")
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or_default();
+        if let Some(items) = &constraints.items {
+            let item_checks = tokenize_checks(
+                items,
+                items_type,
+                &quote! { item },
+                &quote! { format!("{}/{}", #pointer, index) },
+            );
+            if !item_checks.is_empty() {
+                checks.push(quote! {
+                    for (index, item) in #value.iter().enumerate() {
+                        #item_checks
+                    }
+                });
+            }
+        }
+    }
+
+    if rust_type == "String" {
+        if let Some(min_length) = constraints.min_length.filter(|l| *l > 0) {
+            let bound = Literal::u64_unsuffixed(min_length);
+            checks.push(check(
+                quote! { #value.chars().count() < #bound },
+                format!("must be at least {min_length} characters long"),
+            ));
+        }
+        if let Some(max_length) = constraints.max_length {
+            let bound = Literal::u64_unsuffixed(max_length);
+            checks.push(check(
+                quote! { #value.chars().count() > #bound },
+                format!("must be at most {max_length} characters long"),
+            ));
+        }
+        if let Some(pattern) = &constraints.pattern {
+            // the regular expression is compiled once, it was checked when the code was generated
+            let error = error(format!("must match the pattern {pattern}"));
+            checks.push(quote! {
+                {
+                    static PATTERN: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+                        regex::Regex::new(#pattern).expect("the pattern is a valid regular expression")
+                    });
+                    if !PATTERN.is_match(#value) {
+                        #error
+                    }
+                }
+            });
+        }
+        let allowed: Vec<_> = constraints
+            .enum_values
+            .iter()
+            .filter_map(serde_json::Value::as_str)
+            .collect();
+        if !allowed.is_empty() {
+            checks.push(check(
+                quote! { ![#(#allowed),*].contains(&#value.as_str()) },
+                format!("must be one of {}", allowed.join(", ")),
+            ));
+        }
+    }
+
+    if NUMBER_TYPES.contains(&rust_type) {
+        // the bounds are compared as floats, since they can be fractional even for integers
+        let number = match rust_type {
+            "f64" => quote! { *#value },
+            _ => quote! { (*#value as f64) },
+        };
+        let bounds = [
+            (
+                constraints.minimum,
+                quote! { < },
+                "greater than or equal to",
+            ),
+            (constraints.maximum, quote! { > }, "less than or equal to"),
+            (constraints.exclusive_minimum, quote! { <= }, "greater than"),
+            (constraints.exclusive_maximum, quote! { >= }, "less than"),
+        ];
+        for (bound, operator, description) in bounds {
+            if let Some(bound) = bound {
+                let literal = Literal::f64_unsuffixed(bound);
+                checks.push(check(
+                    quote! { #number #operator #literal },
+                    format!("must be {description} {bound}"),
+                ));
+            }
+        }
+        let allowed: Vec<_> = constraints
+            .enum_values
+            .iter()
+            .filter_map(serde_json::Value::as_f64)
+            .collect();
+        if !allowed.is_empty() {
+            let literals = allowed.iter().map(|v| Literal::f64_unsuffixed(*v));
+            let descriptions: Vec<_> = allowed.iter().map(ToString::to_string).collect();
+            checks.push(check(
+                quote! { ![#(#literals),*].contains(&#number) },
+                format!("must be one of {}", descriptions.join(", ")),
+            ));
+        }
+    }
+
+    quote! { #(#checks)* }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_checks() {
+        let constraints = ConstraintsAsRust {
+            min_length: Some(1),
+            max_length: Some(10),
+            pattern: Some("^[a-z]+$".to_string()),
+            enum_values: vec![serde_json::json!("cat"), serde_json::json!("dog")],
+            ..Default::default()
+        };
+        let numbers = ConstraintsAsRust {
+            minimum: Some(1.0),
+            exclusive_maximum: Some(100.0),
+            ..Default::default()
+        };
+        let items = ConstraintsAsRust {
+            max_items: Some(3),
+            items: Some(Box::new(ConstraintsAsRust {
+                min_length: Some(2),
+                pattern: Some("^[a-z]+$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        let value = quote! { value };
+        let pointer = quote! { format!("{}/name", pointer) };
+        let checks = [
+            tokenize_checks(&constraints, "String", &value, &pointer),
+            tokenize_checks(&numbers, "i32", &value, &pointer),
+            tokenize_checks(&items, "Vec<String>", &value, &pointer),
+        ];
+
+        let tokens = quote! {
+            fn validate(value: &String, errors: &mut Vec<ValidationError>) {
+                #(#checks)*
+            }
+        };
+        let b = syn::parse_file(&tokens.to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+
+    #[rstest]
+    #[case("^[a-z]+$", None)]
+    #[case(
+        "^(?!admin)",
+        Some("look-around, including look-ahead and look-behind, is not supported")
+    )]
+    #[case("[a-", Some("unclosed character class"))]
+    fn test_pattern_error(#[case] pattern: &str, #[case] expected: Option<&str>) {
+        assert_eq!(pattern_error(pattern).as_deref(), expected);
+    }
+}
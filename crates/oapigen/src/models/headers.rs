@@ -0,0 +1,126 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::models::schema::Imports;
+
+/// HeaderAsRust represents a header declared by a response, or by the encoding of a part of a multipart body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderAsRust {
+    /// The name of the header as it appears in the spec, like `X-RateLimit-Remaining`
+    pub name: String,
+    /// The name of the field of the headers struct in snake case
+    pub field_name: String,
+    /// The type of the value of the header
+    pub rust_type: String,
+    /// The imports needed to make the type compile.
+    pub imports: Imports,
+    /// The optional description of the header
+    pub comment: Option<String>,
+    /// Is the header required?
+    pub is_required: bool,
+}
+
+/// Renders the struct that collects the headers of a response or of a part, together with
+/// the functions that parse it from a [http::HeaderMap] and encode it to one.
+/// The struct derives `macros`, like the body that holds the headers of its parts.
+pub(crate) fn tokenize_headers_struct(
+    struct_name: &str,
+    headers: &[HeaderAsRust],
+    macros: &TokenStream,
+) -> TokenStream {
+    let struct_name = format_ident!("{}", struct_name);
+
+    let mut fields = Vec::with_capacity(headers.len());
+    let mut parsed_fields = Vec::with_capacity(headers.len());
+    let mut inserted_fields = Vec::with_capacity(headers.len());
+    for header in headers {
+        let field_name = format_ident!("{}", header.field_name);
+        let header_name = &header.name;
+        let rust_type = match header.rust_type.parse::<TokenStream>() {
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn header type to tokens: {}", e)),
+        };
+        let comment = match &header.comment {
+            None => TokenStream::new(),
+            Some(c) => quote! { #[doc = #c] },
+        };
+
+        if header.is_required {
+            fields.push(quote! { #comment pub #field_name: #rust_type });
+            parsed_fields.push(quote! {
+                #field_name: parse_header(headers, #header_name)?
+                    .ok_or(HeaderError::Missing(#header_name))?
+            });
+            inserted_fields.push(quote! {
+                insert_header(&mut headers, #header_name, &self.#field_name)?;
+            });
+        } else {
+            fields.push(quote! { #comment pub #field_name: Option<#rust_type> });
+            parsed_fields.push(quote! { #field_name: parse_header(headers, #header_name)? });
+            inserted_fields.push(quote! {
+                if let Some(value) = &self.#field_name {
+                    insert_header(&mut headers, #header_name, value)?;
+                }
+            });
+        }
+    }
+
+    quote! {
+        #macros
+        pub struct #struct_name {
+            #(#fields,)*
+        }
+
+        impl #struct_name {
+            /// Parses the headers, failing if a required header is missing
+            /// or if a header cannot be parsed to its type.
+            pub fn from_headers(headers: &http::HeaderMap) -> Result<Self, HeaderError> {
+                Ok(Self {
+                    #(#parsed_fields,)*
+                })
+            }
+
+            /// Encodes the headers, failing if a value cannot be sent in a header.
+            pub fn to_headers(&self) -> Result<http::HeaderMap, HeaderError> {
+                let mut headers = http::HeaderMap::new();
+                #(#inserted_fields)*
+                Ok(headers)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_struct() {
+        let headers = vec![
+            HeaderAsRust {
+                name: "ETag".to_string(),
+                field_name: "e_tag".to_string(),
+                rust_type: "String".to_string(),
+                imports: Imports::new(),
+                comment: None,
+                is_required: true,
+            },
+            HeaderAsRust {
+                name: "X-RateLimit-Remaining".to_string(),
+                field_name: "x_rate_limit_remaining".to_string(),
+                rust_type: "i32".to_string(),
+                imports: Imports::new(),
+                comment: Some("The number of requests left".to_string()),
+                is_required: false,
+            },
+        ];
+
+        let tokens = tokenize_headers_struct(
+            "GetWidgetResponse200Headers",
+            &headers,
+            &quote! { #[derive(Debug)] },
+        );
+        let b = syn::parse_file(&tokens.to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+}
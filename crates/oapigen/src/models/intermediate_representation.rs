@@ -1,8 +1,3 @@
-pub(crate) struct OperationSchemaInputs<'a> {
-    pub(crate) operation_name: &'a String,
-    pub(crate) response_name: &'a String,
-}
-
 pub(crate) struct SchemaInputs<'a> {
     pub(crate) schema_name: &'a String,
 }
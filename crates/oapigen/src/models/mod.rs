@@ -1,7 +1,25 @@
+pub mod body;
 pub mod config;
+pub mod constraints;
+pub mod headers;
 pub(crate) mod intermediate_representation;
+pub mod operation;
+pub mod pagination;
+pub mod parameters;
 pub mod schema;
+pub mod security;
+pub mod servers;
+pub mod spec;
 
+pub use body::*;
 pub use config::*;
+pub use constraints::*;
+pub use headers::*;
 pub(crate) use intermediate_representation::*;
+pub use operation::*;
+pub use pagination::*;
+pub use parameters::*;
 pub use schema::*;
+pub use security::*;
+pub use servers::*;
+pub use spec::*;
@@ -0,0 +1,530 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+
+use crate::models::body::{BodyAsRust, PayloadKind, RequestBodyAsRust, tokenize_decode_body};
+use crate::models::headers::{HeaderAsRust, tokenize_headers_struct};
+use crate::models::pagination::PaginationAsRust;
+use crate::models::parameters::{ParameterAsRust, tokenize_parameters_struct};
+use crate::models::schema::field_name;
+use crate::models::security::SecurityRequirementAsRust;
+
+/// StatusCode represents a key of the responses object of an operation.
+///
+/// The variants are declared in the order in which they have to be matched:
+/// exact status codes first, then ranges and finally the default response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StatusCode {
+    /// A single status code, like `200`.
+    Exact(u16),
+    /// A range of status codes, like `2XX`, stored as its leading digit.
+    Range(u16),
+    /// The `default` response, used for every status code not covered otherwise.
+    Default,
+}
+
+impl StatusCode {
+    /// Returns the name used to build the rust identifiers related to this status code,
+    /// for example `200`, `2XX` or `Default`.
+    pub fn name(&self) -> String {
+        match self {
+            StatusCode::Exact(code) => code.to_string(),
+            StatusCode::Range(class) => format!("{class}XX"),
+            StatusCode::Default => "Default".to_string(),
+        }
+    }
+
+    /// Returns the name of the variant of the response enum associated with this status code.
+    pub fn variant_name(&self) -> String {
+        match self {
+            StatusCode::Default => self.name(),
+            _ => format!("Status{}", self.name()),
+        }
+    }
+}
+
+/// ResponseAsRust represents one of the responses of an operation.
+#[derive(Debug)]
+pub struct ResponseAsRust {
+    /// The status code, or range of status codes, of the response
+    pub status: StatusCode,
+    /// The optional description of the response
+    pub description: Option<String>,
+    /// The body of the response, if any of its media types has a schema
+    pub body: Option<BodyAsRust>,
+    /// The headers declared by the response
+    pub headers: Vec<HeaderAsRust>,
+}
+
+impl ResponseAsRust {
+    /// Returns true if one of the media types of the body is streamed.
+    pub fn is_streaming(&self) -> bool {
+        self.body.as_ref().is_some_and(BodyAsRust::is_streaming)
+    }
+}
+
+/// OperationAsRust represents an operation of the OpenAPI specification.
+/// It contains all the information needed to render the types of the operation
+/// and the methods that call it.
+#[derive(Debug)]
+pub struct OperationAsRust {
+    /// The name of the operation in UpperCamel case
+    pub name: String,
+    /// The HTTP method of the operation in uppercase, like `GET`
+    pub method: String,
+    /// The path of the operation, with its template expressions, like `/pets/{petId}`
+    pub path: String,
+    /// The optional description of the operation, taken from its summary if it has no description
+    pub description: Option<String>,
+    /// The parameters of the operation, including the ones declared by its path
+    pub parameters: Vec<ParameterAsRust>,
+    /// The body of the request, if any of its media types has a schema
+    pub request_body: Option<RequestBodyAsRust>,
+    /// The responses of the operation, sorted in the order in which they have to be matched
+    pub responses: Vec<ResponseAsRust>,
+    /// The alternative security requirements of the operation, empty if it needs no authentication
+    pub security: Vec<SecurityRequirementAsRust>,
+    /// How the pages of the operation are requested, if it is paginated
+    pub pagination: Option<PaginationAsRust>,
+    /// The tags of the operation, that group its code in a module of the module tree layout
+    pub tags: Vec<String>,
+}
+
+impl OperationAsRust {
+    /// Returns the name of the methods that call the operation, in snake case.
+    pub fn method_name(&self) -> String {
+        field_name(&self.name)
+    }
+
+    /// Returns the name of the struct that collects the parameters of the operation.
+    pub fn parameters_struct_name(&self) -> String {
+        format!("{}Params", self.name)
+    }
+
+    /// Returns the name of the enum that collects all the responses of the operation.
+    pub fn response_enum_name(&self) -> String {
+        format!("{}Response", self.name)
+    }
+
+    /// Returns the name of the struct that collects the headers of one of the responses.
+    pub fn headers_struct_name(&self, response: &ResponseAsRust) -> String {
+        format!("{}Response{}Headers", self.name, response.status.name())
+    }
+}
+
+impl ToTokens for OperationAsRust {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if !self.parameters.is_empty() {
+            let struct_name = self.parameters_struct_name();
+            tokens.extend(tokenize_parameters_struct(&struct_name, &self.parameters));
+        }
+        if let Some(request_body) = &self.request_body {
+            request_body.body.to_tokens(tokens);
+        }
+        if self.responses.is_empty() {
+            return;
+        }
+
+        tokens.extend(tokenize_response_enum(self));
+        for response in &self.responses {
+            if let Some(body) = &response.body {
+                body.to_tokens(tokens);
+            }
+            if !response.headers.is_empty() {
+                let struct_name = self.headers_struct_name(response);
+                tokens.extend(tokenize_headers_struct(
+                    &struct_name,
+                    &response.headers,
+                    &quote! { #[derive(Debug)] },
+                ));
+            }
+        }
+    }
+}
+
+fn tokenize_response_enum(operation: &OperationAsRust) -> TokenStream {
+    let enum_name = format_ident!("{}", operation.response_enum_name());
+
+    let mut variants = Vec::with_capacity(operation.responses.len());
+    let mut status_arms = Vec::with_capacity(operation.responses.len());
+    let mut decode_arms = Vec::with_capacity(operation.responses.len());
+    let mut stream_arms = Vec::new();
+    let mut streamed_arms = 0;
+
+    for response in &operation.responses {
+        let variant = format_ident!("{}", response.status.variant_name());
+        let comment = match &response.description {
+            Some(c) if !c.is_empty() => quote! { #[doc = #c] },
+            _ => TokenStream::new(),
+        };
+        let body_type = response.body.as_ref().map(|b| format_ident!("{}", b.name));
+
+        let pattern = match response.status {
+            StatusCode::Exact(code) => {
+                let code = Literal::u16_unsuffixed(code);
+                quote! { #code }
+            }
+            StatusCode::Range(class) => {
+                let start = Literal::u16_unsuffixed(class * 100);
+                let end = Literal::u16_unsuffixed(class * 100 + 99);
+                quote! { #start..=#end }
+            }
+            StatusCode::Default => quote! { _ },
+        };
+
+        // the variants hold the status code when it is not exact, then the body and the headers
+        let mut field_types = Vec::new();
+        let mut field_values = Vec::new();
+        if !matches!(response.status, StatusCode::Exact(_)) {
+            field_types.push(quote! { u16 });
+            field_values.push(quote! { status });
+        }
+        if let Some(body_type) = &body_type {
+            field_types.push(quote! { #body_type });
+            field_values.push(quote! { decoded });
+        }
+        if !response.headers.is_empty() {
+            let headers_struct = format_ident!("{}", operation.headers_struct_name(response));
+            field_types.push(quote! { #headers_struct });
+            field_values.push(
+                quote! { #headers_struct::from_headers(headers).map_err(ResponseError::Header)? },
+            );
+        }
+        let (fields, value) = match field_types.is_empty() {
+            true => (TokenStream::new(), quote! { Self::#variant }),
+            false => (
+                quote! { (#(#field_types),*) },
+                quote! { Self::#variant(#(#field_values),*) },
+            ),
+        };
+        let status_arm = match (response.status, field_types.is_empty()) {
+            (StatusCode::Exact(code), true) => {
+                let code = Literal::u16_unsuffixed(code);
+                quote! { Self::#variant => #code }
+            }
+            (StatusCode::Exact(code), false) => {
+                let code = Literal::u16_unsuffixed(code);
+                quote! { Self::#variant(..) => #code }
+            }
+            _ => quote! { Self::#variant(status, ..) => *status },
+        };
+
+        let decode_arm = match &response.body {
+            Some(body) => {
+                let decode_body = tokenize_decode_body(body);
+                quote! {
+                    #pattern => {
+                        let decoded = #decode_body;
+                        Ok(#value)
+                    }
+                }
+            }
+            None => quote! { #pattern => Ok(#value) },
+        };
+
+        // the streaming bodies are decoded while they are received, so they skip `from_parts`
+        let streaming: Vec<_> = response
+            .body
+            .iter()
+            .flat_map(|b| &b.content)
+            .filter(|c| c.kind.is_streaming())
+            .collect();
+        match (&response.body, streaming.as_slice()) {
+            (_, []) => stream_arms.push(quote! { #pattern => {} }),
+            (Some(body), contents) => {
+                for content in contents {
+                    let decoded = match content.kind {
+                        PayloadKind::EventStream => quote! { decode_events(body) },
+                        _ => quote! { decode_json_lines(body) },
+                    };
+                    let (guard, decoded) = match body.content.len() {
+                        1 => (TokenStream::new(), decoded),
+                        _ => {
+                            let media_types = &content.media_types;
+                            let body_enum = format_ident!("{}", body.name);
+                            let body_variant = format_ident!("{}", body.variant_name(content));
+                            (
+                                quote! { if media_type_matches(content_type, &[#(#media_types),*]) },
+                                quote! { #body_enum::#body_variant(#decoded) },
+                            )
+                        }
+                    };
+                    stream_arms.push(quote! {
+                        #pattern #guard => {
+                            let decoded = #decoded;
+                            return Ok(#value);
+                        }
+                    });
+                    streamed_arms = stream_arms.len();
+                }
+            }
+            (None, _) => {}
+        }
+
+        variants.push(quote! { #comment #variant #fields });
+        status_arms.push(status_arm);
+        decode_arms.push(decode_arm);
+    }
+
+    let has_default = operation
+        .responses
+        .iter()
+        .any(|r| r.status == StatusCode::Default);
+    if !has_default {
+        decode_arms.push(quote! { _ => Err(ResponseError::UnexpectedStatus(status)) });
+    }
+
+    // the body is not read when none of the responses has one,
+    // and the content type only when one of the bodies needs it
+    let body = match operation.responses.iter().any(|r| r.body.is_some()) {
+        true => quote! { body },
+        false => quote! { _body },
+    };
+    let reads_content_type = operation
+        .responses
+        .iter()
+        .any(|r| r.body.as_ref().is_some_and(BodyAsRust::reads_content_type));
+    let has_headers = operation.responses.iter().any(|r| !r.headers.is_empty());
+    let headers = match reads_content_type || has_headers {
+        true => quote! { headers },
+        false => quote! { _headers },
+    };
+    let content_type = match reads_content_type {
+        true => quote! {
+            let content_type = headers
+                .get(http::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok());
+        },
+        false => TokenStream::new(),
+    };
+
+    // exact status codes are matched before the ranges that contain them on purpose
+    let allow_overlap = match operation
+        .responses
+        .iter()
+        .any(|r| matches!(r.status, StatusCode::Range(_)))
+    {
+        true => quote! { #[allow(clippy::match_overlapping_arm)] },
+        false => TokenStream::new(),
+    };
+
+    let from_stream = match operation.responses.iter().any(ResponseAsRust::is_streaming) {
+        true => {
+            // the responses that are not streamed are decoded once their body is complete, so only the
+            // ones that precede a streamed response need an arm, to be matched before it
+            stream_arms.truncate(streamed_arms);
+            // the content type selects the streamed body only when the body has many media types
+            let selects_content = operation.responses.iter().any(|r| {
+                r.body
+                    .as_ref()
+                    .is_some_and(|b| b.content.len() > 1 && b.is_streaming())
+            });
+            let stream_content_type = match selects_content {
+                true => content_type.clone(),
+                false => TokenStream::new(),
+            };
+            let catches_all = operation.responses.iter().any(|r| {
+                r.status == StatusCode::Default
+                    && r.body.as_ref().is_some_and(|b| b.content.len() == 1)
+                    && r.is_streaming()
+            });
+            if !catches_all {
+                stream_arms.push(quote! { _ => {} });
+            }
+            // the arms are kept as a match, like the ones of `from_parts`, even when there is only one
+            let allow_single_match = match stream_arms.len() {
+                2 => quote! { #[allow(clippy::single_match)] },
+                _ => TokenStream::new(),
+            };
+            quote! {
+                /// Decodes the response from its status code, headers and a body that is still being received.
+                /// The streaming bodies are decoded while they are received, the others once they are complete.
+                #allow_overlap
+                #allow_single_match
+                pub async fn from_stream(
+                    status: u16,
+                    headers: &http::HeaderMap,
+                    body: ByteStream,
+                ) -> Result<Self, ResponseError> {
+                    #stream_content_type
+                    match status {
+                        #(#stream_arms,)*
+                    }
+                    let body = body.into_bytes().await.map_err(ResponseError::Body)?;
+                    Self::from_parts(status, headers, &body)
+                }
+            }
+        }
+        false => TokenStream::new(),
+    };
+
+    quote! {
+        #[derive(Debug)]
+        pub enum #enum_name {
+            #(#variants,)*
+        }
+
+        impl #enum_name {
+            /// Returns the HTTP status code of the response.
+            pub fn status(&self) -> u16 {
+                match self {
+                    #(#status_arms,)*
+                }
+            }
+
+            /// Decodes the response from its status code, headers and body.
+            /// Exact status codes are matched first, then ranges and finally the default response.
+            #allow_overlap
+            pub fn from_parts(
+                status: u16,
+                #headers: &http::HeaderMap,
+                #body: &[u8],
+            ) -> Result<Self, ResponseError> {
+                #content_type
+                match status {
+                    #(#decode_arms,)*
+                }
+            }
+
+            #from_stream
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::body::{ContentAsRust, PayloadKind};
+    use crate::models::{ConstraintsAsRust, CurrentType, Imports, SchemaAsRust};
+    use std::collections::BTreeSet;
+
+    fn schema(name: &str, rust_type: &str) -> SchemaAsRust {
+        SchemaAsRust {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+            macros: BTreeSet::new(),
+            imports: Imports::new(),
+            comment: None,
+            is_optional: false,
+            fields: vec![],
+            constraints: ConstraintsAsRust::default(),
+            current_type: CurrentType::Type,
+        }
+    }
+
+    fn body(name: &str) -> Option<BodyAsRust> {
+        Some(BodyAsRust {
+            name: name.to_string(),
+            content: vec![ContentAsRust {
+                media_types: vec!["application/json".to_string()],
+                kind: PayloadKind::Json,
+                schema: schema(name, "i32"),
+            }],
+        })
+    }
+
+    #[test]
+    fn test_status_code_order() {
+        let mut got = vec![
+            StatusCode::Default,
+            StatusCode::Range(2),
+            StatusCode::Exact(404),
+            StatusCode::Exact(200),
+        ];
+        got.sort();
+
+        assert_eq!(
+            got,
+            vec![
+                StatusCode::Exact(200),
+                StatusCode::Exact(404),
+                StatusCode::Range(2),
+                StatusCode::Default,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_enum() {
+        let operation = OperationAsRust {
+            name: "GetWidget".to_string(),
+            method: "GET".to_string(),
+            path: "/widgets".to_string(),
+            description: None,
+            parameters: vec![],
+            request_body: None,
+            responses: vec![
+                ResponseAsRust {
+                    status: StatusCode::Exact(200),
+                    description: Some("The request has succeeded.".to_string()),
+                    body: body("GetWidgetResponse200"),
+                    headers: vec![],
+                },
+                ResponseAsRust {
+                    status: StatusCode::Exact(404),
+                    description: None,
+                    body: None,
+                    headers: vec![],
+                },
+                ResponseAsRust {
+                    status: StatusCode::Range(2),
+                    description: None,
+                    body: body("GetWidgetResponse2XX"),
+                    headers: vec![],
+                },
+                ResponseAsRust {
+                    status: StatusCode::Default,
+                    description: None,
+                    body: None,
+                    headers: vec![],
+                },
+            ],
+            security: vec![],
+            pagination: None,
+            tags: vec![],
+        };
+
+        let b = syn::parse_file(&operation.to_token_stream().to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+
+    #[test]
+    fn test_response_enum_with_media_types() {
+        let operation = OperationAsRust {
+            name: "GetWidget".to_string(),
+            method: "GET".to_string(),
+            path: "/widgets".to_string(),
+            description: None,
+            parameters: vec![],
+            request_body: None,
+            responses: vec![ResponseAsRust {
+                status: StatusCode::Exact(200),
+                description: None,
+                body: Some(BodyAsRust {
+                    name: "GetWidgetResponse200".to_string(),
+                    content: vec![
+                        ContentAsRust {
+                            media_types: vec![
+                                "application/json".to_string(),
+                                "application/xml".to_string(),
+                            ],
+                            kind: PayloadKind::Json,
+                            schema: schema("GetWidgetResponse200Json", "i32"),
+                        },
+                        ContentAsRust {
+                            media_types: vec!["text/plain".to_string()],
+                            kind: PayloadKind::Json,
+                            schema: schema("GetWidgetResponse200Plain", "String"),
+                        },
+                    ],
+                }),
+                headers: vec![],
+            }],
+            security: vec![],
+            pagination: None,
+            tags: vec![],
+        };
+
+        let b = syn::parse_file(&operation.to_token_stream().to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+}
@@ -0,0 +1,48 @@
+use crate::models::StatusCode;
+
+/// PaginationParameterAsRust represents a query parameter that selects a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginationParameterAsRust {
+    /// The field of the parameters struct that receives the parameter
+    pub field_name: String,
+    /// The rust type of the parameter, without the Option wrapper for optional parameters
+    pub rust_type: String,
+    /// Is the parameter required?
+    pub is_required: bool,
+}
+
+/// PaginationKind represents how the next page of a list operation is requested.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaginationKind {
+    /// The cursor returned by a page is sent in a query parameter to request the next one
+    Cursor {
+        /// The parameter that receives the cursor
+        parameter: PaginationParameterAsRust,
+        /// The field of the body that holds the cursor of the next page, missing or empty on the last page
+        next_cursor: String,
+    },
+    /// The next page is requested at the URL of the `Link` header with `rel="next"`
+    Link,
+    /// The offset of the first item is moved past the items received so far
+    Offset {
+        /// The parameter that receives the offset
+        offset: PaginationParameterAsRust,
+        /// The parameter that receives the number of items of each page, if any
+        limit: Option<PaginationParameterAsRust>,
+    },
+}
+
+/// PaginationAsRust represents how the client collects the items of all the pages of an operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginationAsRust {
+    /// How the next page is requested
+    pub kind: PaginationKind,
+    /// The status code of the response that holds a page
+    pub status: StatusCode,
+    /// The field of the body of the page that holds the items
+    pub items: String,
+    /// Is the field of the items optional?
+    pub is_items_optional: bool,
+    /// The rust type of one of the items
+    pub item_type: String,
+}
@@ -0,0 +1,115 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::models::constraints::ConstraintsAsRust;
+use crate::models::schema::Imports;
+
+/// ParameterLocation represents where a parameter is sent in the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    /// The parameter replaces a template expression of the path, like `{petId}`.
+    Path,
+    /// The parameter is appended to the query string.
+    Query,
+    /// The parameter is sent as a header.
+    Header,
+    /// The parameter is sent in the `Cookie` header.
+    Cookie,
+}
+
+/// ParameterAsRust represents a parameter of an operation.
+#[derive(Debug)]
+pub struct ParameterAsRust {
+    /// The name of the parameter as it appears in the spec, like `petId`
+    pub name: String,
+    /// The name of the field of the parameters struct in snake case
+    pub field_name: String,
+    /// Where the parameter is sent
+    pub location: ParameterLocation,
+    /// The type of the value of the parameter, without the Option wrapper for optional parameters
+    pub rust_type: String,
+    /// The imports needed to make the type compile.
+    pub imports: Imports,
+    /// The optional description of the parameter
+    pub comment: Option<String>,
+    /// Is the parameter required?
+    pub is_required: bool,
+    /// Is the parameter an array of values?
+    pub is_array: bool,
+    /// Are the values of an array sent as separate parameters, like `id=1&id=2`?
+    pub explode: bool,
+    /// The constraints on the value of the parameter
+    pub constraints: ConstraintsAsRust,
+}
+
+/// Renders the struct that collects the parameters of an operation.
+pub(crate) fn tokenize_parameters_struct(
+    struct_name: &str,
+    parameters: &[ParameterAsRust],
+) -> TokenStream {
+    let struct_name = format_ident!("{}", struct_name);
+
+    let fields = parameters.iter().map(|parameter| {
+        let field_name = match parameter.field_name.parse::<TokenStream>() {
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+        };
+        let rust_type = match parameter.rust_type.parse::<TokenStream>() {
+            Ok(v) if parameter.is_required => v,
+            Ok(v) => quote! { Option<#v> },
+            Err(e) => panic!("{}", format!("cannot turn parameter type to tokens: {}", e)),
+        };
+        let comment = match &parameter.comment {
+            None => TokenStream::new(),
+            Some(c) => quote! { #[doc = #c] },
+        };
+
+        quote! { #comment pub #field_name: #rust_type }
+    });
+
+    quote! {
+        #[derive(Debug, Clone)]
+        pub struct #struct_name {
+            #(#fields,)*
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters_struct() {
+        let parameters = vec![
+            ParameterAsRust {
+                name: "petId".to_string(),
+                field_name: "pet_id".to_string(),
+                location: ParameterLocation::Path,
+                rust_type: "i64".to_string(),
+                imports: Imports::new(),
+                comment: Some("The id of the pet".to_string()),
+                is_required: true,
+                is_array: false,
+                explode: false,
+                constraints: ConstraintsAsRust::default(),
+            },
+            ParameterAsRust {
+                name: "tags".to_string(),
+                field_name: "tags".to_string(),
+                location: ParameterLocation::Query,
+                rust_type: "Vec<String>".to_string(),
+                imports: Imports::new(),
+                comment: None,
+                is_required: false,
+                is_array: true,
+                explode: true,
+                constraints: ConstraintsAsRust::default(),
+            },
+        ];
+
+        let tokens = tokenize_parameters_struct("GetPetParams", &parameters);
+        let b = syn::parse_file(&tokens.to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+}
@@ -1,8 +1,10 @@
+use crate::models::constraints::ConstraintsAsRust;
+use convert_case::{Case, Casing};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{ToTokens, quote};
 use std::hash::Hash;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet},
     fmt,
 };
 
@@ -11,7 +13,7 @@ use std::{
 /// For example assume that two different structs need
 /// chrono::DateTime then we add "chrono::DateTime" to the Imports
 /// for the first struct that we hit, and we will not add it in the second case since we already have it.
-pub type Imports = HashMap<String, String>;
+pub type Imports = BTreeMap<String, String>;
 
 #[derive(Debug)]
 pub enum CurrentType {
@@ -22,6 +24,25 @@ pub enum CurrentType {
     Struct,
 }
 
+/// FieldAsRust represents a field of a struct, inferred from a property of an object schema.
+#[derive(Debug)]
+pub struct FieldAsRust {
+    /// The name of the field in snake case
+    pub name: String,
+    /// The name of the property in the spec, used to rename the field when (de)serializing
+    pub original_name: String,
+    /// The type of the field, without the Option wrapper for optional fields
+    pub rust_type: String,
+    /// The optional comment to the field
+    pub comment: Option<String>,
+    /// Is the field optional?
+    pub is_optional: bool,
+    /// Is the field left out when (de)serializing, like the headers of the parts of a multipart body?
+    pub is_skipped: bool,
+    /// The constraints on the value of the field
+    pub constraints: ConstraintsAsRust,
+}
+
 /// SchemaAsRust represents a rust type that was inferred from a OpenAPI specification.
 /// It contains all the information needed to render the type correctly.
 #[derive(Debug)]
@@ -31,13 +52,17 @@ pub struct SchemaAsRust {
     /// The type that the struct uses
     pub rust_type: String,
     /// The macros that will be applied to this type as a TokenStream
-    pub macros: HashSet<String>,
+    pub macros: BTreeSet<String>,
     /// The imports needed to make the type compile.
     pub imports: Imports,
     /// The optional comment to the schema
     pub comment: Option<String>,
     /// Is the type optional?
     pub is_optional: bool,
+    /// The fields of the type, used only by structs
+    pub fields: Vec<FieldAsRust>,
+    /// The constraints on the value of the type, used only by types that wrap another one
+    pub constraints: ConstraintsAsRust,
 
     pub current_type: CurrentType,
 }
@@ -86,7 +111,7 @@ impl ToTokens for SchemaAsRust {
             CurrentType::Const => todo!(),
             CurrentType::Enum => todo!(),
             CurrentType::Vector => todo!(),
-            CurrentType::Struct => todo!(),
+            CurrentType::Struct => tokenize_struct(self),
         };
 
         tokens.clone_from(&generated_tokens);
@@ -94,16 +119,73 @@ impl ToTokens for SchemaAsRust {
 }
 
 fn tokenize_type(rust_schema: &SchemaAsRust) -> TokenStream {
-    let tokenized_name = match &rust_schema.name.parse::<TokenStream>() {
-        Ok(v) => v.clone(),
-        Err(e) => panic!("{}", format!("cannot turn name to tokens: {}", e)),
-    };
+    let tokenized_name = tokenize_name(rust_schema);
     let tokenized_type = match &rust_schema.rust_type.parse::<TokenStream>() {
         Ok(v) => v.clone(),
         Err(e) => panic!("{}", format!("cannot turn rust type to tokens: {}", e)),
     };
+    let tokenized_macros = tokenize_macros(rust_schema);
+    let tokenized_comment = tokenize_comment(&rust_schema.comment);
+
+    quote! {
+        #tokenized_comment
+        #tokenized_macros
+        pub struct #tokenized_name(pub #tokenized_type);
+    }
+}
+
+fn tokenize_struct(rust_schema: &SchemaAsRust) -> TokenStream {
+    let tokenized_name = tokenize_name(rust_schema);
+    let tokenized_macros = tokenize_macros(rust_schema);
+    let tokenized_comment = tokenize_comment(&rust_schema.comment);
+
+    let tokenized_fields = rust_schema.fields.iter().map(|field| {
+        let field_name = match field.name.parse::<TokenStream>() {
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn field name to tokens: {}", e)),
+        };
+        let field_type = match field.rust_type.parse::<TokenStream>() {
+            Ok(v) if field.is_optional => quote! { Option<#v> },
+            Ok(v) => v,
+            Err(e) => panic!("{}", format!("cannot turn field type to tokens: {}", e)),
+        };
+        let field_comment = tokenize_comment(&field.comment);
+        let original_name = &field.original_name;
+        let rename = match (
+            field.is_skipped,
+            field.name.trim_start_matches("r#") == original_name,
+        ) {
+            (true, _) => quote! { #[serde(skip)] },
+            (false, true) => TokenStream::new(),
+            (false, false) => quote! { #[serde(rename = #original_name)] },
+        };
+
+        quote! {
+            #field_comment
+            #rename
+            pub #field_name: #field_type
+        }
+    });
+
+    quote! {
+        #tokenized_comment
+        #tokenized_macros
+        pub struct #tokenized_name {
+            #(#tokenized_fields,)*
+        }
+    }
+}
+
+fn tokenize_name(rust_schema: &SchemaAsRust) -> TokenStream {
+    match &rust_schema.name.parse::<TokenStream>() {
+        Ok(v) => v.clone(),
+        Err(e) => panic!("{}", format!("cannot turn name to tokens: {}", e)),
+    }
+}
+
+fn tokenize_macros(rust_schema: &SchemaAsRust) -> TokenStream {
     // TODO: rewrite this as something readable, it is just chaining the macros with \n
-    let tokenized_macros = match &rust_schema
+    match &rust_schema
         .macros
         .clone()
         .into_iter()
@@ -113,18 +195,44 @@ fn tokenize_type(rust_schema: &SchemaAsRust) -> TokenStream {
     {
         Ok(v) => v.clone(),
         Err(e) => panic!("{}", format!("cannot turn macros to tokens: {}", e)),
-    };
-    let tokenized_comment = match &rust_schema.comment {
+    }
+}
+
+fn tokenize_comment(comment: &Option<String>) -> TokenStream {
+    match comment {
         None => TokenStream::new(),
         Some(c) => {
             quote! { #[doc = #c] }
         }
-    };
+    }
+}
 
-    quote! {
-        #tokenized_comment
-        #tokenized_macros
-        struct #tokenized_name(#tokenized_type);
+/// Returns the name of the field associated with a property, in snake case.
+/// Properties named like a keyword, like `type`, are turned into raw identifiers,
+/// and the other names are turned into identifiers by [identifier], like `@type` into `_type`.
+pub(crate) fn field_name(property_name: &str) -> String {
+    let name = identifier(&property_name.to_case(Case::Snake));
+    match syn::parse_str::<syn::Ident>(&name) {
+        Ok(_) => name,
+        Err(_) if matches!(name.as_str(), "self" | "super" | "crate") => format!("{name}_"),
+        Err(_) => format!("r#{name}"),
+    }
+}
+
+/// Turns a name into an identifier: the characters that cannot appear in an identifier are replaced with `_`,
+/// and `_` is put in front of a leading digit, so that `1st` becomes `_1st`.
+/// A name without any character to keep, like `@`, gives `_` which is still not an identifier.
+pub(crate) fn identifier(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    match name.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{name}"),
+        false => name,
     }
 }
 
@@ -137,7 +245,7 @@ mod tests {
     fn test_parsed_schema_display() {
         let tokenized_schema = "DateTime".to_string();
 
-        let mut imports = HashMap::new();
+        let mut imports = BTreeMap::new();
         imports.insert(
             "chrono::DateTime".to_string(),
             " use chrono::DateTime;".to_string(),
@@ -146,10 +254,12 @@ mod tests {
         let parsed_schema = SchemaAsRust {
             name: "time".to_string(),
             rust_type: tokenized_schema,
-            macros: HashSet::from(["#[derive(Serialize, Deserialize, Debug)]".to_string()]),
+            macros: BTreeSet::from(["#[derive(Serialize, Deserialize, Debug)]".to_string()]),
             imports,
             comment: Some("My favourite comment".to_string()),
             is_optional: false,
+            fields: vec![],
+            constraints: ConstraintsAsRust::default(),
             current_type: CurrentType::Type,
         };
 
@@ -157,4 +267,15 @@ mod tests {
 
         assert_snapshot!(formatted_parsed_schema);
     }
+
+    #[rstest::rstest]
+    #[case("petId", "pet_id")]
+    #[case("type", "r#type")]
+    #[case("self", "self_")]
+    #[case("@type", "_type")]
+    #[case("1st", "_1_st")]
+    #[case("page[size]", "page_size_")]
+    fn test_field_name(#[case] property_name: &str, #[case] expected: &str) {
+        assert_eq!(field_name(property_name), expected);
+    }
 }
@@ -0,0 +1,48 @@
+use crate::models::parameters::ParameterLocation;
+use crate::models::schema::field_name;
+
+/// SecuritySchemeKind represents how the credentials of a security scheme are sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecuritySchemeKind {
+    /// An API key sent as a header, a query parameter or a cookie named `name`.
+    ApiKey {
+        name: String,
+        location: ParameterLocation,
+    },
+    /// A token sent in the `Authorization` header with the `Bearer` scheme.
+    /// OAuth2 and OpenID Connect schemes without a client credentials flow use it too,
+    /// since their tokens are obtained outside the client.
+    Bearer,
+    /// A username and a password sent in the `Authorization` header with the `Basic` scheme.
+    Basic,
+    /// An OAuth2 token obtained by the client from `token_url` with its client id and secret,
+    /// and sent in the `Authorization` header with the `Bearer` scheme.
+    ClientCredentials { token_url: String },
+}
+
+/// SecuritySchemeAsRust represents a security scheme declared in the components of the spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecuritySchemeAsRust {
+    /// The name of the scheme in the spec, like `api_key`
+    pub name: String,
+    /// The optional description of the scheme
+    pub comment: Option<String>,
+    /// How the credentials of the scheme are sent
+    pub kind: SecuritySchemeKind,
+}
+
+impl SecuritySchemeAsRust {
+    /// Returns the name of the field that holds the credentials of the scheme, in snake case.
+    pub fn field_name(&self) -> String {
+        field_name(&self.name)
+    }
+}
+
+/// SecurityRequirementAsRust represents one of the alternative ways to authenticate an operation,
+/// that is satisfied when the credentials of all its schemes are sent.
+/// A requirement without schemes makes the authentication optional.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SecurityRequirementAsRust {
+    /// The names of the schemes in the spec, together with the scopes required for OAuth2 schemes
+    pub schemes: Vec<(String, Vec<String>)>,
+}
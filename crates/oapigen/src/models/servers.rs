@@ -0,0 +1,52 @@
+use crate::models::schema::field_name;
+use convert_case::{Case, Casing};
+
+/// ServerVariableAsRust represents a variable of the URL template of a server, like `{region}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerVariableAsRust {
+    /// The name of the variable in the URL template
+    pub name: String,
+    /// The optional description of the variable
+    pub comment: Option<String>,
+    /// The value used when no other is selected
+    pub default: String,
+    /// The values allowed for the variable, together with the names of their variants.
+    /// Empty if the variable accepts any string
+    pub values: Vec<(String, String)>,
+}
+
+impl ServerVariableAsRust {
+    /// Returns the name of the field that holds the value of the variable, in snake case.
+    pub fn field_name(&self) -> String {
+        field_name(&self.name)
+    }
+}
+
+/// ServerAsRust represents one of the servers of the spec, that the client can use as its base URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerAsRust {
+    /// The name of the variant of the server, like `Production`
+    pub name: String,
+    /// The optional description of the server
+    pub comment: Option<String>,
+    /// The URL of the server, that can contain variables like `https://{region}.example.com`
+    pub url: String,
+    /// The variables of the URL
+    pub variables: Vec<ServerVariableAsRust>,
+}
+
+impl ServerAsRust {
+    /// Returns the name of the struct that holds the variables of the server.
+    pub fn variables_struct_name(&self) -> String {
+        format!("{}Server", self.name)
+    }
+
+    /// Returns the name of the enum of the values allowed for a variable of the server.
+    pub fn variable_enum_name(&self, variable: &ServerVariableAsRust) -> String {
+        format!(
+            "{}{}",
+            self.variables_struct_name(),
+            variable.name.to_case(Case::UpperCamel)
+        )
+    }
+}
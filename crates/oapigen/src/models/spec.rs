@@ -0,0 +1,17 @@
+use crate::models::operation::OperationAsRust;
+use crate::models::schema::SchemaAsRust;
+use crate::models::security::SecuritySchemeAsRust;
+use crate::models::servers::ServerAsRust;
+
+/// SpecAsRust collects everything that was inferred from an OpenAPI specification.
+#[derive(Debug, Default)]
+pub struct SpecAsRust {
+    /// The types defined in the components of the specification
+    pub schemas: Vec<SchemaAsRust>,
+    /// The operations defined in the paths of the specification
+    pub operations: Vec<OperationAsRust>,
+    /// The security schemes defined in the components of the specification
+    pub security_schemes: Vec<SecuritySchemeAsRust>,
+    /// The servers listed in the specification
+    pub servers: Vec<ServerAsRust>,
+}
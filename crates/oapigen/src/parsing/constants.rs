@@ -2,16 +2,25 @@ use oas3::spec::ObjectSchema;
 use serde_json::Value;
 
 use crate::models;
+use crate::parsing::errors::ParsingError;
+use crate::parsing::errors::ParsingError::UnsupportedSchema;
 
-fn parse_constants(schema_name: String, schema: ObjectSchema) -> models::SchemaAsRust {
-    match schema.const_value.unwrap() {
-        Value::Null => todo!(),
-        Value::Bool(_) => todo!(),
-        Value::Number(_) => todo!(),
-        Value::String(_) => todo!(),
-        Value::Array(_) => todo!(),
-        Value::Object(_) => todo!(),
-    }
+fn parse_constants(
+    schema_name: String,
+    schema: ObjectSchema,
+) -> Result<models::SchemaAsRust, ParsingError> {
+    let kind = match schema.const_value {
+        None => "missing",
+        Some(Value::Null) => "null",
+        Some(Value::Bool(_)) => "boolean",
+        Some(Value::Number(_)) => "number",
+        Some(Value::String(_)) => "string",
+        Some(Value::Array(_)) => "array",
+        Some(Value::Object(_)) => "object",
+    };
+    Err(UnsupportedSchema(format!(
+        "the {kind} constant of {schema_name} cannot be generated yet"
+    )))
 }
 
 fn parse_value(original_value: Value) {
@@ -8,6 +8,20 @@ pub enum ParsingError {
     CannotParseString(#[from] LexError),
     #[error("could generate union type, reason {0}")]
     CannotGenerateUnionType(String),
+    #[error("the schema is not supported, reason {0}")]
+    UnsupportedSchema(String),
+    #[error("the path {0} cannot be served, reason {1}")]
+    UnsupportedPath(String, String),
+    #[error("the security scheme {0} required by {1} cannot be generated")]
+    UnsupportedSecurityScheme(String, String),
+    #[error("the pagination of {0} cannot be generated, reason {1}")]
+    UnsupportedPagination(String, String),
+    #[error("the pattern of {0} is not a valid regular expression, reason {1}")]
+    InvalidPattern(String, String),
+    #[error("{0} cannot be turned into a Rust identifier")]
+    InvalidIdentifier(String),
+    #[error("{0} is not a valid status code for a response")]
+    InvalidStatusCode(String),
     #[error("could not resolve the reference with the passed spec")]
     ReferenceNotFound(#[from] spec::RefError),
 }
@@ -1,22 +1,36 @@
 use crate::models;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 
 const NAME_IMPORTS_SERDE: &str = "SERDE";
 
-fn get_default_macros() -> (HashSet<String>, models::Imports) {
-    let mut imports = HashMap::new();
+fn get_default_macros() -> (BTreeSet<String>, models::Imports) {
+    let mut imports = BTreeMap::new();
     imports.insert(
         NAME_IMPORTS_SERDE.to_string(),
         "use serde::{Deserialize, Serialize};".to_string(),
     );
 
-    let default_macros = HashSet::from(["#[derive(Debug, Deserialize, Serialize)]".to_string()]);
+    let default_macros = BTreeSet::from(["#[derive(Debug, Deserialize, Serialize)]".to_string()]);
 
     (default_macros, imports)
 }
 
 /// Returns the macros that are associated with the current type.
-/// By default, we add derive(Debug, Deserialize, Serialize) to allow for reasonable behaviour.
-pub(crate) fn get_macros() -> (HashSet<String>, models::Imports) {
-    get_default_macros()
+/// By default, we add derive(Debug, Deserialize, Serialize) to allow for reasonable behaviour,
+/// followed by the derives of the config that are not already there.
+pub(crate) fn get_macros(config: &models::Config) -> (BTreeSet<String>, models::Imports) {
+    let (default_macros, imports) = get_default_macros();
+    if config.derives.is_empty() {
+        return (default_macros, imports);
+    }
+
+    let mut derives = vec!["Debug", "Deserialize", "Serialize"];
+    for derive in &config.derives {
+        if !derives.contains(&derive.as_str()) {
+            derives.push(derive);
+        }
+    }
+    let macros = BTreeSet::from([format!("#[derive({})]", derives.join(", "))]);
+
+    (macros, imports)
 }
@@ -2,6 +2,10 @@ mod constants;
 pub mod errors;
 mod fixtures;
 mod macros;
-mod routes;
+mod multipart;
+mod pagination;
+pub(crate) mod routes;
+mod security;
+mod servers;
 pub mod specs;
 mod typeset;
@@ -0,0 +1,144 @@
+use crate::models;
+use crate::parsing::errors::ParsingError;
+use crate::parsing::errors::ParsingError::UnsupportedSchema;
+use crate::parsing::routes::{headers_to_rust, is_json, type_name};
+use oas3::spec::{Encoding, ObjectOrReference, ObjectSchema, Schema, SchemaType};
+use std::collections::BTreeMap;
+
+const FILE_TYPE: &str = "FilePart";
+
+/// Converts the properties of the schema of a multipart body to parts, following their encoding.
+/// The fields of the body that are sent as files are retyped to [FILE_TYPE], since binary values
+/// cannot be represented by strings. The headers declared by the encoding of a part are held by
+/// a field of the body, named after the part, that is not serialized.
+pub(crate) fn parts_to_rust(
+    config: &models::Config,
+    spec: &oas3::Spec,
+    schema: &ObjectSchema,
+    encoding: &BTreeMap<String, Encoding>,
+    parsed: &mut models::SchemaAsRust,
+) -> Result<Vec<models::PartAsRust>, ParsingError> {
+    if !matches!(parsed.current_type, models::CurrentType::Struct) {
+        return Err(UnsupportedSchema(format!(
+            "the multipart body {} must be an object",
+            parsed.name
+        )));
+    }
+
+    let mut parts = Vec::with_capacity(parsed.fields.len());
+    let mut headers_fields = Vec::new();
+    for field in &mut parsed.fields {
+        let property = match schema.properties.get(&field.original_name) {
+            Some(ObjectOrReference::Object(property)) => Some(property),
+            Some(ObjectOrReference::Ref { .. }) => None,
+            None => continue,
+        };
+        let part_encoding = encoding.get(&field.original_name);
+        let content_type = part_encoding.and_then(|e| e.content_type.as_deref());
+
+        // the schemas of the components, and the arrays of them, are sent as JSON like the objects
+        let kind = if let Some(property) = property.filter(|p| !is_reference_array(p)) {
+            property_kind(property, field, content_type)
+        } else {
+            models::PartKind::Json
+        };
+
+        let content_types = content_type
+            .map(|c| {
+                c.split(',')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let owner = format!("the part {} of {}", field.original_name, parsed.name);
+        // the spec says that a Content-Type header must be ignored in favour of contentType
+        let headers = match part_encoding {
+            Some(e) => headers_to_rust(config, spec, &owner, &e.headers)?,
+            None => Vec::new(),
+        };
+        let headers_struct_name =
+            format!("{}{}Headers", parsed.name, type_name(&field.original_name));
+
+        let part = models::PartAsRust {
+            name: field.original_name.clone(),
+            field_name: field.name.clone(),
+            kind,
+            content_types,
+            headers,
+            headers_struct_name,
+            is_optional: field.is_optional,
+        };
+        if !part.headers.is_empty() {
+            for header in &part.headers {
+                parsed.imports.extend(header.imports.clone());
+            }
+            headers_fields.push(models::FieldAsRust {
+                name: part.headers_field_name(),
+                original_name: part.headers_field_name(),
+                rust_type: part.headers_struct_name.clone(),
+                comment: Some(format!("The headers sent with the part {}", part.name)),
+                is_optional: true,
+                is_skipped: true,
+                constraints: models::ConstraintsAsRust::default(),
+            });
+        }
+        parts.push(part);
+    }
+    parsed.fields.extend(headers_fields);
+
+    Ok(parts)
+}
+
+/// Returns how a property that is not a reference is turned into parts, retyping the files.
+fn property_kind(
+    property: &ObjectSchema,
+    field: &mut models::FieldAsRust,
+    content_type: Option<&str>,
+) -> models::PartKind {
+    if is_binary(property) {
+        field.rust_type = FILE_TYPE.to_string();
+        models::PartKind::File
+    } else if items(property).is_some_and(is_binary) {
+        field.rust_type = format!("Vec<{FILE_TYPE}>");
+        models::PartKind::Files
+    } else if content_type.is_some_and(is_json) {
+        models::PartKind::Json
+    } else if items(property).is_some() {
+        models::PartKind::Texts
+    } else {
+        models::PartKind::Text
+    }
+}
+
+/// Returns true if the schema is an array of references.
+fn is_reference_array(schema: &ObjectSchema) -> bool {
+    matches!(
+        schema.items.as_deref(),
+        Some(Schema::Object(items)) if matches!(items.as_ref(), ObjectOrReference::Ref { .. })
+    )
+}
+
+fn is_binary(schema: &ObjectSchema) -> bool {
+    let is_string = schema
+        .schema_type
+        .as_ref()
+        .is_some_and(|t| t.contains(SchemaType::String));
+    is_string && schema.format.as_deref() == Some("binary")
+}
+
+/// Returns the schema of the items of an array, if the schema is an array.
+fn items(schema: &ObjectSchema) -> Option<&ObjectSchema> {
+    let is_array = schema
+        .schema_type
+        .as_ref()
+        .is_some_and(|t| t.contains(SchemaType::Array));
+    match schema.items.as_deref() {
+        Some(Schema::Object(items)) if is_array => match items.as_ref() {
+            ObjectOrReference::Object(items) => Some(items),
+            ObjectOrReference::Ref { .. } => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,277 @@
+use crate::models;
+use crate::models::{
+    PaginationAsRust, PaginationKind, PaginationParameterAsRust, ParameterLocation, PayloadKind,
+    StatusCode,
+};
+use crate::parsing::errors::ParsingError;
+use crate::parsing::errors::ParsingError::UnsupportedPagination;
+use oas3::spec;
+
+/// Returns how the pages of an operation are requested, taken from the config or otherwise
+/// from the `x-pagination` extension of the operation.
+///
+/// The items of a page are an array property of the body of the first successful response,
+/// that has to be a JSON object, and the parameters that select a page have to be in the query.
+pub(crate) fn pagination_to_rust(
+    config: &models::Config,
+    operation_name: &str,
+    operation: &spec::Operation,
+    operation_as_rust: &models::OperationAsRust,
+) -> Result<Option<PaginationAsRust>, ParsingError> {
+    let extension = match operation.extensions.get("pagination") {
+        Some(extension) => Some(extension_to_pagination(operation_name, extension)?),
+        None => None,
+    };
+    let configured = operation
+        .operation_id
+        .as_ref()
+        .and_then(|id| config.pagination.get(id));
+    let Some(pagination) = configured.or(extension.as_ref()) else {
+        return Ok(None);
+    };
+
+    let unsupported = |reason: String| UnsupportedPagination(operation_name.to_string(), reason);
+    if operation_as_rust.request_body.is_some() {
+        return Err(unsupported(
+            "the paginated operations cannot have a request body".to_string(),
+        ));
+    }
+
+    let Some((status, page)) = operation_as_rust
+        .responses
+        .iter()
+        .find_map(|r| match r.status {
+            StatusCode::Exact(200..=299) => r.body.as_ref().map(|b| (r.status, b)),
+            _ => None,
+        })
+    else {
+        return Err(unsupported(
+            "none of the successful responses has a body".to_string(),
+        ));
+    };
+    let schema = match page.content.as_slice() {
+        [content]
+            if content.kind == PayloadKind::Json
+                && matches!(content.schema.current_type, models::CurrentType::Struct) =>
+        {
+            &content.schema
+        }
+        _ => {
+            return Err(unsupported(format!(
+                "the body of the page {} is not a JSON object",
+                page.name
+            )));
+        }
+    };
+    let field = |property: &str| {
+        schema
+            .fields
+            .iter()
+            .find(|f| f.original_name == property)
+            .ok_or_else(|| unsupported(format!("the page has no property {property}")))
+    };
+    let parameter = |name: &str| {
+        operation_as_rust
+            .parameters
+            .iter()
+            .find(|p| p.name == name && p.location == ParameterLocation::Query && !p.is_array)
+            .map(|p| PaginationParameterAsRust {
+                field_name: p.field_name.clone(),
+                rust_type: p.rust_type.clone(),
+                is_required: p.is_required,
+            })
+            .ok_or_else(|| unsupported(format!("there is no query parameter {name}")))
+    };
+
+    let (kind, items) = match pagination {
+        models::Pagination::Cursor {
+            parameter: parameter_name,
+            next_cursor,
+            items,
+        } => {
+            let parameter = parameter(parameter_name)?;
+            let next_cursor = field(next_cursor)?;
+            if !next_cursor.is_optional {
+                return Err(unsupported(format!(
+                    "the property {} is required, but it has to be missing on the last page",
+                    next_cursor.original_name
+                )));
+            }
+            if next_cursor.rust_type != parameter.rust_type {
+                return Err(unsupported(format!(
+                    "the property {} and the parameter {parameter_name} have different types",
+                    next_cursor.original_name
+                )));
+            }
+            let kind = PaginationKind::Cursor {
+                parameter,
+                next_cursor: next_cursor.name.clone(),
+            };
+            (kind, items)
+        }
+        models::Pagination::Link { items } => (PaginationKind::Link, items),
+        models::Pagination::Offset {
+            offset,
+            limit,
+            items,
+        } => {
+            let kind = PaginationKind::Offset {
+                offset: parameter(offset)?,
+                limit: limit.as_deref().map(parameter).transpose()?,
+            };
+            (kind, items)
+        }
+    };
+
+    let items = field(items)?;
+    let Some(item_type) = items
+        .rust_type
+        .strip_prefix("Vec<")
+        .and_then(|t| t.strip_suffix('>'))
+    else {
+        return Err(unsupported(format!(
+            "the property {} is not an array",
+            items.original_name
+        )));
+    };
+
+    Ok(Some(PaginationAsRust {
+        kind,
+        status,
+        items: items.name.clone(),
+        is_items_optional: items.is_optional,
+        item_type: item_type.to_string(),
+    }))
+}
+
+/// Reads the `x-pagination` extension of an operation, like
+/// `{kind: offset, offset: offset, limit: limit, items: data}`.
+fn extension_to_pagination(
+    operation_name: &str,
+    extension: &serde_json::Value,
+) -> Result<models::Pagination, ParsingError> {
+    let property = |name: &str| {
+        extension
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .map(ToString::to_string)
+    };
+    let required = |name: &str| {
+        property(name).ok_or_else(|| {
+            UnsupportedPagination(
+                operation_name.to_string(),
+                format!("x-pagination has no {name}"),
+            )
+        })
+    };
+
+    match property("kind").as_deref() {
+        Some("cursor") => Ok(models::Pagination::Cursor {
+            parameter: required("parameter")?,
+            next_cursor: required("nextCursor")?,
+            items: required("items")?,
+        }),
+        Some("link") => Ok(models::Pagination::Link {
+            items: required("items")?,
+        }),
+        Some("offset") => Ok(models::Pagination::Offset {
+            offset: required("offset")?,
+            limit: property("limit"),
+            items: required("items")?,
+        }),
+        kind => Err(UnsupportedPagination(
+            operation_name.to_string(),
+            format!("{kind:?} is not a kind of x-pagination, use cursor, link or offset"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models;
+    use crate::parsing::specs::parse_specs;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("cursor", "listPets")]
+    #[case("link", "listOwners")]
+    #[case("offset", "listVisits")]
+    #[case("extension", "listToys")]
+    fn test_pagination(#[case] name: &str, #[case] operation_id: &str) {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_suffix(name);
+
+        let config = models::Config {
+            pagination: [
+                (
+                    "listPets".to_string(),
+                    models::Pagination::Cursor {
+                        parameter: "cursor".to_string(),
+                        next_cursor: "next_cursor".to_string(),
+                        items: "data".to_string(),
+                    },
+                ),
+                (
+                    "listOwners".to_string(),
+                    models::Pagination::Link {
+                        items: "owners".to_string(),
+                    },
+                ),
+                (
+                    "listVisits".to_string(),
+                    models::Pagination::Offset {
+                        offset: "offset".to_string(),
+                        limit: Some("limit".to_string()),
+                        items: "visits".to_string(),
+                    },
+                ),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let yaml = std::fs::read_to_string("fixtures/pagination.yaml").unwrap();
+        let spec = oas3::from_yaml(yaml).unwrap();
+
+        let got = parse_specs(&config, spec).unwrap();
+        let operation = got
+            .operations
+            .iter()
+            .find(|o| o.method_name() == models::field_name(operation_id))
+            .unwrap();
+
+        settings.bind(|| {
+            insta::assert_debug_snapshot!(operation.pagination);
+        });
+    }
+
+    #[rstest]
+    #[case("body", "createPet")]
+    #[case("missing cursor", "listBrokenPets")]
+    fn test_unsupported_pagination(#[case] name: &str, #[case] operation_id: &str) {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_suffix(name);
+
+        let config = models::Config {
+            pagination: [(
+                operation_id.to_string(),
+                models::Pagination::Cursor {
+                    parameter: "cursor".to_string(),
+                    next_cursor: "next_cursor".to_string(),
+                    items: "data".to_string(),
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+
+        let yaml = std::fs::read_to_string("fixtures/pagination.yaml").unwrap();
+        let spec = oas3::from_yaml(yaml).unwrap();
+
+        let got = parse_specs(&config, spec).unwrap_err();
+
+        settings.bind(|| {
+            insta::assert_snapshot!(got.to_string());
+        });
+    }
+}
@@ -1,24 +1,55 @@
 use crate::models;
+use crate::models::field_name;
 use crate::models::schema;
 use crate::parsing::errors::ParsingError;
-use crate::parsing::typeset::schema_to_rust;
+use crate::parsing::errors::ParsingError::{
+    InvalidIdentifier, InvalidStatusCode, UnsupportedPath, UnsupportedSchema,
+};
+use crate::parsing::multipart::parts_to_rust;
+use crate::parsing::pagination::pagination_to_rust;
+use crate::parsing::security::security_requirements_to_rust;
+use crate::parsing::typeset::{
+    property_to_rust_type, schema_constraints, schema_to_rust, schema_to_rust_type,
+};
 use convert_case::{Case, Casing};
 use oas3::spec;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::option::Option;
 
 pub(crate) fn parse_routes(
     config: &models::Config,
     spec: &oas3::Spec,
-) -> Result<Vec<schema::SchemaAsRust>, ParsingError> {
+    security_schemes: &[models::SecuritySchemeAsRust],
+) -> Result<Vec<models::OperationAsRust>, ParsingError> {
     let mut output = Vec::new();
 
     let paths = &spec.paths;
 
     if let Some(paths) = paths {
         for (route_name, path) in paths {
-            if path.get.is_some() {
-                let parsed =
-                    parse_operation(config, spec, &"Get".to_string(), route_name, &path.get)?;
+            if config.targets.server {
+                check_server_route(route_name)?;
+            }
+            let operations = [
+                ("Get", &path.get),
+                ("Put", &path.put),
+                ("Post", &path.post),
+                ("Delete", &path.delete),
+                ("Options", &path.options),
+                ("Head", &path.head),
+                ("Patch", &path.patch),
+                ("Trace", &path.trace),
+            ];
+            for (method_name, operation) in operations {
+                let parsed = parse_operation(
+                    config,
+                    spec,
+                    security_schemes,
+                    &method_name.to_string(),
+                    route_name,
+                    &path.parameters,
+                    operation,
+                )?;
                 output.extend(parsed);
             }
         }
@@ -30,82 +61,622 @@ pub(crate) fn parse_routes(
 fn parse_operation(
     config: &models::Config,
     spec: &oas3::Spec,
+    security_schemes: &[models::SecuritySchemeAsRust],
     method_name: &String,
-    route_name: &String,
+    route_name: &str,
+    path_parameters: &[spec::ObjectOrReference<spec::Parameter>],
     operation: &Option<spec::Operation>,
-) -> Result<Vec<schema::SchemaAsRust>, ParsingError> {
-    let mut output = Vec::new();
+) -> Result<Option<models::OperationAsRust>, ParsingError> {
     if let Some(operation) = operation {
         let operation_name = match &operation.operation_id {
-            Some(operation_id) => operation_id.to_case(Case::UpperCamel),
-            None => [method_name, &route_name.to_case(Case::UpperCamel)]
-                .map(String::as_str)
-                .concat(),
+            Some(operation_id) => valid_identifier(
+                type_name(operation_id),
+                &format!("the operation id {operation_id}"),
+            )?,
+            None => format!("{method_name}{}", to_upper_camel_identifier(route_name)),
+        };
+        let filtered_name = operation.operation_id.as_ref().unwrap_or(&operation_name);
+        if !config.filters.operations.allows(filtered_name) {
+            return Ok(None);
+        }
+
+        let parameters = parameters_to_rust(
+            config,
+            spec,
+            &operation_name,
+            path_parameters,
+            &operation.parameters,
+        )?;
+
+        let request_body = match &operation.request_body {
+            Some(request_body) => {
+                let resolved_request_body = request_body.resolve(spec)?;
+                let schema_inputs = models::SchemaInputs {
+                    schema_name: &format!("{operation_name}RequestBody"),
+                };
+                content_to_rust(
+                    config,
+                    spec,
+                    &schema_inputs,
+                    &resolved_request_body.content,
+                    false,
+                )?
+                .map(|body| models::RequestBodyAsRust {
+                    body,
+                    description: resolved_request_body.description,
+                    is_required: resolved_request_body.required.unwrap_or(false),
+                })
+            }
+            None => None,
         };
 
+        let mut parsed_responses = Vec::new();
         let responses = &operation.responses;
         if let Some(responses) = responses {
             for (response_name, response) in responses {
-                let schema_inputs = models::OperationSchemaInputs {
-                    operation_name: &operation_name,
-                    response_name: &response_name,
+                let status = parse_status_code(response_name)?;
+                let schema_inputs = models::SchemaInputs {
+                    schema_name: &format!("{operation_name}Response{}", status.name()),
                 };
 
                 let resolved_response = response.resolve(spec)?;
-                let parsed = respose_to_rust(config, spec, &schema_inputs, &resolved_response)?;
-                output.extend(parsed);
+                let body = content_to_rust(
+                    config,
+                    spec,
+                    &schema_inputs,
+                    &resolved_response.content,
+                    true,
+                )?;
+                let headers = headers_to_rust(
+                    config,
+                    spec,
+                    schema_inputs.schema_name,
+                    &resolved_response.headers,
+                )?;
+                parsed_responses.push(models::ResponseAsRust {
+                    status,
+                    description: resolved_response.description,
+                    body,
+                    headers,
+                });
             }
         }
+        // exact status codes have to be matched before ranges, and ranges before the default
+        parsed_responses.sort_by_key(|r| r.status);
+
+        let security = security_requirements_to_rust(
+            config,
+            spec,
+            security_schemes,
+            &operation_name,
+            operation,
+        )?;
+
+        let mut parsed_operation = models::OperationAsRust {
+            name: operation_name,
+            method: method_name.to_uppercase(),
+            path: route_name.to_string(),
+            description: operation
+                .description
+                .clone()
+                .or_else(|| operation.summary.clone()),
+            parameters,
+            request_body,
+            responses: parsed_responses,
+            security,
+            pagination: None,
+            tags: operation.tags.clone(),
+        };
+        parsed_operation.pagination =
+            pagination_to_rust(config, &parsed_operation.name, operation, &parsed_operation)?;
+
+        return Ok(Some(parsed_operation));
     }
-    Ok(output)
+    Ok(None)
 }
 
-fn respose_to_rust(
+/// Checks that every template expression of a path fills a whole segment, like `/pets/{petId}`,
+/// since the routers of the servers cannot match a segment that mixes text and templates.
+fn check_server_route(route_name: &str) -> Result<(), ParsingError> {
+    for segment in route_name.split('/') {
+        if !segment.contains(['{', '}']) {
+            continue;
+        }
+        let is_template = segment.starts_with('{')
+            && segment.ends_with('}')
+            && segment.matches('{').count() == 1
+            && segment.matches('}').count() == 1;
+        if !is_template {
+            return Err(UnsupportedPath(
+                route_name.to_string(),
+                format!("the segment {segment} is not a single template expression"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the key of a response, that can be a status code like `200`,
+/// a range of status codes like `2XX` or `default`.
+pub(crate) fn parse_status_code(response_name: &str) -> Result<models::StatusCode, ParsingError> {
+    if response_name == "default" {
+        return Ok(models::StatusCode::Default);
+    }
+
+    let invalid = || InvalidStatusCode(response_name.to_string());
+    match response_name.as_bytes() {
+        [class @ b'1'..=b'5', b'X' | b'x', b'X' | b'x'] => {
+            Ok(models::StatusCode::Range(u16::from(class - b'0')))
+        }
+        [b'1'..=b'5', _, _] => response_name
+            .parse::<u16>()
+            .map(models::StatusCode::Exact)
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Converts the content of a request or response body to rust.
+/// Media types with identical schemas share the same type, while media types with different schemas
+/// get a type each, named after the body and suffixed with the media type.
+/// Only the bodies of the responses are streamed, see [payload_kind].
+fn content_to_rust(
     config: &models::Config,
     spec: &oas3::Spec,
-    schema_inputs: &models::OperationSchemaInputs,
-    response: &spec::Response,
-) -> Result<Vec<schema::SchemaAsRust>, ParsingError> {
-    let mut v = Vec::with_capacity(response.content.len());
-    for (mediatype_name, mediatype) in &response.content {
-        let parsed = mediatype_to_rust(config, spec, schema_inputs, mediatype)?;
-        match parsed {
-            Some(tokenized_schema) => v.push(tokenized_schema),
-            None => {}
+    schema_inputs: &models::SchemaInputs,
+    content: &BTreeMap<String, spec::MediaType>,
+    is_response: bool,
+) -> Result<Option<models::BodyAsRust>, ParsingError> {
+    let mut parsed_content: Vec<models::ContentAsRust> = Vec::with_capacity(content.len());
+    for (mediatype_name, mediatype) in content {
+        let parsed = mediatype_to_rust(
+            config,
+            spec,
+            schema_inputs,
+            mediatype_name,
+            mediatype,
+            is_response,
+        )?;
+        let Some((parsed, kind)) = parsed else {
+            continue;
+        };
+
+        // media types are grouped only if they are also encoded in the same way
+        match parsed_content
+            .iter_mut()
+            .find(|c| c.schema == parsed && c.kind == kind)
+        {
+            Some(existing) => existing.media_types.push(mediatype_name.clone()),
+            None => parsed_content.push(models::ContentAsRust {
+                media_types: vec![mediatype_name.clone()],
+                kind,
+                schema: parsed,
+            }),
+        }
+    }
+
+    if parsed_content.is_empty() {
+        return Ok(None);
+    }
+
+    if parsed_content.len() > 1 {
+        // the subtype is enough to tell the media types apart, unless two of them share it
+        let mut suffixes: Vec<_> = parsed_content
+            .iter()
+            .map(|c| media_type_suffix(&c.media_types[0], false))
+            .collect();
+        if suffixes.iter().collect::<HashSet<_>>().len() < suffixes.len() {
+            suffixes = parsed_content
+                .iter()
+                .map(|c| media_type_suffix(&c.media_types[0], true))
+                .collect();
+        }
+
+        for (c, suffix) in parsed_content.iter_mut().zip(suffixes) {
+            let item = match c.kind.is_streaming() {
+                true => "Item",
+                false => "",
+            };
+            c.schema.name = format!("{}{}{}", schema_inputs.schema_name, suffix, item);
         }
     }
 
-    Ok(v)
+    Ok(Some(models::BodyAsRust {
+        name: schema_inputs.schema_name.clone(),
+        content: parsed_content,
+    }))
+}
+
+/// Returns the suffix used to name the type associated with a media type,
+/// for example `Json` for `application/json` or, if `with_type` is true, `ApplicationJson`.
+fn media_type_suffix(media_type: &str, with_type: bool) -> String {
+    let essence = media_type_essence(media_type);
+    let name = match essence.split_once('/') {
+        Some((_, subtype)) if !with_type => subtype,
+        _ => &essence,
+    };
+
+    let suffix = to_upper_camel_identifier(name);
+    match suffix.is_empty() {
+        true => "Any".to_string(),
+        false => suffix,
+    }
+}
+
+/// Turns a string like `/pets/{petId}` into a valid identifier in UpperCamel case, like `PetsPetId`,
+/// dropping all the characters that cannot be part of an identifier.
+pub(crate) fn to_upper_camel_identifier(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_case(Case::UpperCamel))
+        .collect()
+}
+
+/// Turns a name into the name of a type in UpperCamel case, like `2Pet` into `_2Pet`,
+/// see [to_upper_camel_identifier] and [models::identifier].
+pub(crate) fn type_name(name: &str) -> String {
+    models::identifier(&to_upper_camel_identifier(name))
+}
+
+/// Returns the identifier made of the name of `source`, or an error if it is not a valid identifier,
+/// like the empty one made of a name without letters or digits.
+pub(crate) fn valid_identifier(identifier: String, source: &str) -> Result<String, ParsingError> {
+    match is_identifier(&identifier) {
+        true => Ok(identifier),
+        false => Err(InvalidIdentifier(source.to_string())),
+    }
+}
+
+/// Returns true if the name is an identifier, a raw one like `r#type` included.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    syn::parse_str::<syn::Ident>(name).is_ok()
+}
+
+/// Converts the parameters of an operation to rust. The parameters declared by the operation
+/// override the ones declared by its path that have the same name and location.
+fn parameters_to_rust(
+    config: &models::Config,
+    spec: &oas3::Spec,
+    operation_name: &str,
+    path_parameters: &[spec::ObjectOrReference<spec::Parameter>],
+    operation_parameters: &[spec::ObjectOrReference<spec::Parameter>],
+) -> Result<Vec<models::ParameterAsRust>, ParsingError> {
+    let mut resolved_parameters: Vec<spec::Parameter> = Vec::new();
+    for parameter in path_parameters.iter().chain(operation_parameters) {
+        let parameter = parameter.resolve(spec)?;
+        resolved_parameters
+            .retain(|p| p.name != parameter.name || p.location != parameter.location);
+        resolved_parameters.push(parameter);
+    }
+
+    let mut parameters = Vec::with_capacity(resolved_parameters.len());
+    for parameter in resolved_parameters {
+        let location = match parameter.location {
+            spec::ParameterIn::Path => models::ParameterLocation::Path,
+            spec::ParameterIn::Query => models::ParameterLocation::Query,
+            spec::ParameterIn::Header => models::ParameterLocation::Header,
+            spec::ParameterIn::Cookie => models::ParameterLocation::Cookie,
+        };
+        // the spec says that these headers are set by other parts of the spec and must be ignored
+        let is_reserved_header = ["accept", "content-type", "authorization"]
+            .iter()
+            .any(|h| parameter.name.eq_ignore_ascii_case(h));
+        if location == models::ParameterLocation::Header && is_reserved_header {
+            continue;
+        }
+
+        let resolved_schema = match &parameter.schema {
+            Some(schema) => Some(schema.resolve(spec)?),
+            None => None,
+        };
+        let (rust_type, imports) = match &resolved_schema {
+            Some(schema) => property_to_rust_type(config, schema)?,
+            None => ("String".to_string(), models::Imports::new()),
+        };
+        let is_array = resolved_schema.as_ref().is_some_and(|s| {
+            s.schema_type
+                .as_ref()
+                .is_some_and(|t| t.contains(spec::SchemaType::Array))
+        });
+        // only the form style, the default one for queries and cookies, explodes arrays by default
+        let explode = parameter.explode.unwrap_or(matches!(
+            location,
+            models::ParameterLocation::Query | models::ParameterLocation::Cookie
+        ));
+
+        let constraints = match &resolved_schema {
+            Some(schema) => {
+                let owner = format!("the parameter {} of {}", parameter.name, operation_name);
+                schema_constraints(schema, &owner)?
+            }
+            None => models::ConstraintsAsRust::default(),
+        };
+
+        parameters.push(models::ParameterAsRust {
+            field_name: valid_identifier(
+                field_name(&parameter.name),
+                &format!("the parameter {} of {}", parameter.name, operation_name),
+            )?,
+            name: parameter.name,
+            location,
+            rust_type,
+            imports,
+            comment: parameter.description,
+            // path parameters are always required
+            is_required: location == models::ParameterLocation::Path
+                || parameter.required.unwrap_or(false),
+            is_array,
+            explode,
+            constraints,
+        });
+    }
+
+    Ok(parameters)
+}
+
+/// Converts the headers declared by a response, or by the encoding of a part of a multipart body,
+/// named `owner` in the errors.
+pub(crate) fn headers_to_rust(
+    config: &models::Config,
+    spec: &oas3::Spec,
+    owner: &str,
+    declared_headers: &BTreeMap<String, spec::ObjectOrReference<spec::Header>>,
+) -> Result<Vec<models::HeaderAsRust>, ParsingError> {
+    let mut headers = Vec::with_capacity(declared_headers.len());
+    for (header_name, header) in declared_headers {
+        // the spec says that a Content-Type header must be ignored
+        if header_name.eq_ignore_ascii_case("content-type") {
+            continue;
+        }
+
+        let resolved_header = header.resolve(spec)?;
+        let (rust_type, imports) = match &resolved_header.schema {
+            Some(schema) => schema_to_rust_type(config, &schema.resolve(spec)?)?,
+            None => ("String".to_string(), models::Imports::new()),
+        };
+
+        headers.push(models::HeaderAsRust {
+            name: header_name.clone(),
+            field_name: valid_identifier(
+                field_name(header_name),
+                &format!("the header {header_name} of {owner}"),
+            )?,
+            rust_type,
+            imports,
+            comment: resolved_header.description,
+            is_required: resolved_header.required.unwrap_or(false),
+        });
+    }
+
+    Ok(headers)
+}
+
+/// Returns the media type without its parameters, like `application/json` for
+/// `application/json; charset=utf-8`, in lowercase.
+fn media_type_essence(media_type: &str) -> String {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence.to_ascii_lowercase()
+}
+
+/// Returns true if the media type, like `application/vnd.api+json`, is serialized as JSON.
+pub(crate) fn is_json(media_type: &str) -> bool {
+    let essence = media_type_essence(media_type);
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+/// Returns how a body is encoded from its media type. The schema is used only to recognize
+/// the binary bodies, since bodies that are not serialized by serde ignore their schema.
+/// The streaming media types are kept as they are in the requests, since only the responses are streamed.
+pub(crate) fn payload_kind(
+    mediatype_name: &str,
+    schema: Option<&spec::ObjectSchema>,
+    is_response: bool,
+) -> models::PayloadKind {
+    let essence = media_type_essence(mediatype_name);
+    let is_binary = schema.is_some_and(|s| s.format.as_deref() == Some("binary"));
+    let is_json_lines = matches!(
+        essence.as_str(),
+        "application/x-ndjson" | "application/jsonl" | "application/x-jsonlines"
+    );
+
+    if is_json(&essence) {
+        models::PayloadKind::Json
+    } else if essence == "application/x-www-form-urlencoded" {
+        models::PayloadKind::Form
+    } else if essence.starts_with("multipart/") {
+        // the parts are filled in once the schema of the body is known
+        models::PayloadKind::Multipart(vec![])
+    } else if essence == "text/event-stream" && is_response {
+        models::PayloadKind::EventStream
+    } else if is_json_lines && is_response {
+        models::PayloadKind::JsonLines
+    } else if is_binary || essence == "application/octet-stream" {
+        models::PayloadKind::Binary
+    } else if essence.starts_with("text/") {
+        models::PayloadKind::Text
+    } else {
+        models::PayloadKind::Raw
+    }
+}
+
+/// Returns the type of a body that is not serialized by serde, that wraps the value as it is.
+fn raw_schema_to_rust(
+    schema_inputs: &models::SchemaInputs,
+    kind: &models::PayloadKind,
+    schema: Option<&spec::ObjectSchema>,
+) -> schema::SchemaAsRust {
+    let rust_type = match kind {
+        models::PayloadKind::Text => "String",
+        models::PayloadKind::Binary => "Vec<u8>",
+        _ => "RawPayload",
+    };
+
+    schema::SchemaAsRust {
+        name: schema_inputs.schema_name.clone(),
+        rust_type: rust_type.to_string(),
+        macros: BTreeSet::from(["#[derive(Debug)]".to_string()]),
+        imports: models::Imports::new(),
+        comment: schema.and_then(|s| s.description.clone()),
+        is_optional: false,
+        fields: vec![],
+        constraints: models::ConstraintsAsRust::default(),
+        current_type: models::CurrentType::Type,
+    }
 }
 
 fn mediatype_to_rust(
     config: &models::Config,
     spec: &oas3::Spec,
-    operation_schema_inputs: &models::OperationSchemaInputs,
+    schema_inputs: &models::SchemaInputs,
+    mediatype_name: &str,
     media_type: &spec::MediaType,
-) -> Result<Option<schema::SchemaAsRust>, ParsingError> {
-    let schema_name = [
-        operation_schema_inputs
-            .operation_name
-            .to_case(Case::UpperCamel),
-        "Response".to_string(),
-        operation_schema_inputs
-            .response_name
-            .to_case(Case::UpperCamel),
-    ]
-    .join("");
-
-    let schema_inputs = models::SchemaInputs {
-        schema_name: &schema_name,
+    is_response: bool,
+) -> Result<Option<(schema::SchemaAsRust, models::PayloadKind)>, ParsingError> {
+    let parsed_schema = match &media_type.schema {
+        Some(schema) => Some(schema.resolve(spec)?),
+        None => None,
+    };
+
+    let kind = payload_kind(mediatype_name, parsed_schema.as_ref(), is_response);
+    // raw bodies do not need a schema, since they are not deserialized
+    if kind.is_raw() {
+        let tokens = raw_schema_to_rust(schema_inputs, &kind, parsed_schema.as_ref());
+        return Ok(Some((tokens, kind)));
+    }
+
+    let Some(parsed_schema) = parsed_schema else {
+        return Ok(None);
+    };
+    // the schema of a streaming body is the one of its items, so the body is named after them
+    let item_name = format!("{}Item", schema_inputs.schema_name);
+    let schema_inputs = match kind.is_streaming() {
+        true => &models::SchemaInputs {
+            schema_name: &item_name,
+        },
+        false => schema_inputs,
+    };
+    let mut tokens = schema_to_rust(config, schema_inputs, parsed_schema.clone())?;
+    let kind = match kind {
+        models::PayloadKind::Form => {
+            if !matches!(tokens.current_type, models::CurrentType::Struct) {
+                return Err(UnsupportedSchema(format!(
+                    "the form body {} must be an object",
+                    tokens.name
+                )));
+            }
+            models::PayloadKind::Form
+        }
+        models::PayloadKind::Multipart(_) => {
+            let parts = parts_to_rust(
+                config,
+                spec,
+                &parsed_schema,
+                &media_type.encoding,
+                &mut tokens,
+            )?;
+            models::PayloadKind::Multipart(parts)
+        }
+        kind => kind,
     };
 
-    let schema = &media_type.schema;
-    if let Some(schema) = schema {
-        let parsed_schema = schema.resolve(spec)?;
-        let tokens = schema_to_rust(config, &schema_inputs, parsed_schema)?;
+    Ok(Some((tokens, kind)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
 
-        return Ok(Some(tokens));
+    #[rstest]
+    #[case("200", models::StatusCode::Exact(200))]
+    #[case("404", models::StatusCode::Exact(404))]
+    #[case("2XX", models::StatusCode::Range(2))]
+    #[case("5xx", models::StatusCode::Range(5))]
+    #[case("default", models::StatusCode::Default)]
+    fn test_parse_status_code(#[case] response_name: &str, #[case] expected: models::StatusCode) {
+        assert_eq!(parse_status_code(response_name).unwrap(), expected);
     }
 
-    Ok(None)
+    #[rstest]
+    #[case("application/json", false, "Json")]
+    #[case("application/vnd.api+json; charset=utf-8", false, "VndApiJson")]
+    #[case("text/plain", true, "TextPlain")]
+    #[case("*/*", false, "Any")]
+    fn test_media_type_suffix(
+        #[case] media_type: &str,
+        #[case] with_type: bool,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(media_type_suffix(media_type, with_type), expected);
+    }
+
+    #[rstest]
+    #[case("/pets/{petId}", "PetsPetId")]
+    #[case("/pets/{pet_id}/photos.json", "PetsPetIdPhotosJson")]
+    #[case("/", "")]
+    fn test_to_upper_camel_identifier(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(to_upper_camel_identifier(name), expected);
+    }
+
+    #[rstest]
+    #[case("Pet", "Pet")]
+    #[case("pet-owner", "PetOwner")]
+    #[case("2Pet", "_2Pet")]
+    #[case("@", "")]
+    fn test_type_name(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(type_name(name), expected);
+    }
+
+    #[rstest]
+    #[case("application/json", None, models::PayloadKind::Json)]
+    #[case("application/problem+json", None, models::PayloadKind::Json)]
+    #[case("application/x-www-form-urlencoded", None, models::PayloadKind::Form)]
+    #[case("text/plain; charset=utf-8", None, models::PayloadKind::Text)]
+    #[case("text/plain", Some("binary"), models::PayloadKind::Binary)]
+    #[case("application/octet-stream", None, models::PayloadKind::Binary)]
+    #[case("image/png", Some("binary"), models::PayloadKind::Binary)]
+    #[case("application/xml", None, models::PayloadKind::Raw)]
+    #[case("text/event-stream", None, models::PayloadKind::EventStream)]
+    #[case("application/x-ndjson", None, models::PayloadKind::JsonLines)]
+    fn test_payload_kind(
+        #[case] media_type: &str,
+        #[case] format: Option<&str>,
+        #[case] expected: models::PayloadKind,
+    ) {
+        let schema = spec::ObjectSchema {
+            format: format.map(ToString::to_string),
+            ..Default::default()
+        };
+        assert_eq!(payload_kind(media_type, Some(&schema), true), expected);
+    }
+
+    #[rstest]
+    #[case("text/event-stream", models::PayloadKind::Text)]
+    #[case("application/x-ndjson", models::PayloadKind::Raw)]
+    fn test_payload_kind_of_request(
+        #[case] media_type: &str,
+        #[case] expected: models::PayloadKind,
+    ) {
+        assert_eq!(payload_kind(media_type, None, false), expected);
+    }
+
+    #[rstest]
+    #[case("20")]
+    #[case("600")]
+    #[case("2X0")]
+    #[case("Default")]
+    fn test_parse_invalid_status_code(#[case] response_name: &str) {
+        assert!(parse_status_code(response_name).is_err());
+    }
+
+    #[rstest]
+    #[case("/pets", true)]
+    #[case("/pets/{petId}", true)]
+    #[case("/pets/{petId}/photos/{photoId}", true)]
+    #[case("/pets/{petId}.json", false)]
+    #[case("/pets/pet-{petId}", false)]
+    #[case("/pets/{owner}{name}", false)]
+    fn test_check_server_route(#[case] route_name: &str, #[case] expected: bool) {
+        assert_eq!(check_server_route(route_name).is_ok(), expected);
+    }
 }
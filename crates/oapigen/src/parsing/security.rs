@@ -0,0 +1,177 @@
+use crate::models;
+use crate::parsing::errors::ParsingError;
+use crate::parsing::errors::ParsingError::UnsupportedSecurityScheme;
+use oas3::spec;
+
+/// Converts the security schemes declared in the components of the spec to rust.
+/// The schemes that cannot be generated, like mutual TLS, are skipped, together with the
+/// requirements that use them, see [security_requirements_to_rust].
+pub(crate) fn security_schemes_to_rust(
+    spec: &oas3::Spec,
+) -> Result<Vec<models::SecuritySchemeAsRust>, ParsingError> {
+    let Some(components) = &spec.components else {
+        return Ok(vec![]);
+    };
+
+    let mut schemes = Vec::with_capacity(components.security_schemes.len());
+    for (scheme_name, scheme) in &components.security_schemes {
+        let (comment, kind) = match scheme.resolve(spec)? {
+            spec::SecurityScheme::ApiKey {
+                description,
+                name,
+                location,
+            } => {
+                let location = match location.as_str() {
+                    "header" => models::ParameterLocation::Header,
+                    "query" => models::ParameterLocation::Query,
+                    "cookie" => models::ParameterLocation::Cookie,
+                    _ => continue,
+                };
+                (
+                    description,
+                    models::SecuritySchemeKind::ApiKey { name, location },
+                )
+            }
+            spec::SecurityScheme::Http {
+                description,
+                scheme,
+                ..
+            } => match scheme.to_ascii_lowercase().as_str() {
+                "bearer" => (description, models::SecuritySchemeKind::Bearer),
+                "basic" => (description, models::SecuritySchemeKind::Basic),
+                _ => continue,
+            },
+            spec::SecurityScheme::OAuth2 { description, flows } => match flows.client_credentials {
+                Some(flow) => (
+                    description,
+                    models::SecuritySchemeKind::ClientCredentials {
+                        token_url: flow.token_url.to_string(),
+                    },
+                ),
+                None => (description, models::SecuritySchemeKind::Bearer),
+            },
+            spec::SecurityScheme::OpenIdConnect { description, .. } => {
+                (description, models::SecuritySchemeKind::Bearer)
+            }
+            spec::SecurityScheme::MutualTls { .. } => continue,
+        };
+
+        schemes.push(models::SecuritySchemeAsRust {
+            name: scheme_name.clone(),
+            comment,
+            kind,
+        });
+    }
+
+    Ok(schemes)
+}
+
+/// Converts the security requirements of an operation to rust, falling back to the ones of the spec
+/// when the operation declares none. Since the spec parses an empty list like a missing one,
+/// an operation cannot opt out of the requirements of the spec with `security: []`, but it can
+/// make them optional with `security: [{}]`.
+///
+/// The client drops the requirements that use a scheme that cannot be generated, since it cannot
+/// send its credentials anyway, while the server rejects them so that the operation is not served
+/// without authentication.
+pub(crate) fn security_requirements_to_rust(
+    config: &models::Config,
+    spec: &oas3::Spec,
+    schemes: &[models::SecuritySchemeAsRust],
+    operation_name: &str,
+    operation: &spec::Operation,
+) -> Result<Vec<models::SecurityRequirementAsRust>, ParsingError> {
+    let requirements = match operation.security.is_empty() {
+        true => &spec.security,
+        false => &operation.security,
+    };
+
+    let mut parsed = Vec::with_capacity(requirements.len());
+    for requirement in requirements {
+        let unknown = requirement
+            .0
+            .keys()
+            .find(|name| !schemes.iter().any(|s| &s.name == *name));
+        match (unknown, config.targets.server) {
+            (None, _) => parsed.push(models::SecurityRequirementAsRust {
+                schemes: requirement
+                    .0
+                    .iter()
+                    .map(|(name, scopes)| (name.clone(), scopes.clone()))
+                    .collect(),
+            }),
+            (Some(name), true) => {
+                return Err(UnsupportedSecurityScheme(
+                    name.clone(),
+                    operation_name.to_string(),
+                ));
+            }
+            (Some(_), false) => {}
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_schemes_to_rust() {
+        let yaml = r#"
+openapi: 3.1.0
+info:
+  title: Security
+  version: 0.0.0
+security:
+  - api_key: []
+paths:
+  /pets:
+    get:
+      responses: {}
+    post:
+      security:
+        - oauth: [write]
+          basic: []
+        - tls: []
+      responses: {}
+components:
+  securitySchemes:
+    api_key:
+      type: apiKey
+      name: X-Api-Key
+      in: header
+    basic:
+      type: http
+      scheme: Basic
+    oauth:
+      type: oauth2
+      flows:
+        clientCredentials:
+          tokenUrl: https://example.com/token
+          scopes:
+            write: modify the pets
+    tls:
+      type: mutualTLS
+"#;
+        let spec = oas3::from_yaml(yaml).unwrap();
+        let schemes = security_schemes_to_rust(&spec).unwrap();
+        let path = &spec.paths.as_ref().unwrap()["/pets"];
+
+        let config = models::Config::default();
+        let get = path.get.as_ref().unwrap();
+        let get_requirements =
+            security_requirements_to_rust(&config, &spec, &schemes, "Get", get).unwrap();
+        let post = path.post.as_ref().unwrap();
+        let post_requirements =
+            security_requirements_to_rust(&config, &spec, &schemes, "Post", post).unwrap();
+
+        insta::assert_debug_snapshot!((&schemes, get_requirements, post_requirements));
+
+        let mut config = models::Config::default();
+        config.targets.server = true;
+        let got = security_requirements_to_rust(&config, &spec, &schemes, "Post", post);
+        assert!(matches!(got, Err(UnsupportedSecurityScheme(..))));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::models;
+use convert_case::{Case, Casing};
+
+/// Converts the servers of the spec to rust, naming each one after its description.
+/// The servers without a description, or whose description is not a valid name,
+/// are named after their position, like `Server2`.
+pub(crate) fn servers_to_rust(spec: &oas3::Spec) -> Vec<models::ServerAsRust> {
+    let mut servers: Vec<models::ServerAsRust> = Vec::with_capacity(spec.servers.len());
+    for (i, server) in spec.servers.iter().enumerate() {
+        // "Production server" is named `Production`, since its variables are in `ProductionServer`
+        let name = server
+            .description
+            .as_deref()
+            .and_then(variant_name)
+            .map(|name| match name.strip_suffix("Server") {
+                Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+                _ => name,
+            })
+            .filter(|name| !servers.iter().any(|s| &s.name == name))
+            .unwrap_or_else(|| format!("Server{}", i + 1));
+
+        let variables = server
+            .variables
+            .iter()
+            .map(|(variable_name, variable)| {
+                let mut values: Vec<(String, String)> = Vec::new();
+                for (j, value) in variable.substitutions_enum.iter().enumerate() {
+                    let variant = variant_name(value)
+                        .filter(|variant| !values.iter().any(|(v, _)| v == variant))
+                        .unwrap_or_else(|| format!("Value{}", j + 1));
+                    values.push((variant, value.clone()));
+                }
+                models::ServerVariableAsRust {
+                    name: variable_name.clone(),
+                    comment: variable.description.clone(),
+                    default: variable.default.clone(),
+                    values,
+                }
+            })
+            .collect();
+
+        servers.push(models::ServerAsRust {
+            name,
+            comment: server.description.clone(),
+            url: server.url.clone(),
+            variables,
+        });
+    }
+
+    servers
+}
+
+/// Returns the name of a variant in upper camel case, if the text can be turned into one.
+fn variant_name(text: &str) -> Option<String> {
+    let words: String = text
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect();
+    let name = words.to_case(Case::UpperCamel);
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_servers_to_rust() {
+        let yaml = r#"
+openapi: 3.1.0
+info:
+  title: Servers
+  version: 0.0.0
+servers:
+  - url: https://{region}.api.example.com/{version}
+    description: Production server
+    variables:
+      region:
+        default: eu-west
+        enum: [eu-west, us-east, "1"]
+        description: The region of the data center
+      version:
+        default: v1
+  - url: http://localhost:8080
+  - url: https://staging.example.com
+    description: Production server
+paths: {}
+"#;
+        let spec = oas3::from_yaml(yaml).unwrap();
+
+        insta::assert_debug_snapshot!(servers_to_rust(&spec));
+    }
+}
@@ -1,38 +1,41 @@
 use crate::models;
-use crate::models::schema;
 use crate::parsing::errors::ParsingError;
-use crate::parsing::routes::parse_routes;
-use oas3::spec;
+use crate::parsing::routes::{parse_routes, valid_identifier};
+use crate::parsing::security::security_schemes_to_rust;
+use crate::parsing::servers::servers_to_rust;
+use crate::parsing::typeset::{component_type_name, schema_to_rust};
 
 pub fn parse_specs(
     config: &models::Config,
     spec: oas3::Spec,
-) -> Result<Vec<schema::SchemaAsRust>, ParsingError> {
-    let mut parsed_schemas = Vec::new();
+) -> Result<models::SpecAsRust, ParsingError> {
+    let security_schemes = security_schemes_to_rust(&spec)?;
+    let mut parsed_spec = models::SpecAsRust {
+        operations: parse_routes(config, &spec, &security_schemes)?,
+        security_schemes,
+        servers: servers_to_rust(&spec),
+        ..Default::default()
+    };
 
-    let components = spec.clone().components;
-    if let Some(components) = components {
-        let parsed_schemas_from_routes = parse_routes(config, &spec)?;
-        parsed_schemas.extend(parsed_schemas_from_routes);
-
-        for (schema_name, schema) in components.schemas {
+    if let Some(components) = &spec.components {
+        for (schema_name, schema) in &components.schemas {
+            if !config.filters.schemas.allows(schema_name) {
+                continue;
+            }
             let resolved_schema = schema.resolve(&spec)?;
-
-            let _ = resolved_schema.title.is_none();
-
-            match resolved_schema.schema_type {
-                Some(t) => match t {
-                    spec::SchemaTypeSet::Single(i) => todo!(),
-                    spec::SchemaTypeSet::Multiple(_) => todo!(),
-                },
-                None => todo!(),
+            let inputs = models::SchemaInputs {
+                schema_name: &valid_identifier(
+                    component_type_name(schema_name),
+                    &format!("the schema {schema_name}"),
+                )?,
             };
+            parsed_spec
+                .schemas
+                .push(schema_to_rust(config, &inputs, resolved_schema)?);
         }
-
-        return Ok(parsed_schemas);
     }
 
-    Ok(Vec::new())
+    Ok(parsed_spec)
 }
 
 #[cfg(test)]
@@ -44,6 +47,10 @@ mod tests {
 
     #[rstest]
     #[case("one route int", "fixtures/one_route_int.yaml")]
+    #[case("one route status ranges", "fixtures/one_route_status_ranges.yaml")]
+    #[case("one route media types", "fixtures/one_route_media_types.yaml")]
+    #[case("one route form", "fixtures/one_route_form.yaml")]
+    #[case("one route raw payloads", "fixtures/one_route_raw_payloads.yaml")]
     fn test_parse_structs(#[case] name: &str, #[case] path: &str) {
         let mut settings = insta::Settings::clone_current();
         settings.set_snapshot_suffix(name);
@@ -52,14 +59,27 @@ mod tests {
             output_path: Default::default(),
             libraries: Libraries {
                 datetime: DateTimeLibraries::Chrono,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let yaml = std::fs::read_to_string(path).unwrap();
         let spec = oas3::from_yaml(yaml).unwrap();
 
         let got = parse_specs(&config, spec).unwrap();
-        let got_as_strings = got.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let got_as_strings = got
+            .operations
+            .iter()
+            .flat_map(|o| {
+                let request_body = o.request_body.as_ref().map(|r| &r.body);
+                request_body
+                    .into_iter()
+                    .chain(o.responses.iter().flat_map(|r| &r.body))
+            })
+            .flat_map(|b| &b.content)
+            .map(|c| c.schema.to_string())
+            .collect::<Vec<_>>();
         settings.bind(|| {
             insta::assert_snapshot!(got_as_strings.join("\n"));
         });
@@ -1,9 +1,15 @@
+use crate::models::field_name;
 use crate::parsing::errors::ParsingError;
-use crate::parsing::errors::ParsingError::CannotGenerateUnionType;
+use crate::parsing::errors::ParsingError::{
+    CannotGenerateUnionType, InvalidPattern, UnsupportedSchema,
+};
 use crate::parsing::macros::get_macros;
-use crate::{format, models, Imports};
-use oas3::spec::{ObjectSchema, SchemaType, SchemaTypeSet};
-use std::collections::HashSet;
+use crate::parsing::routes::{type_name, valid_identifier};
+use crate::{Imports, format, models};
+use oas3::spec::{ObjectOrReference, ObjectSchema, Schema, SchemaType, SchemaTypeSet};
+use std::collections::BTreeSet;
+
+const COMPONENTS_SCHEMAS: &str = "#/components/schemas/";
 
 enum BaseType {
     Boolean,
@@ -29,7 +35,10 @@ pub(crate) fn schema_to_rust(
                 convert_multiple_types(config, inputs, &schema, multiple_types)
             }
         },
-        None => todo!(),
+        None => Err(UnsupportedSchema(format!(
+            "the schema {} has no type",
+            inputs.schema_name
+        ))),
     }
 }
 
@@ -60,8 +69,212 @@ fn convert_single_type(
         SchemaType::Integer => convert_base_schema_type(config, inputs, BaseType::Integer, schema),
         SchemaType::Number => convert_base_schema_type(config, inputs, BaseType::Number, schema),
         SchemaType::String => convert_base_schema_type(config, inputs, BaseType::String, schema),
-        SchemaType::Array => todo!(),
-        SchemaType::Object => todo!(),
+        SchemaType::Array => convert_array_type(config, inputs, schema),
+        SchemaType::Object => convert_object_type(config, inputs, schema),
+    }
+}
+
+fn convert_object_type(
+    config: &models::Config,
+    inputs: &models::SchemaInputs,
+    schema: &ObjectSchema,
+) -> Result<models::SchemaAsRust, ParsingError> {
+    let (tokenized_macros, mut imports) = get_macros(config);
+    if schema.properties.is_empty() {
+        if let Some((rust_type, value_imports)) = map_to_rust_type(config, schema)? {
+            imports.extend(value_imports);
+            return Ok(models::SchemaAsRust {
+                name: inputs.schema_name.clone(),
+                rust_type,
+                macros: tokenized_macros,
+                imports,
+                comment: schema.description.clone(),
+                is_optional: false,
+                fields: vec![],
+                constraints: models::ConstraintsAsRust::default(),
+                current_type: models::CurrentType::Type,
+            });
+        }
+    }
+
+    let mut fields = Vec::with_capacity(schema.properties.len());
+    for (property_name, property) in &schema.properties {
+        let property = match property {
+            ObjectOrReference::Object(property) => property,
+            ObjectOrReference::Ref { ref_path, .. } => {
+                fields.push(models::FieldAsRust {
+                    name: property_field_name(property_name, inputs.schema_name)?,
+                    original_name: property_name.clone(),
+                    rust_type: reference_to_rust_type(ref_path)?,
+                    comment: None,
+                    is_optional: !schema.required.contains(property_name),
+                    is_skipped: false,
+                    constraints: models::ConstraintsAsRust::default(),
+                });
+                continue;
+            }
+        };
+
+        let (rust_type, property_imports) = match items_reference(property) {
+            Some(ref_path) => (
+                format!("Vec<{}>", reference_to_rust_type(ref_path)?),
+                models::Imports::new(),
+            ),
+            None => property_to_rust_type(config, property)?,
+        };
+        imports.extend(property_imports);
+        let is_nullable = property
+            .schema_type
+            .as_ref()
+            .is_some_and(|t| t.contains(SchemaType::Null));
+
+        fields.push(models::FieldAsRust {
+            name: property_field_name(property_name, inputs.schema_name)?,
+            original_name: property_name.clone(),
+            rust_type,
+            comment: property.description.clone(),
+            is_optional: is_nullable || !schema.required.contains(property_name),
+            is_skipped: false,
+            constraints: schema_constraints(
+                property,
+                &format!("the property {} of {}", property_name, inputs.schema_name),
+            )?,
+        });
+    }
+
+    Ok(models::SchemaAsRust {
+        name: inputs.schema_name.clone(),
+        rust_type: String::new(),
+        macros: tokenized_macros,
+        imports,
+        comment: schema.description.clone(),
+        is_optional: false,
+        fields,
+        constraints: models::ConstraintsAsRust::default(),
+        current_type: models::CurrentType::Struct,
+    })
+}
+
+/// Converts an array schema to a type that wraps a vector of its items, that are either simple values
+/// or references to the schemas of the components, like `Vec<Pet>`.
+fn convert_array_type(
+    config: &models::Config,
+    inputs: &models::SchemaInputs,
+    schema: &ObjectSchema,
+) -> Result<models::SchemaAsRust, ParsingError> {
+    let (tokenized_macros, mut imports) = get_macros(config);
+    let rust_type = match items_reference(schema) {
+        Some(ref_path) => format!("Vec<{}>", reference_to_rust_type(ref_path)?),
+        None => {
+            let (rust_type, items_imports) = property_to_rust_type(config, schema)?;
+            imports.extend(items_imports);
+            rust_type
+        }
+    };
+
+    Ok(models::SchemaAsRust {
+        name: inputs.schema_name.clone(),
+        rust_type,
+        macros: tokenized_macros,
+        imports,
+        comment: schema.description.clone(),
+        is_optional: false,
+        fields: vec![],
+        constraints: schema_constraints(schema, inputs.schema_name)?,
+        current_type: models::CurrentType::Type,
+    })
+}
+
+/// Returns the type of an object without properties whose `additionalProperties` describe its values,
+/// like `BTreeMap<String, i32>`, or None if the object does not describe them.
+/// Objects that allow any additional property map to JSON values.
+fn map_to_rust_type(
+    config: &models::Config,
+    schema: &ObjectSchema,
+) -> Result<Option<(String, models::Imports)>, ParsingError> {
+    let (value_type, imports) = match &schema.additional_properties {
+        Some(Schema::Object(values)) => match values.as_ref() {
+            ObjectOrReference::Object(values) => property_to_rust_type(config, values)?,
+            ObjectOrReference::Ref { ref_path, .. } => {
+                (reference_to_rust_type(ref_path)?, models::Imports::new())
+            }
+        },
+        Some(Schema::Boolean(allowed)) if allowed.0 => {
+            ("serde_json::Value".to_string(), models::Imports::new())
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((
+        format!("std::collections::BTreeMap<String, {value_type}>"),
+        imports,
+    )))
+}
+
+/// Returns the path of the reference of the items of an array schema, like `#/components/schemas/Pet`.
+fn items_reference(schema: &ObjectSchema) -> Option<&str> {
+    match schema.items.as_deref() {
+        Some(Schema::Object(items)) => match items.as_ref() {
+            ObjectOrReference::Ref { ref_path, .. } => Some(ref_path),
+            ObjectOrReference::Object(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the name of the type of the schema of the components that a reference points to,
+/// like `Pet` for `#/components/schemas/Pet`, see [component_type_name].
+pub(crate) fn reference_to_rust_type(ref_path: &str) -> Result<String, ParsingError> {
+    match ref_path.strip_prefix(COMPONENTS_SCHEMAS) {
+        Some(schema_name) if !schema_name.contains('/') => valid_identifier(
+            component_type_name(schema_name),
+            &format!("the schema {schema_name}"),
+        ),
+        _ => Err(UnsupportedSchema(format!(
+            "only the references to the schemas of the components are supported, but got {ref_path}"
+        ))),
+    }
+}
+
+/// Returns the name of the field of a property of the schema `owner`, see [field_name],
+/// or an error if no identifier can be made of the property name, like for `@`.
+fn property_field_name(property_name: &str, owner: &str) -> Result<String, ParsingError> {
+    valid_identifier(
+        field_name(property_name),
+        &format!("the property {property_name} of {owner}"),
+    )
+}
+
+/// Returns the name of the type generated for a schema of the components, in UpperCamel case.
+pub(crate) fn component_type_name(schema_name: &str) -> String {
+    type_name(schema_name)
+}
+
+/// Returns the rust type of a property of an object, or of a parameter, that can be a simple value or an array of simple values.
+pub(crate) fn property_to_rust_type(
+    config: &models::Config,
+    property: &ObjectSchema,
+) -> Result<(String, models::Imports), ParsingError> {
+    let is_array = property
+        .schema_type
+        .as_ref()
+        .is_some_and(|t| t.contains(SchemaType::Array));
+    if !is_array {
+        return schema_to_rust_type(config, property);
+    }
+
+    match property.items.as_deref() {
+        Some(Schema::Object(items)) => match items.as_ref() {
+            ObjectOrReference::Object(items) => {
+                let (rust_type, imports) = schema_to_rust_type(config, items)?;
+                Ok((format!("Vec<{rust_type}>"), imports))
+            }
+            ObjectOrReference::Ref { ref_path, .. } => Err(UnsupportedSchema(format!(
+                "arrays of references like {ref_path} are not supported yet"
+            ))),
+        },
+        _ => Err(UnsupportedSchema(
+            "arrays without a schema for their items are not supported yet".to_string(),
+        )),
     }
 }
 
@@ -76,7 +289,50 @@ fn convert_base_schema_type(
         _ => false,
     };
 
-    let (rust_type, mut imports) = match schema_type {
+    let (rust_type, mut imports) = base_type_to_rust(config, schema_type, schema);
+
+    let (tokenized_macros, imports_macros) = get_macros(config);
+
+    imports.extend(imports_macros);
+
+    Ok(models::SchemaAsRust {
+        name: inputs.schema_name.clone(),
+        rust_type,
+        macros: tokenized_macros,
+        imports,
+        comment: schema.description.clone(),
+        is_optional,
+        fields: vec![],
+        constraints: schema_constraints(schema, inputs.schema_name)?,
+        current_type: models::CurrentType::Type,
+    })
+}
+
+/// Returns the constraints declared by a schema, failing if one of its patterns cannot be compiled
+/// by the generated validation. `owner` names the schema in the error, like `the property name of Pet`.
+pub(crate) fn schema_constraints(
+    schema: &ObjectSchema,
+    owner: &str,
+) -> Result<models::ConstraintsAsRust, ParsingError> {
+    let constraints = models::ConstraintsAsRust::from_schema(schema);
+    match constraints.invalid_pattern() {
+        Some(reason) => Err(InvalidPattern(owner.to_string(), reason)),
+        None => Ok(constraints),
+    }
+}
+
+fn base_type_to_rust(
+    config: &models::Config,
+    schema_type: BaseType,
+    schema: &ObjectSchema,
+) -> (String, models::Imports) {
+    // the types of the config replace the built-in ones of their format
+    let mapped_type = schema.format.as_ref().and_then(|f| config.types.get(f));
+    if let Some(rust_type) = mapped_type {
+        return (rust_type.clone(), models::Imports::new());
+    }
+
+    match schema_type {
         BaseType::Integer => match &schema.format {
             Some(format) => format::format_number(format),
             None => format::format_number(format::DEFAULT_INTEGER),
@@ -91,21 +347,42 @@ fn convert_base_schema_type(
         },
         BaseType::Boolean => format::format_boolean(),
         BaseType::Null => format::format_null(),
+    }
+}
+
+/// Returns the rust type that represents a schema of a simple value, without defining a new type for it.
+/// It is used where the spec only allows simple values, like the headers of a response.
+/// A schema without a type is represented as a string.
+pub(crate) fn schema_to_rust_type(
+    config: &models::Config,
+    schema: &ObjectSchema,
+) -> Result<(String, models::Imports), ParsingError> {
+    let schema_types = match &schema.schema_type {
+        Some(SchemaTypeSet::Single(single_type)) => vec![*single_type],
+        Some(SchemaTypeSet::Multiple(multiple_types)) => multiple_types.clone(),
+        None => vec![SchemaType::String],
     };
 
-    let (tokenized_macros, imports_macros) = get_macros();
+    // a value that can be null is represented by the type of the value when it is present
+    let non_null_types: Vec<_> = schema_types
+        .into_iter()
+        .filter(|t| *t != SchemaType::Null)
+        .collect();
 
-    imports.extend(imports_macros);
+    let base_type = match non_null_types.as_slice() {
+        [SchemaType::Boolean] => BaseType::Boolean,
+        [SchemaType::Integer] => BaseType::Integer,
+        [SchemaType::Number] => BaseType::Number,
+        [SchemaType::String] => BaseType::String,
+        other => {
+            return Err(UnsupportedSchema(format!(
+                "expected a simple value but got the types {:?}",
+                other
+            )));
+        }
+    };
 
-    Ok(models::SchemaAsRust {
-        name: inputs.schema_name.clone(),
-        rust_type,
-        macros: tokenized_macros,
-        imports,
-        comment: schema.description.clone(),
-        is_optional,
-        current_type: models::CurrentType::Type,
-    })
+    Ok(base_type_to_rust(config, base_type, schema))
 }
 
 fn union_type(types: &Vec<models::SchemaAsRust>) -> Result<models::SchemaAsRust, ParsingError> {
@@ -115,10 +392,12 @@ fn union_type(types: &Vec<models::SchemaAsRust>) -> Result<models::SchemaAsRust,
 
     let mut name = String::new();
     let mut rust_types: Vec<String> = Vec::new();
-    let mut macros: HashSet<String> = HashSet::new();
+    let mut macros: BTreeSet<String> = BTreeSet::new();
     let mut imports = Imports::new();
     let mut is_optional = false;
     let mut comment: Option<String> = None;
+    // the types come from the same schema, so they share its constraints
+    let constraints = types[0].constraints.clone();
 
     for t in types {
         if name != "" && name != t.name {
@@ -154,6 +433,8 @@ fn union_type(types: &Vec<models::SchemaAsRust>) -> Result<models::SchemaAsRust,
         imports,
         comment,
         is_optional,
+        fields: vec![],
+        constraints,
         current_type: models::CurrentType::Type,
     })
 }
@@ -163,6 +444,8 @@ mod tests {
     use super::*;
 
     use crate::models::{DateTimeLibraries, Libraries};
+    use crate::parsing::errors::ParsingError::InvalidIdentifier;
+    use quote::ToTokens;
     use rstest::rstest;
 
     #[rstest]
@@ -170,6 +453,26 @@ mod tests {
     #[case("number", "Height", "type: number")]
     #[case("string", "Name", "type: string")]
     #[case("boolean", "True", "type: boolean")]
+    #[case(
+        "object",
+        "Widget",
+        "{type: object, required: [id], properties: {id: {type: integer}, type: {type: string}, tagNames: {type: array, items: {type: string}}}}"
+    )]
+    #[case(
+        "references",
+        "Pet",
+        "{type: object, required: [owner], properties: {owner: {$ref: '#/components/schemas/pet_owner'}, tags: {type: array, items: {$ref: '#/components/schemas/Tag'}}}}"
+    )]
+    #[case(
+        "array",
+        "Tags",
+        "{type: array, items: {$ref: '#/components/schemas/Tag'}}"
+    )]
+    #[case(
+        "map",
+        "Inventory",
+        "{type: object, additionalProperties: {type: integer, format: int32}}"
+    )]
     fn test_parse_base_cases(
         #[case] name: &str,
         #[case] schema_name: &str,
@@ -179,7 +482,9 @@ mod tests {
             output_path: Default::default(),
             libraries: Libraries {
                 datetime: DateTimeLibraries::Chrono,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let mut insta_settings = insta::Settings::clone_current();
@@ -197,6 +502,72 @@ mod tests {
         });
     }
 
+    #[rstest]
+    #[case("#/components/schemas/Pet/properties/name")]
+    #[case("#/components/responses/Pet")]
+    #[case("pets.yaml#/Pet")]
+    fn test_unsupported_reference(#[case] ref_path: &str) {
+        assert!(matches!(
+            reference_to_rust_type(ref_path),
+            Err(UnsupportedSchema(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("{type: string, pattern: '^(?!admin)'}", "Name")]
+    #[case(
+        "{type: object, properties: {name: {type: string, pattern: '^(?<=a)b'}}}",
+        "the property name of Name"
+    )]
+    #[case("{type: array, items: {type: string, pattern: '[a-'}}", "Name")]
+    fn test_invalid_pattern(#[case] schema_spec: &str, #[case] owner: &str) {
+        let schema = serde_yaml::from_str::<ObjectSchema>(schema_spec).unwrap();
+        let inputs = models::SchemaInputs {
+            schema_name: &"Name".to_string(),
+        };
+
+        let got = schema_to_rust(&models::Config::default(), &inputs, schema);
+
+        assert!(
+            matches!(&got, Err(InvalidPattern(o, _)) if o == owner),
+            "{got:?}"
+        );
+    }
+
+    #[test]
+    fn test_property_identifiers() {
+        let schema = serde_yaml::from_str::<ObjectSchema>(
+            "{type: object, properties: {'@type': {type: string}, 1st: {type: integer}}}",
+        )
+        .unwrap();
+        let inputs = models::SchemaInputs {
+            schema_name: &"Name".to_string(),
+        };
+
+        let got = schema_to_rust(&models::Config::default(), &inputs, schema).unwrap();
+
+        let b = syn::parse_file(&got.to_token_stream().to_string()).unwrap();
+        insta::assert_snapshot!(prettyplease::unparse(&b));
+    }
+
+    #[test]
+    fn test_invalid_identifier() {
+        let schema = serde_yaml::from_str::<ObjectSchema>(
+            "{type: object, properties: {'@': {type: string}}}",
+        )
+        .unwrap();
+        let inputs = models::SchemaInputs {
+            schema_name: &"Name".to_string(),
+        };
+
+        let got = schema_to_rust(&models::Config::default(), &inputs, schema);
+
+        assert!(
+            matches!(&got, Err(InvalidIdentifier(o)) if o == "the property @ of Name"),
+            "{got:?}"
+        );
+    }
+
     /*
 
     TODO: fix constant test
@@ -0,0 +1,41 @@
+#[test]
+fn builder_test() {
+    let out = std::env::temp_dir().join(format!("oapigen_builder_{}.rs", std::process::id()));
+    let config = oapigen::Config {
+        output_path: out.clone(),
+        targets: oapigen::Targets {
+            client: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let got = oapigen::Builder::new()
+        .spec("fixtures/client.yaml")
+        .config(config)
+        .generate()
+        .unwrap();
+
+    let content = std::fs::read_to_string(&got).unwrap();
+    std::fs::remove_file(&got).unwrap();
+    assert_eq!(got, out);
+    assert!(content.contains("pub struct Client"));
+}
+
+#[test]
+fn builder_without_spec_test() {
+    let got = oapigen::Builder::new().generate().unwrap_err();
+
+    assert!(matches!(got, oapigen::GeneratingError::MissingSpecError));
+}
+
+#[test]
+fn builder_outside_build_script_test() {
+    let got = oapigen::Builder::new()
+        .spec("fixtures/client.yaml")
+        .out_dir_env()
+        .generate()
+        .unwrap_err();
+
+    assert!(matches!(got, oapigen::GeneratingError::MissingOutDirError));
+}
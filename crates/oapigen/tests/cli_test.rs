@@ -0,0 +1,201 @@
+use rstest::rstest;
+use std::process::Command;
+
+fn oapigen() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_oapigen"))
+}
+
+#[test]
+fn generate_test() {
+    let out = std::env::temp_dir().join(format!("oapigen_cli_{}.rs", std::process::id()));
+
+    let status = oapigen()
+        .args([
+            "generate",
+            "--client",
+            "--spec",
+            "fixtures/client.yaml",
+            "--out",
+        ])
+        .arg(&out)
+        .status()
+        .unwrap();
+
+    let got = std::fs::read_to_string(&out).unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert!(status.success());
+    assert!(got.contains("pub struct Client"));
+}
+
+#[test]
+fn generate_with_config_test() {
+    let out = std::env::temp_dir().join(format!("oapigen_cli_{}_config.rs", std::process::id()));
+
+    let status = oapigen()
+        .args(["generate", "--spec", "fixtures/pagination.yaml"])
+        .args(["--config", "src/config/fixtures/oapigen.toml", "--out"])
+        .arg(&out)
+        .env("OAPIGEN_TARGETS__SERVER", "true")
+        .status()
+        .unwrap();
+
+    let got = std::fs::read_to_string(&out).unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert!(status.success());
+    assert!(got.contains("pub fn list_pets_all"));
+    assert!(got.contains("pub mod blocking"));
+    assert!(got.contains("pub mod server"));
+}
+
+#[rstest]
+#[case("missing spec", "fixtures/missing.yaml", 74)]
+#[case("invalid spec", "Cargo.toml", 65)]
+#[case("unsupported schema", "fixtures/train.yaml", 65)]
+#[case(
+    "missing config",
+    "fixtures/client.yaml --config src/config/fixtures/missing.toml",
+    74
+)]
+#[case(
+    "invalid config",
+    "fixtures/client.yaml --config src/config/fixtures/unknown_option.toml",
+    65
+)]
+fn generate_error_test(#[case] name: &str, #[case] spec: &str, #[case] code: i32) {
+    let out = std::env::temp_dir().join(format!("oapigen_cli_{}_error.rs", std::process::id()));
+
+    let output = oapigen()
+        .args(["generate", "--spec"])
+        .args(spec.split(' '))
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(code), "{name}");
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: "));
+    assert!(!out.exists());
+}
+
+#[test]
+fn generate_without_output_test() {
+    let output = oapigen()
+        .args(["generate", "--spec", "fixtures/client.yaml"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--out"));
+}
+
+#[rstest]
+#[case("up to date", None, 0)]
+#[case("imports in another order", Some("reorder"), 1)]
+#[case("changed file", Some("change"), 1)]
+#[case("missing file", Some("remove"), 1)]
+fn generate_check_test(#[case] name: &str, #[case] edit: Option<&str>, #[case] code: i32) {
+    let out = std::env::temp_dir().join(format!(
+        "oapigen_cli_{}_check_{}.rs",
+        std::process::id(),
+        name.replace(' ', "_")
+    ));
+    let generate = |check: bool| {
+        let mut command = oapigen();
+        command
+            .args([
+                "generate",
+                "--client",
+                "--spec",
+                "fixtures/one_route_date.yaml",
+                "--out",
+            ])
+            .arg(&out);
+        if check {
+            command.arg("--check");
+        }
+        command.output().unwrap()
+    };
+
+    assert!(generate(false).status.success());
+    let generated = std::fs::read_to_string(&out).unwrap();
+    match edit {
+        Some("reorder") => {
+            let mut lines: Vec<_> = generated.lines().collect();
+            let imports = lines.iter().take_while(|l| l.starts_with("use ")).count();
+            lines[..imports].reverse();
+            std::fs::write(&out, lines.join("\n")).unwrap();
+        }
+        Some("change") => std::fs::write(
+            &out,
+            generated.replace("pub struct Client", "pub struct Other"),
+        )
+        .unwrap(),
+        Some("remove") => std::fs::remove_file(&out).unwrap(),
+        _ => {}
+    }
+    let output = generate(true);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let _ = std::fs::remove_file(&out);
+
+    assert_eq!(output.status.code(), Some(code), "{name}");
+    assert_eq!(stdout.contains("@@"), code != 0, "{name}: {stdout}");
+    if edit == Some("change") {
+        assert!(stdout.contains("-pub struct Other"), "{name}: {stdout}");
+        assert!(stdout.contains("+pub struct Client"), "{name}: {stdout}");
+    }
+}
+
+#[test]
+fn generate_crate_test() {
+    let out = std::env::temp_dir().join(format!("oapigen_cli_{}_crate", std::process::id()));
+
+    let status = oapigen()
+        .args([
+            "generate",
+            "--crate",
+            "--client",
+            "--spec",
+            "fixtures/client.yaml",
+        ])
+        .arg("--out")
+        .arg(&out)
+        .status()
+        .unwrap();
+
+    let manifest = std::fs::read_to_string(out.join("Cargo.toml")).unwrap();
+    let lib = std::fs::read_to_string(out.join("src").join("lib.rs")).unwrap();
+    std::fs::remove_dir_all(&out).unwrap();
+    assert!(status.success());
+    assert!(manifest.contains("[dependencies]"));
+    assert!(manifest.contains("serde = "));
+    assert!(lib.contains("mod client;"));
+}
+
+#[rstest]
+#[case("clean spec", "fixtures/client.yaml", "human", 0)]
+#[case("warnings only", "fixtures/one_route_media_types.yaml", "human", 0)]
+#[case("errors", "fixtures/lint.yaml", "human", 1)]
+#[case("errors as json", "fixtures/lint.yaml", "json", 1)]
+#[case("missing spec", "fixtures/missing.yaml", "human", 74)]
+fn lint_test(#[case] name: &str, #[case] spec: &str, #[case] format: &str, #[case] code: i32) {
+    let output = oapigen()
+        .args(["lint", spec, "--format", format])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(code), "{name}");
+    if format == "json" {
+        let issues: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(
+            issues[0]["pointer"], "/components/schemas/Owner/properties/friend",
+            "{name}"
+        );
+        assert_eq!(issues[0]["severity"], "error", "{name}");
+    } else if code == 1 {
+        assert!(
+            stdout.contains("error: /paths/~1pets/post: "),
+            "{name}: {stdout}"
+        );
+    }
+}
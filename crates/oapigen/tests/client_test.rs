@@ -0,0 +1,41 @@
+#[test]
+fn client_test() {
+    let config = oapigen::Config {
+        libraries: oapigen::Libraries {
+            transports: vec![oapigen::TransportLibraries::Reqwest],
+            ..Default::default()
+        },
+        targets: oapigen::Targets {
+            client: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let f = std::fs::File::open("fixtures/client.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
+
+#[test]
+fn blocking_client_test() {
+    let config = oapigen::Config {
+        libraries: oapigen::Libraries {
+            transports: vec![oapigen::TransportLibraries::Ureq],
+            ..Default::default()
+        },
+        targets: oapigen::Targets {
+            blocking_client: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let f = std::fs::File::open("fixtures/client.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+#[test]
+fn config_test() {
+    let config = oapigen::Config {
+        targets: oapigen::Targets {
+            client: true,
+            ..Default::default()
+        },
+        types: HashMap::from([("int64".to_string(), "u64".to_string())]),
+        derives: vec!["Clone".to_string(), "Debug".to_string()],
+        filters: oapigen::Filters {
+            operations: oapigen::Filter {
+                include: vec!["getPetById".to_string(), "getInventory".to_string()],
+                ..Default::default()
+            },
+            schemas: oapigen::Filter {
+                exclude: vec!["Order".to_string(), "User".to_string()],
+                ..Default::default()
+            },
+        },
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/petstore.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
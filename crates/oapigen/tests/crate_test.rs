@@ -0,0 +1,50 @@
+#[test]
+fn crate_test() {
+    let config = oapigen::Config {
+        targets: oapigen::Targets {
+            client: true,
+            server: true,
+            ..Default::default()
+        },
+        layout: oapigen::Layout {
+            standalone_crate: true,
+            file_per_tag: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/modules.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_crate(&config, spec).unwrap();
+    let paths: Vec<_> = got
+        .iter()
+        .map(|file| file.path.display().to_string())
+        .collect();
+
+    insta::assert_snapshot!(paths.join("\n"));
+    insta::assert_snapshot!(got[0].content);
+}
+
+#[test]
+fn crate_package_test() {
+    let config = oapigen::Config {
+        layout: oapigen::Layout {
+            standalone_crate: true,
+            ..Default::default()
+        },
+        package: oapigen::Package {
+            name: Some("pets-api".to_string()),
+            version: Some("2.1.0".to_string()),
+        },
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/one_route_date.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_crate(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got[0].content);
+}
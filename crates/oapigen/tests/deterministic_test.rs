@@ -0,0 +1,48 @@
+use rstest::rstest;
+
+/// Generates the spec with every target, as a single file and as a module tree.
+fn generate(path: &str) -> Vec<String> {
+    let targets = || oapigen::Targets {
+        client: true,
+        blocking_client: true,
+        server: true,
+    };
+    let config = oapigen::Config {
+        targets: targets(),
+        ..Default::default()
+    };
+    let modules_config = oapigen::Config {
+        targets: targets(),
+        layout: oapigen::Layout {
+            modules: true,
+            file_per_schema: true,
+            file_per_tag: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let read_spec = || oapigen::read_spec(std::path::Path::new(path)).unwrap();
+
+    let mut got = vec![oapigen::spec_to_rust(&config, read_spec()).unwrap()];
+    got.extend(
+        oapigen::spec_to_files(&modules_config, read_spec())
+            .unwrap()
+            .into_iter()
+            .map(|file| format!("// {}\n{}", file.path.display(), file.content)),
+    );
+    got
+}
+
+#[rstest]
+#[case("fixtures/petstore.yaml")]
+#[case("fixtures/one_route_date.yaml")]
+#[case("fixtures/modules.yaml")]
+#[case("fixtures/validation.yaml")]
+#[case("fixtures/pagination.yaml")]
+fn deterministic_test(#[case] path: &str) {
+    let expected = generate(path);
+
+    for _ in 0..20 {
+        assert_eq!(generate(path), expected, "{path}");
+    }
+}
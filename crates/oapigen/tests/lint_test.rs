@@ -0,0 +1,23 @@
+#[test]
+fn lint_test() {
+    let got = oapigen::lint_spec(std::path::Path::new("fixtures/lint.yaml")).unwrap();
+    let got = got
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    insta::assert_snapshot!(got);
+}
+
+#[test]
+fn lint_names_test() {
+    let got = oapigen::lint_spec(std::path::Path::new("fixtures/lint_names.yaml")).unwrap();
+    let got = got
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    insta::assert_snapshot!(got);
+}
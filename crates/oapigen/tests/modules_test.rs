@@ -0,0 +1,33 @@
+use rstest::rstest;
+
+#[rstest]
+#[case("modules", oapigen::Layout { modules: true, ..Default::default() })]
+#[case("file_per_schema_and_tag", oapigen::Layout { modules: true, file_per_schema: true, file_per_tag: true, ..Default::default() })]
+fn modules_test(#[case] name: &str, #[case] layout: oapigen::Layout) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_suffix(name);
+
+    let config = oapigen::Config {
+        targets: oapigen::Targets {
+            client: true,
+            server: true,
+            ..Default::default()
+        },
+        layout,
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/modules.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_files(&config, spec).unwrap();
+    let got = got
+        .iter()
+        .map(|file| format!("// {}\n{}", file.path.display(), file.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    settings.bind(|| {
+        insta::assert_snapshot!(got);
+    });
+}
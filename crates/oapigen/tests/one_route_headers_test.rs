@@ -0,0 +1,22 @@
+#[test]
+fn one_route_headers_test() {
+    let config = oapigen::Config::default();
+    let f = std::fs::File::open("fixtures/one_route_headers.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
+
+#[test]
+fn one_route_headers_server_test() {
+    let mut config = oapigen::Config::default();
+    config.targets.server = true;
+    let f = std::fs::File::open("fixtures/one_route_headers.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
@@ -0,0 +1,48 @@
+use rstest::rstest;
+
+#[rstest]
+#[case("client", oapigen::Targets { client: true, ..Default::default() })]
+#[case("blocking_client", oapigen::Targets { blocking_client: true, ..Default::default() })]
+fn pagination_test(#[case] name: &str, #[case] targets: oapigen::Targets) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_suffix(name);
+
+    let config = oapigen::Config {
+        targets,
+        pagination: [
+            (
+                "listPets".to_string(),
+                oapigen::Pagination::Cursor {
+                    parameter: "cursor".to_string(),
+                    next_cursor: "next_cursor".to_string(),
+                    items: "data".to_string(),
+                },
+            ),
+            (
+                "listOwners".to_string(),
+                oapigen::Pagination::Link {
+                    items: "owners".to_string(),
+                },
+            ),
+            (
+                "listVisits".to_string(),
+                oapigen::Pagination::Offset {
+                    offset: "offset".to_string(),
+                    limit: Some("limit".to_string()),
+                    items: "visits".to_string(),
+                },
+            ),
+        ]
+        .into(),
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/pagination.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    settings.bind(|| {
+        insta::assert_snapshot!(got.to_string());
+    });
+}
@@ -0,0 +1,24 @@
+use rstest::rstest;
+
+#[rstest]
+#[case("client", oapigen::Targets { client: true, ..Default::default() })]
+#[case("blocking_client", oapigen::Targets { blocking_client: true, ..Default::default() })]
+#[case("server", oapigen::Targets { server: true, ..Default::default() })]
+fn security_test(#[case] name: &str, #[case] targets: oapigen::Targets) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_suffix(name);
+
+    let config = oapigen::Config {
+        targets,
+        ..Default::default()
+    };
+
+    let f = std::fs::File::open("fixtures/security.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    settings.bind(|| {
+        insta::assert_snapshot!(got.to_string());
+    });
+}
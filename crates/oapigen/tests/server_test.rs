@@ -0,0 +1,22 @@
+use rstest::rstest;
+
+#[rstest]
+#[case("axum", oapigen::ServerLibraries::Axum)]
+#[case("actix_web", oapigen::ServerLibraries::ActixWeb)]
+fn server_test(#[case] name: &str, #[case] library: oapigen::ServerLibraries) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_suffix(name);
+
+    let mut config = oapigen::Config::default();
+    config.libraries.server = library;
+    config.targets.server = true;
+
+    let f = std::fs::File::open("fixtures/client.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    settings.bind(|| {
+        insta::assert_snapshot!(got.to_string());
+    });
+}
@@ -0,0 +1,12 @@
+#[test]
+fn validation_test() {
+    let mut config = oapigen::Config::default();
+    config.targets.server = true;
+
+    let f = std::fs::File::open("fixtures/validation.yaml").unwrap();
+    let spec: oas3::Spec = serde_yaml::from_reader(f).unwrap();
+
+    let got = oapigen::spec_to_rust(&config, spec).unwrap();
+
+    insta::assert_snapshot!(got.to_string());
+}
@@ -0,0 +1,151 @@
+#![doc = include_str!("../readme.md")]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::path::Path;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token, parse_macro_input};
+
+/// The arguments of [include_spec!], like `"api.yaml", config = "oapigen.toml"`.
+struct IncludeSpecArgs {
+    spec: LitStr,
+    config: Option<LitStr>,
+}
+
+impl Parse for IncludeSpecArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let spec = input.parse()?;
+        let mut config = None;
+        while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "config" if config.is_none() => config = Some(input.parse()?),
+                "config" => return Err(syn::Error::new_spanned(key, "`config` is set twice")),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "unknown argument, expected `config`",
+                    ));
+                }
+            }
+        }
+        if !input.is_empty() {
+            return Err(input.error("expected `,`"));
+        }
+
+        Ok(IncludeSpecArgs { spec, config })
+    }
+}
+
+/// Generates the rust code of an OpenAPI spec in the module where it is called.
+/// The spec and the optional config are relative to the directory of the `Cargo.toml` of the crate,
+/// like `include_spec!("api.yaml", config = "oapigen.toml")`.
+#[proc_macro]
+pub fn include_spec(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as IncludeSpecArgs);
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+
+    match expand(Path::new(&manifest_dir), &args) {
+        Ok(output) => TokenStream::from(output),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(manifest_dir: &Path, args: &IncludeSpecArgs) -> Result<TokenStream2, syn::Error> {
+    let spec_path = manifest_dir.join(args.spec.value());
+    let config_path = args.config.as_ref().map(|c| manifest_dir.join(c.value()));
+
+    // without a file the config can only fail because of the environment variables
+    let config = oapigen::load_config(config_path.as_deref())
+        .map_err(|e| compile_error(args.config.as_ref().unwrap_or(&args.spec), &e))?;
+    let spec = oapigen::read_spec(&spec_path).map_err(|e| compile_error(&args.spec, &e))?;
+    let code = oapigen::spec_to_rust(&config, spec).map_err(|e| compile_error(&args.spec, &e))?;
+    let code = code
+        .parse::<TokenStream2>()
+        .map_err(|e| compile_error(&args.spec, &e))?;
+
+    // the files are included so that the crate is compiled again when they change
+    let tracked_files = [Some(spec_path), config_path]
+        .into_iter()
+        .flatten()
+        .map(|path| path.display().to_string());
+
+    Ok(quote! {
+        #(const _: &[u8] = include_bytes!(#tracked_files);)*
+        #code
+    })
+}
+
+/// Returns a compile error at the literal with the message of the error followed by the ones of its sources.
+fn compile_error(literal: &LitStr, error: &dyn std::error::Error) -> syn::Error {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message = format!("{}: {}", message.trim_end_matches('.'), e);
+        source = e.source();
+    }
+    syn::Error::new(literal.span(), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IncludeSpecArgs, expand};
+    use rstest::rstest;
+    use std::path::Path;
+
+    #[rstest]
+    #[case(r#""api.yaml""#, "api.yaml", None)]
+    #[case(r#""api.yaml","#, "api.yaml", None)]
+    #[case(
+        r#""api.yaml", config = "oapigen.toml""#,
+        "api.yaml",
+        Some("oapigen.toml")
+    )]
+    fn test_parse_args(#[case] input: &str, #[case] spec: &str, #[case] config: Option<&str>) {
+        let got = syn::parse_str::<IncludeSpecArgs>(input).unwrap();
+
+        assert_eq!(got.spec.value(), spec);
+        assert_eq!(got.config.map(|c| c.value()).as_deref(), config);
+    }
+
+    #[rstest]
+    #[case(
+        r#""api.yaml", confg = "oapigen.toml""#,
+        "unknown argument, expected `config`"
+    )]
+    #[case(
+        r#""api.yaml", config = "a.toml", config = "b.toml""#,
+        "`config` is set twice"
+    )]
+    #[case(r#""api.yaml" "oapigen.toml""#, "expected `,`")]
+    fn test_parse_invalid_args(#[case] input: &str, #[case] expected: &str) {
+        let got = syn::parse_str::<IncludeSpecArgs>(input).err().unwrap();
+
+        assert_eq!(got.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case(
+        r#""missing.yaml""#,
+        "could not read the spec: No such file or directory (os error 2)"
+    )]
+    #[case(
+        r#""tests/fixtures/unsupported.yaml""#,
+        "Could not parse spec: the schema is not supported, reason the form body CreateThingRequestBody must be an object"
+    )]
+    #[case(
+        r#""../oapigen/fixtures/one_route_int.yaml", config = "missing.toml""#,
+        "could not read the config file"
+    )]
+    fn test_expand_errors(#[case] input: &str, #[case] expected: &str) {
+        let args = syn::parse_str::<IncludeSpecArgs>(input).unwrap();
+
+        let got = expand(Path::new(env!("CARGO_MANIFEST_DIR")), &args)
+            .err()
+            .unwrap();
+
+        assert!(got.to_string().starts_with(expected), "{got}");
+    }
+}
@@ -0,0 +1,21 @@
+mod api {
+    oapigen_macros::include_spec!(
+        "../oapigen/fixtures/one_route_int.yaml",
+        config = "tests/fixtures/oapigen.toml"
+    );
+}
+
+#[test]
+fn include_spec_test() {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    let got = api::GetAllIntegersResponse::from_parts(200, &headers, b"42").unwrap();
+
+    assert!(matches!(
+        got,
+        api::GetAllIntegersResponse::Status200(api::GetAllIntegersResponse200(42))
+    ));
+}